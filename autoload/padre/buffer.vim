@@ -78,8 +78,10 @@ function! padre#buffer#SetMainPadreKeyBindings(name)
   call s:LoadBuffer(a:name)
 
   nnoremap <silent> <buffer> r :PadreRun<cr>
+  nnoremap <silent> <buffer> R :PadreRestart<cr>
   nnoremap <silent> <buffer> S :PadreStepIn<cr>
   nnoremap <silent> <buffer> s :PadreStepOver<cr>
+  nnoremap <silent> <buffer> f :PadreStepOut<cr>
   vnoremap <silent> <buffer> p y:PadrePrintVariable <C-R>"<cr>
   nnoremap <silent> <buffer> C :PadreContinue<cr>
   nnoremap <silent> <buffer> ZZ :PadreStop<cr>
@@ -97,8 +99,10 @@ function! padre#buffer#UnsetPadreKeyBindings(name)
   call s:LoadBuffer(a:name)
 
   nnoremap <silent> <buffer> r r
+  nnoremap <silent> <buffer> R R
   nnoremap <silent> <buffer> S S
   nnoremap <silent> <buffer> s s
+  nnoremap <silent> <buffer> f f
   vnoremap <silent> <buffer> p p
   nnoremap <silent> <buffer> C C
   nnoremap <silent> <buffer> ZZ ZZ
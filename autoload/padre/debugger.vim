@@ -20,6 +20,7 @@ function! padre#debugger#Setup()
   " Create buffers for PADRE
   call padre#buffer#Create('PADRE_Main', 'PADRE_Main', 0)
   call padre#buffer#Create('PADRE_Stdio', 'PADRE_Data', 1)
+  call padre#buffer#Create('PADRE_Assembly', 'asm', 0)
   " call padre#buffer#Create('PADRE_Preprocessing', 'PADRE_Preprocessing', 1)
 
   call padre#buffer#SetMainPadreKeyBindings('PADRE_Main')
@@ -38,6 +39,9 @@ function! padre#debugger#Debug(...)
   let l:padre_port = 0
   let l:debugger = 'lldb'
   let l:debugger_type = 'lldb'
+  let l:attach = ''
+  let l:core = ''
+  let l:remote = ''
 
   let l:args = a:000
   let l:process_vim_args = 1
@@ -70,6 +74,24 @@ function! padre#debugger#Debug(...)
       continue
     endif
 
+    let l:match = matchlist(l:arg, '^\(--attach\|--attach-name\)=\(.*\)$')
+    if !empty(l:match) && l:process_vim_args == 1
+      let l:attach = l:arg
+      continue
+    endif
+
+    let l:match = matchlist(l:arg, '^--core=\(.*\)$')
+    if !empty(l:match) && l:process_vim_args == 1
+      let l:core = l:arg
+      continue
+    endif
+
+    let l:match = matchlist(l:arg, '^\(--remote\|--path-map\)=\(.*\)$')
+    if !empty(l:match) && l:process_vim_args == 1
+      let l:remote .= ' ' . l:arg
+      continue
+    endif
+
     let l:match = matchlist(l:arg, '^--connect=\([^ ]*\):\([0-9]*\)$')
     if !empty(l:match) && l:process_vim_args == 1
       let l:padre_host = match[1]
@@ -90,7 +112,7 @@ function! padre#debugger#Debug(...)
     endif
   endwhile
 
-  if l:program == '' && l:padre_port == 0
+  if l:program == '' && l:padre_port == 0 && l:attach == '' && l:remote !~# '--remote='
     if s:PadreDebugProgram != ''
       let l:program = s:PadreDebugProgram
     elseif get(g:, 'PadreDebugProgram', '') != ''
@@ -109,7 +131,7 @@ function! padre#debugger#Debug(...)
     wincmd b
 
     " TODO: Check for errors and report
-    let l:command = s:PluginRoot . '/padre/target/debug/padre --debugger=' . l:debugger . ' --type=' . l:debugger_type . ' -- ' . l:program
+    let l:command = s:PluginRoot . '/padre/target/debug/padre --debugger=' . l:debugger . ' --type=' . l:debugger_type . ' ' . l:attach . ' ' . l:core . l:remote . ' -- ' . l:program
     execute 'terminal ++curwin ' . l:command
 
     sleep 500ms
@@ -142,6 +164,32 @@ function! padre#debugger#Run()
   call padre#socket#Send({"cmd": "run"}, function('padre#debugger#RunCallback'))
 endfunction
 
+function! padre#debugger#Restart()
+  if s:Running == 0
+    echoerr 'PADRE is not running'
+  endif
+
+  call padre#socket#Send({"cmd": "restart"}, function('padre#debugger#RunCallback'))
+endfunction
+
+function! padre#debugger#Attach(target)
+  if a:target =~# '^[0-9]\+$'
+    let l:request = {"cmd": "attach", "pid": str2nr(a:target)}
+  else
+    let l:request = {"cmd": "attach", "name": a:target}
+  endif
+
+  call padre#socket#Send(l:request, function('padre#debugger#RunCallback'))
+endfunction
+
+function! padre#debugger#Kill()
+  call padre#socket#Send({"cmd": "kill"}, function('padre#debugger#KillCallback'))
+endfunction
+
+function! padre#debugger#Detach()
+  call padre#socket#Send({"cmd": "detach"}, function('padre#debugger#DetachCallback'))
+endfunction
+
 function! padre#debugger#Stop()
   call padre#job#StopAllJobs()
 
@@ -156,11 +204,22 @@ function! s:SetBreakpointInDebugger(line, file)
   call padre#socket#Send({"cmd": "breakpoint", "file": a:file, "line": str2nr(a:line)}, function('padre#debugger#BreakpointCallback'))
 endfunction
 
+function! s:RemoveBreakpointInDebugger(line, file)
+  call padre#socket#Send({"cmd": "removeBreakpoint", "file": a:file, "line": str2nr(a:line)}, function('padre#debugger#BreakpointCallback'))
+endfunction
+
 function! padre#debugger#Breakpoint()
+  let l:file = expand('%')
+  let l:line = getpos('.')[1]
+
   let l:breakpointAdded = padre#signs#ToggleBreakpoint()
 
-  if !empty(l:breakpointAdded) && s:Running == 1
-    call s:SetBreakpointInDebugger(l:breakpointAdded['line'], l:breakpointAdded['file'])
+  if s:Running == 1
+    if !empty(l:breakpointAdded)
+      call s:SetBreakpointInDebugger(l:breakpointAdded['line'], l:breakpointAdded['file'])
+    else
+      call s:RemoveBreakpointInDebugger(l:line, l:file)
+    endif
   endif
 endfunction
 
@@ -172,6 +231,26 @@ function! padre#debugger#StepOver()
   call padre#socket#Send({"cmd": "stepOver"}, function('padre#debugger#StepOverCallback'))
 endfunction
 
+function! padre#debugger#StepOut()
+  call padre#socket#Send({"cmd": "stepOut"}, function('padre#debugger#StepOutCallback'))
+endfunction
+
+function! padre#debugger#StepInstruction()
+  call padre#socket#Send({"cmd": "stepInstruction"}, function('padre#debugger#StepInstructionCallback'))
+endfunction
+
+function! padre#debugger#NextInstruction()
+  call padre#socket#Send({"cmd": "nextInstruction"}, function('padre#debugger#StepInstructionCallback'))
+endfunction
+
+function! padre#debugger#RunToLine(...)
+  let l:args = {"cmd": "runToLine", "file": expand('%'), "line": getpos('.')[1]}
+  if a:0 > 0 && a:1 == '!'
+    let l:args['ignoreBreakpoints'] = v:true
+  endif
+  call padre#socket#Send(l:args, function('padre#debugger#RunToLineCallback'))
+endfunction
+
 function! padre#debugger#PrintVariable(variable)
   call padre#socket#Send({"cmd": "print", "variable": a:variable}, function('padre#debugger#PrintVariableCallback'))
 endfunction
@@ -180,6 +259,74 @@ function! padre#debugger#Continue()
   call padre#socket#Send({"cmd": "continue"}, function('padre#debugger#ContinueCallback'))
 endfunction
 
+function! padre#debugger#Pause()
+  call padre#socket#Send({"cmd": "pause"}, function('padre#debugger#PauseCallback'))
+endfunction
+
+function! padre#debugger#Backtrace()
+  call padre#socket#Send({"cmd": "backtrace"}, function('padre#debugger#BacktraceCallback'))
+endfunction
+
+function! padre#debugger#Disassemble(...)
+  let l:args = {"cmd": "disassemble"}
+  if a:0 > 0
+    let l:args['address'] = a:1
+  endif
+  call padre#socket#Send(l:args, function('padre#debugger#DisassembleCallback'))
+endfunction
+
+function! padre#debugger#Registers(...)
+  let l:args = {"cmd": "registers"}
+  if a:0 > 0
+    let l:args['group'] = a:1
+  endif
+  call padre#socket#Send(l:args, function('padre#debugger#RegistersCallback'))
+endfunction
+
+function! padre#debugger#SetRegister(name, ...)
+  call padre#socket#Send({"cmd": "setRegister", "name": a:name, "value": join(a:000, ' ')}, function('padre#debugger#SetRegisterCallback'))
+endfunction
+
+function! padre#debugger#Locals()
+  call padre#socket#Send({"cmd": "locals"}, function('padre#debugger#LocalsCallback'))
+endfunction
+
+function! padre#debugger#ExpandVariable(variables_reference)
+  call padre#socket#Send({"cmd": "expandVariable", "variablesReference": str2nr(a:variables_reference)}, function('padre#debugger#ExpandVariableCallback'))
+endfunction
+
+function! padre#debugger#Evaluate(expression, ...)
+  let l:args = {"cmd": "evaluate", "expression": a:expression}
+  if a:0 > 0
+    let l:args['frame'] = str2nr(a:1)
+  endif
+  call padre#socket#Send(l:args, function('padre#debugger#EvaluateCallback'))
+endfunction
+
+function! padre#debugger#SetVariable(variable, ...)
+  call padre#socket#Send({"cmd": "setVariable", "variable": a:variable, "value": join(a:000, ' ')}, function('padre#debugger#SetVariableCallback'))
+endfunction
+
+function! padre#debugger#Threads()
+  call padre#socket#Send({"cmd": "threads"}, function('padre#debugger#ThreadsCallback'))
+endfunction
+
+function! padre#debugger#SelectThread(id)
+  call padre#socket#Send({"cmd": "selectThread", "id": str2nr(a:id)}, function('padre#debugger#SelectThreadCallback'))
+endfunction
+
+function! padre#debugger#SelectFrame(index)
+  call padre#socket#Send({"cmd": "selectFrame", "index": str2nr(a:index)}, function('padre#debugger#SelectFrameCallback'))
+endfunction
+
+function! padre#debugger#FrameUp()
+  call padre#socket#Send({"cmd": "frameUp"}, function('padre#debugger#SelectFrameCallback'))
+endfunction
+
+function! padre#debugger#FrameDown()
+  call padre#socket#Send({"cmd": "frameDown"}, function('padre#debugger#SelectFrameCallback'))
+endfunction
+
 function! padre#debugger#AddDataWindow()
   let l:created = 0
   let l:item = 0
@@ -246,6 +393,18 @@ function! padre#debugger#RunCallback(channel_id, data)
   endif
 endfunction
 
+function! padre#debugger#KillCallback(channel_id, data)
+  if a:data['status'] != 'OK'
+    call padre#debugger#Log(2, 'Error killing process')
+  endif
+endfunction
+
+function! padre#debugger#DetachCallback(channel_id, data)
+  if a:data['status'] != 'OK'
+    call padre#debugger#Log(2, 'Error detaching from process')
+  endif
+endfunction
+
 function! padre#debugger#BreakpointCallback(channel_id, data)
   if a:data['status'] != 'OK'
     call padre#debugger#Log(2, 'Error: ' . string(a:data))
@@ -257,6 +416,29 @@ function! padre#debugger#BreakpointSet(fileName, lineNum)
   call padre#debugger#Log(4, l:msg)
 endfunction
 
+function! padre#debugger#BreakpointRemoved(fileName, lineNum)
+  let l:msg = 'Breakpoint removed file=' . a:fileName . ', line=' . a:lineNum
+  call padre#debugger#Log(4, l:msg)
+
+  call padre#signs#RemoveBreakpoint(a:fileName, a:lineNum)
+endfunction
+
+function! padre#debugger#Logpoint(fileName, lineNum, msg)
+  call padre#debugger#Log(4, a:fileName . ':' . a:lineNum . ': ' . a:msg)
+endfunction
+
+function! padre#debugger#ExceptionThrown(fileName, lineNum, msg)
+  call padre#debugger#Log(2, 'Exception at ' . a:fileName . ':' . a:lineNum . ': ' . a:msg)
+endfunction
+
+function! padre#debugger#WatchpointHit(id, oldValue, newValue)
+  if empty(a:oldValue)
+    call padre#debugger#Log(4, 'Watchpoint ' . a:id . ' hit, value: ' . a:newValue)
+  else
+    call padre#debugger#Log(4, 'Watchpoint ' . a:id . ' hit, old value: ' . a:oldValue . ', new value: ' . a:newValue)
+  endif
+endfunction
+
 function! padre#debugger#StepInCallback(channel_id, data)
   if a:data['status'] != 'OK'
     call padre#debugger#Log(2, 'Error: ' . string(a:data))
@@ -269,12 +451,194 @@ function! padre#debugger#StepOverCallback(channel_id, data)
   endif
 endfunction
 
+function! padre#debugger#StepOutCallback(channel_id, data)
+  if a:data['status'] != 'OK'
+    call padre#debugger#Log(2, 'Error: ' . string(a:data))
+  elseif has_key(a:data, 'returnValue')
+    call padre#debugger#Log(4, 'Returned ' . a:data['returnValue'])
+  endif
+endfunction
+
+function! padre#debugger#StepInstructionCallback(channel_id, data)
+  if a:data['status'] != 'OK'
+    call padre#debugger#Log(2, 'Error: ' . string(a:data))
+  endif
+endfunction
+
+function! padre#debugger#RunToLineCallback(channel_id, data)
+  if a:data['status'] != 'OK'
+    call padre#debugger#Log(2, 'Error: ' . string(a:data))
+  endif
+endfunction
+
 function! padre#debugger#ContinueCallback(channel_id, data)
   if a:data['status'] != 'OK'
     call padre#debugger#Log(2, 'Error: ' . string(a:data))
   endif
 endfunction
 
+function! padre#debugger#PauseCallback(channel_id, data)
+  if a:data['status'] != 'OK'
+    call padre#debugger#Log(2, 'Error: ' . string(a:data))
+  endif
+endfunction
+
+function! padre#debugger#BacktraceCallback(channel_id, data)
+  if a:data['status'] != 'OK'
+    call padre#debugger#Log(2, 'Error getting backtrace')
+    return
+  endif
+
+  for l:frame in a:data['frames']
+    let l:msg = '#' . l:frame['index'] . ' ' . l:frame['function']
+    if type(l:frame['file']) == v:t_string
+      let l:msg .= ' at ' . l:frame['file'] . ':' . l:frame['line']
+    endif
+    if type(l:frame['module']) == v:t_string
+      let l:msg .= ' from ' . l:frame['module']
+    endif
+    call padre#debugger#Log(4, l:msg)
+  endfor
+endfunction
+
+function! padre#debugger#DisassembleCallback(channel_id, data)
+  if a:data['status'] != 'OK'
+    call padre#debugger#Log(2, 'Error disassembling: ' . get(a:data, 'error', ''))
+    return
+  endif
+
+  let l:lines = []
+  let l:current_line = 0
+  for l:instruction in a:data['instructions']
+    let l:line = l:instruction['address']
+    if type(l:instruction['symbol']) == v:t_string
+      let l:line .= ' <' . l:instruction['symbol']
+      if type(l:instruction['offset']) == v:t_number
+        let l:line .= '+' . l:instruction['offset']
+      endif
+      let l:line .= '>'
+    endif
+    call add(l:lines, l:line . ': ' . l:instruction['instruction'])
+    if l:instruction['current']
+      let l:current_line = len(l:lines)
+    endif
+  endfor
+
+  call padre#buffer#ReplaceBuffer('PADRE_Assembly', l:lines)
+
+  call s:OpenCodeWindow()
+  execute 'buffer ' . padre#buffer#GetBufNumForBufName('PADRE_Assembly')
+  let s:CurrentFileLoaded = 'PADRE_Assembly'
+  call padre#buffer#SetMainPadreKeyBindings(s:CurrentFileLoaded)
+  let s:CurrentFileBufWindow = winnr()
+
+  call padre#signs#ReplaceCodePointer(l:current_line)
+
+  if l:current_line != 0
+    execute 'normal ' . l:current_line . 'G'
+  endif
+endfunction
+
+function! padre#debugger#RegistersCallback(channel_id, data)
+  if a:data['status'] != 'OK'
+    call padre#debugger#Log(2, 'Error reading registers: ' . get(a:data, 'error', ''))
+    return
+  endif
+
+  for l:register in a:data['registers']
+    call padre#debugger#Log(4, l:register['name'] . ' = ' . l:register['value'])
+  endfor
+endfunction
+
+function! padre#debugger#SetRegisterCallback(channel_id, data)
+  if a:data['status'] != 'OK'
+    call padre#debugger#Log(2, 'Error setting register: ' . get(a:data, 'error', ''))
+    return
+  endif
+
+  call padre#debugger#Log(4, a:data['name'] . ' = ' . a:data['value'])
+endfunction
+
+function! padre#debugger#ThreadsCallback(channel_id, data)
+  if a:data['status'] != 'OK'
+    call padre#debugger#Log(2, 'Error getting threads')
+    return
+  endif
+
+  for l:thread in a:data['threads']
+    let l:msg = (l:thread['current'] ? '* ' : '  ') . 'Thread ' . l:thread['id']
+    if l:thread['name'] != ''
+      let l:msg .= ' ' . l:thread['name']
+    endif
+    if type(l:thread['file']) == v:t_string
+      let l:msg .= ' at ' . l:thread['file'] . ':' . l:thread['line']
+    endif
+    call padre#debugger#Log(4, l:msg)
+  endfor
+endfunction
+
+function! padre#debugger#SelectThreadCallback(channel_id, data)
+  if a:data['status'] != 'OK'
+    call padre#debugger#Log(2, 'Error selecting thread')
+  endif
+endfunction
+
+function! padre#debugger#LocalsCallback(channel_id, data)
+  if a:data['status'] != 'OK'
+    call padre#debugger#Log(2, 'Error getting locals')
+    return
+  endif
+
+  for l:local in a:data['locals']
+    call padre#debugger#Log(4, s:VariableMessage(l:local))
+  endfor
+endfunction
+
+function! padre#debugger#ExpandVariableCallback(channel_id, data)
+  if a:data['status'] != 'OK'
+    call padre#debugger#Log(2, 'Error expanding variable')
+    return
+  endif
+
+  for l:variable in a:data['variables']
+    call padre#debugger#Log(4, s:VariableMessage(l:variable))
+  endfor
+endfunction
+
+function! padre#debugger#EvaluateCallback(channel_id, data)
+  if a:data['status'] != 'OK'
+    call padre#debugger#Log(2, 'Error evaluating expression: ' . get(a:data, 'error', ''))
+    return
+  endif
+
+  let a:data['name'] = a:data['expression']
+  call padre#debugger#Log(4, s:VariableMessage(a:data))
+endfunction
+
+function! padre#debugger#SetVariableCallback(channel_id, data)
+  if a:data['status'] != 'OK'
+    call padre#debugger#Log(2, 'Error setting variable: ' . get(a:data, 'error', ''))
+    return
+  endif
+
+  let a:data['name'] = a:data['variable']
+  call padre#debugger#Log(4, s:VariableMessage(a:data))
+endfunction
+
+function! s:VariableMessage(variable)
+  let l:msg = a:variable['name'] . ' (' . a:variable['type'] . ') = ' . a:variable['value']
+  if a:variable['variablesReference'] != 0
+    let l:msg .= ' [' . a:variable['variablesReference'] . ']'
+  endif
+  return l:msg
+endfunction
+
+function! padre#debugger#SelectFrameCallback(channel_id, data)
+  if a:data['status'] != 'OK'
+    call padre#debugger#Log(2, 'Error selecting frame')
+  endif
+endfunction
+
 function! padre#debugger#PrintVariableCallback(channel_id, data)
   let l:status = remove(a:data, 'status')
   if l:status != 'OK'
@@ -289,8 +653,11 @@ function! padre#debugger#PrintVariableCallback(channel_id, data)
   call padre#debugger#Log(4, l:msg)
 endfunction
 
-function! padre#debugger#JumpToPosition(file, line)
+function! padre#debugger#JumpToPosition(file, line, ...)
   let l:msg = 'Stopped file=' . a:file . ' line=' . a:line
+  if a:0 > 0
+    let l:msg .= ' thread=' . a:1
+  endif
   call padre#debugger#Log(4, l:msg)
 
   if a:file[0] == '/'
@@ -300,21 +667,7 @@ function! padre#debugger#JumpToPosition(file, line)
   endif
 
   if l:fileToLoad != s:CurrentFileLoaded
-    call padre#layout#OpenTabWithBuffer('PADRE_Main', 0)
-
-    if s:CurrentFileBufWindow == 0
-      if winwidth(winnr()) <= 30
-        let l:width = winwidth(winnr()) / 2
-      else
-        let l:width = winwidth(winnr()) - 15
-      endif
-
-      vnew
-      execute 'vertical resize ' . l:height
-    else
-      execute s:CurrentFileBufWindow . 'wincmd w'
-      call padre#buffer#UnsetPadreKeyBindings(bufname('%'))
-    endif
+    call s:OpenCodeWindow()
 
     if filereadable(l:fileToLoad)
       execute 'view ' . l:fileToLoad
@@ -334,6 +687,35 @@ function! padre#debugger#JumpToPosition(file, line)
   let s:Debug += 1
 endfunction
 
+function! padre#debugger#JumpToAddress(address, ...)
+  let l:msg = 'Stopped address=' . a:address
+  if a:0 > 0
+    let l:msg .= ' thread=' . a:1
+  endif
+  call padre#debugger#Log(4, l:msg)
+
+  call padre#debugger#Disassemble()
+endfunction
+
+" Go to the window showing the code we're stopped in, creating it if need be
+function! s:OpenCodeWindow()
+  call padre#layout#OpenTabWithBuffer('PADRE_Main', 0)
+
+  if s:CurrentFileBufWindow == 0
+    if winwidth(winnr()) <= 30
+      let l:width = winwidth(winnr()) / 2
+    else
+      let l:width = winwidth(winnr()) - 15
+    endif
+
+    vnew
+    execute 'vertical resize ' . l:height
+  else
+    execute s:CurrentFileBufWindow . 'wincmd w'
+    call padre#buffer#UnsetPadreKeyBindings(bufname('%'))
+  endif
+endfunction
+
 function! padre#debugger#ProcessExited(exit_code, pid)
   call padre#debugger#Log(4, 'Process ' . a:pid . ' finished with exit code=' . a:exit_code)
 endfunction
@@ -92,6 +92,17 @@ function! s:LineHasBreakpoint(file, line)
   return 0
 endfunction
 
+function! padre#signs#RemoveBreakpoint(file, line)
+  if !bufexists(a:file)
+    return
+  endif
+
+  let l:breakpointId = s:LineHasBreakpoint(a:file, a:line)
+  if l:breakpointId != 0
+    execute 'sign unplace ' . l:breakpointId . ' file=' . a:file
+  endif
+endfunction
+
 function! padre#signs#GetAllBreakpointSignsForFile(file)
   redir => l:signs
     silent exec 'sign place file=' . a:file
@@ -16,15 +16,15 @@ Feature: LLDB
         When I send a command 'run' using the terminal
         Then I expect to be called with
             | function                      | args                   |
-            | padre#debugger#JumpToPosition | [".*test_prog.c$", 22] |
+            | padre#debugger#JumpToPosition | [".*test_prog.c$", 22,"\\d+"] |
         When I send a command 's' using the terminal
         Then I expect to be called with
             | function                      | args                  |
-            | padre#debugger#JumpToPosition | [".*test_prog.c$", 8] |
+            | padre#debugger#JumpToPosition | [".*test_prog.c$", 8,"\\d+"] |
         When I send a command 'n' using the terminal
         Then I expect to be called with
             | function                      | args                  |
-            | padre#debugger#JumpToPosition | [".*test_prog.c$", 9] |
+            | padre#debugger#JumpToPosition | [".*test_prog.c$", 9,"\\d+"] |
         When I send a command 'c' using the terminal
         Then I expect to be called with
             | function                     | args       |
@@ -41,42 +41,42 @@ Feature: LLDB
             | function                          | args |
             | padre#debugger#SignalPADREStarted | []   |
         When I send a request to PADRE '{"cmd":"breakpoint","file":"test_prog.c","line":17}'
-        Then I receive both a response '{"status":"OK"}' and I expect to be called with
+        Then I receive both a response '{"status":"OK","id":1}' and I expect to be called with
             | function                     | args                     |
             | padre#debugger#Log           | [4, ".*test_prog.c.*17"] |
             | padre#debugger#BreakpointSet | [".*test_prog.c$", 17]   |
         When I send a request to PADRE '{"cmd":"breakpoint","file":"not_exists.c","line":17}'
-        Then I receive both a response '{"status":"PENDING"}' and I expect to be called with
+        Then I receive both a response '{"status":"PENDING","id":2}' and I expect to be called with
             | function           | args                     |
             | padre#debugger#Log | [4,".*not_exists.c.*17"] |
         When I send a request to PADRE '{"cmd":"run"}'
         Then I receive both a response '{"status":"OK","pid":"\\d+"}' and I expect to be called with
             | function                      | args                    |
             | padre#debugger#BreakpointSet  | [".*test_prog.c$",22]   |
-            | padre#debugger#JumpToPosition | [".*test_prog.c$",22]   |
+            | padre#debugger#JumpToPosition | [".*test_prog.c$",22,"\\d+"]   |
             | padre#debugger#Log            | [4,"Launching process"] |
         When I send a request to PADRE '{"cmd":"stepIn"}'
         Then I receive both a response '{"status":"OK"}' and I expect to be called with
             | function                      | args                 |
-            | padre#debugger#JumpToPosition | [".*test_prog.c$",8] |
+            | padre#debugger#JumpToPosition | [".*test_prog.c$",8,"\\d+"] |
         When I send a request to PADRE '{"cmd":"stepOver"}'
         Then I receive both a response '{"status":"OK"}' and I expect to be called with
             | function                      | args                 |
-            | padre#debugger#JumpToPosition | [".*test_prog.c$",9] |
+            | padre#debugger#JumpToPosition | [".*test_prog.c$",9,"\\d+"] |
         When I send a request to PADRE '{"cmd":"stepIn"}'
         Then I receive both a response '{"status":"OK"}' and I expect to be called with
             | function                      | args                  |
-            | padre#debugger#JumpToPosition | [".*test_prog.c$",13] |
+            | padre#debugger#JumpToPosition | [".*test_prog.c$",13,"\\d+"] |
         When I send a request to PADRE '{"cmd":"continue"}'
         Then I receive both a response '{"status":"OK"}' and I expect to be called with
             | function                      | args                  |
-            | padre#debugger#JumpToPosition | [".*test_prog.c$",17] |
+            | padre#debugger#JumpToPosition | [".*test_prog.c$",17,"\\d+"] |
         When I send a request to PADRE '{"cmd":"stepOver"}'
         Then I receive both a response '{"status":"OK"}' and I expect to be called with
             | function                      | args                  |
-            | padre#debugger#JumpToPosition | [".*test_prog.c$",18] |
+            | padre#debugger#JumpToPosition | [".*test_prog.c$",18,"\\d+"] |
         When I send a request to PADRE '{"cmd":"print","variable":"a"}'
-        Then I receive a response '{"status":"OK","variable":"a","value":"1","type":"int"}'
+        Then I receive a response '{"status":"OK","variable":"a","value":"1","type":"int","variablesReference":0}'
         When I send a request to PADRE '{"cmd":"continue"}'
         Then I receive both a response '{"status":"OK"}' and I expect to be called with
             | function                     | args       |
@@ -105,30 +105,30 @@ Feature: LLDB
         Then I receive both a response '{"status":"OK","pid":"\\d+"}' and I expect to be called with
             | function                      | args                    |
             | padre#debugger#BreakpointSet  | [".*test_prog.c$",22]   |
-            | padre#debugger#JumpToPosition | [".*test_prog.c$",22]   |
+            | padre#debugger#JumpToPosition | [".*test_prog.c$",22,"\\d+"]   |
             | padre#debugger#Log            | [4,"Launching process"] |
         When I send a command 's' using the terminal
         Then I expect to be called with
             | function                      | args                  |
-            | padre#debugger#JumpToPosition | [".*test_prog.c$", 8] |
+            | padre#debugger#JumpToPosition | [".*test_prog.c$", 8,"\\d+"] |
         When I send a request to PADRE '{"cmd":"stepOver"}'
         Then I receive both a response '{"status":"OK"}' and I expect to be called with
             | function                      | args                 |
-            | padre#debugger#JumpToPosition | [".*test_prog.c$",9] |
+            | padre#debugger#JumpToPosition | [".*test_prog.c$",9,"\\d+"] |
         When I send a command 'n' using the terminal
         Then I expect to be called with
             | function                      | args                   |
-            | padre#debugger#JumpToPosition | [".*test_prog.c$", 17] |
+            | padre#debugger#JumpToPosition | [".*test_prog.c$", 17,"\\d+"] |
         When I send a command 'n' using the terminal
         Then I expect to be called with
             | function                      | args                   |
-            | padre#debugger#JumpToPosition | [".*test_prog.c$", 18] |
+            | padre#debugger#JumpToPosition | [".*test_prog.c$", 18,"\\d+"] |
         When I send a request to PADRE '{"cmd":"print","variable":"a"}'
-        Then I receive a response '{"status":"OK","variable":"a","value":"1","type":"int"}'
+        Then I receive a response '{"status":"OK","variable":"a","value":"1","type":"int","variablesReference":0}'
         When I send a command 'c' using the terminal
         Then I expect to be called with
             | function                      | args                   |
-            | padre#debugger#JumpToPosition | [".*test_prog.c$", 10] |
+            | padre#debugger#JumpToPosition | [".*test_prog.c$", 10,"\\d+"] |
         When I send a request to PADRE '{"cmd":"continue"}'
         Then I receive both a response '{"status":"OK"}' and I expect to be called with
             | function                     | args       |
@@ -175,7 +175,7 @@ Feature: LLDB
         Then I receive both a response '{"status":"OK","pid":"\\d+"}' and I expect to be called with
             | function                      | args                    |
             | padre#debugger#BreakpointSet  | [".*test_prog.c$",22]   |
-            | padre#debugger#JumpToPosition | [".*test_prog.c$",22]   |
+            | padre#debugger#JumpToPosition | [".*test_prog.c$",22,"\\d+"]   |
             | padre#debugger#Log            | [4,"Launching process"] |
         When I send a request to PADRE '{"cmd":"print","variable":"a"}'
         Then I receive both a response '{"status":"ERROR"}' and I expect to be called with
@@ -200,41 +200,41 @@ Feature: LLDB
         When I send a request to PADRE '{"cmd":"continue"}'
         Then I receive both a response '{"status":"OK"}' and I expect to be called with
             | function                      | args                              |
-            | padre#debugger#JumpToPosition | [".*test_print_variables.rs$",16] |
+            | padre#debugger#JumpToPosition | [".*test_print_variables.rs$",16,"\\d+"] |
         When I send a request to PADRE '{"cmd":"stepOver"}'
         Then I receive both a response '{"status":"OK"}' and I expect to be called with
             | function                      | args                              |
-            | padre#debugger#JumpToPosition | [".*test_print_variables.rs$",17] |
+            | padre#debugger#JumpToPosition | [".*test_print_variables.rs$",17,"\\d+"] |
         When I send a request to PADRE '{"cmd":"print","variable":"a"}'
-        Then I receive a response '{"status":"OK","variable":"a","value":"42","type":"int"}'
+        Then I receive a response '{"status":"OK","variable":"a","value":"42","type":"int","variablesReference":0}'
         When I send a request to PADRE '{"cmd":"stepOver"}'
         Then I receive both a response '{"status":"OK"}' and I expect to be called with
             | function                      | args                              |
-            | padre#debugger#JumpToPosition | [".*test_print_variables.rs$",18] |
+            | padre#debugger#JumpToPosition | [".*test_print_variables.rs$",18,"\\d+"] |
             #When I send a request to PADRE '{"cmd":"print","variable":"b"}'
             #Then I receive a response '{"status":"OK","variable":"b","deref":{"variable":"\\*b","type":"int","value":"42"},"type":"int \\*","value":"^&0x[0-9a-f]*$"}'
             #When I send a request to PADRE '{"cmd":"stepOver"}'
             #Then I receive both a response '{"status":"OK"}' and I expect to be called with
             #    | function                      | args                              |
-            #    | padre#debugger#JumpToPosition | [".*test_print_variables.rs$",19] |
+            #    | padre#debugger#JumpToPosition | [".*test_print_variables.rs$",19,"\\d+"] |
             #When I send a request to PADRE '{"cmd":"print","variable":"a"}'
             #Then I receive a response '{"status":"OK","variable":"a","value":"^42.[0-9][0-9]*$","type":"float"}'
             #When I send a request to PADRE '{"cmd":"stepOver"}'
             #Then I receive both a response '{"status":"OK"}' and I expect to be called with
             #    | function                      | args                              |
-            #    | padre#debugger#JumpToPosition | [".*test_print_variables.rs$",20] |
+            #    | padre#debugger#JumpToPosition | [".*test_print_variables.rs$",20,"\\d+"] |
             #When I send a request to PADRE '{"cmd":"print","variable":"a"}'
             #Then I receive a response '{"status":"OK","variable":"a","value":"true","type":"bool"}'
             #When I send a request to PADRE '{"cmd":"stepOver"}'
             #Then I receive both a response '{"status":"OK"}' and I expect to be called with
             #    | function                      | args                              |
-            #    | padre#debugger#JumpToPosition | [".*test_print_variables.rs$",21] |
+            #    | padre#debugger#JumpToPosition | [".*test_print_variables.rs$",21,"\\d+"] |
             #When I send a request to PADRE '{"cmd":"print","variable":"a"}'
             #Then I receive a response '{"status":"OK","variable":"a","value":"TEST","type":"&str"}'
             #When I send a request to PADRE '{"cmd":"stepOver"}'
             #Then I receive both a response '{"status":"OK"}' and I expect to be called with
             #    | function                      | args                               |
-            #    | padre#debugger#JumpToPosition | [".*test_print_variables.rs$",22] |
+            #    | padre#debugger#JumpToPosition | [".*test_print_variables.rs$",22,"\\d+"] |
             #When I send a request to PADRE '{"cmd":"print","variable":"b"}'
             #Then I receive a response '{"status":"OK","variable":"b","deref":{"variable":"\\*b","type":"&str","value":"TEST"},"type":"&str *","value":"^&0x[0-9a-f]*$"}'
         When I terminate padre
@@ -287,7 +287,7 @@ Feature: LLDB
         Then I receive both a response '{"status":"OK","pid":"\\d+"}' and I expect to be called with
             | function                      | args                    |
             | padre#debugger#BreakpointSet  | [".*test_prog.c$",22]   |
-            | padre#debugger#JumpToPosition | [".*test_prog.c$",22]   |
+            | padre#debugger#JumpToPosition | [".*test_prog.c$",22,"\\d+"]   |
             | padre#debugger#Log            | [4,"Launching process"] |
         When I send a request to PADRE '{"cmd":"print","variable":"a"}'
         Then I receive both a response '{"status":"ERROR"}' and I expect to be called with
@@ -8,7 +8,7 @@ Feature: NodeJS
             | function                          | args |
             | padre#debugger#SignalPADREStarted | []   |
         When I send a request to PADRE '{"cmd":"breakpoint","file":"test_files/test_prog.js","line":16}'
-        Then I receive a response '{"status":"PENDING"}'
+        Then I receive a response '{"status":"PENDING","id":1}'
         When I send a request to PADRE '{"cmd":"run"}'
         Then I receive both a response '{"status":"OK","pid":"\\d+"}' and I expect to be called with
             | function                      | args                    |
@@ -16,7 +16,7 @@ Feature: NodeJS
             | padre#debugger#JumpToPosition | [".*test_prog.js",22]   |
             | padre#debugger#BreakpointSet  | [".*test_prog.js",16]   |
         When I send a request to PADRE '{"cmd":"breakpoint","file":"test_files/test_prog.js","line":19}'
-        Then I receive both a response '{"status":"OK"}' and I expect to be called with
+        Then I receive both a response '{"status":"OK","id":2}' and I expect to be called with
             | function                      | args                  |
             | padre#debugger#BreakpointSet  | [".*test_prog.js",19] |
         When I send a request to PADRE '{"cmd":"stepOver"}'
@@ -40,10 +40,85 @@ Feature: NodeJS
             | function                      | args                  |
             | padre#debugger#JumpToPosition | [".*test_prog.js",19] |
         When I send a request to PADRE '{"cmd":"print","variable":"b"}'
-        Then I receive a response '{"status":"OK","variable":"b","value":123,"type":"number"}'
+        Then I receive a response '{"status":"OK","variable":"b","value":123,"type":"number","variablesReference":0}'
         When I send a request to PADRE '{"cmd":"continue"}'
         Then I receive both a response '{"status":"OK"}' and I expect to be called with
             | function                     | args       |
             | padre#debugger#ProcessExited | [0,"\\d+"] |
         When I terminate padre
         Then padre is not running
+
+    Scenario: Remove breakpoints in a nodeJS program
+        Given that we have a test program './test_files/test_prog.js' that runs with 'node' debugger
+        When I debug the program with PADRE
+        Then I expect to be called with
+            | function                          | args |
+            | padre#debugger#SignalPADREStarted | []   |
+        When I send a request to PADRE '{"cmd":"breakpoint","file":"test_files/test_prog.js","line":16}'
+        Then I receive a response '{"status":"PENDING","id":1}'
+        When I send a request to PADRE '{"cmd":"breakpoint","file":"test_files/test_prog.js","line":17}'
+        Then I receive a response '{"status":"PENDING","id":2}'
+        When I send a request to PADRE '{"cmd":"removeBreakpoint","file":"test_files/test_prog.js","line":17}'
+        Then I receive both a response '{"status":"OK"}' and I expect to be called with
+            | function                         | args                  |
+            | padre#debugger#BreakpointRemoved | [".*test_prog.js",17] |
+        When I send a request to PADRE '{"cmd":"run"}'
+        Then I receive both a response '{"status":"OK","pid":"\\d+"}' and I expect to be called with
+            | function                      | args                    |
+            | padre#debugger#Log            | [4,"Launching process"] |
+            | padre#debugger#JumpToPosition | [".*test_prog.js",22]   |
+            | padre#debugger#BreakpointSet  | [".*test_prog.js",16]   |
+        When I send a request to PADRE '{"cmd":"removeBreakpoint","file":"test_files/test_prog.js","line":16}'
+        Then I receive both a response '{"status":"OK"}' and I expect to be called with
+            | function                         | args                  |
+            | padre#debugger#BreakpointRemoved | [".*test_prog.js",16] |
+        When I send a request to PADRE '{"cmd":"continue"}'
+        Then I receive both a response '{"status":"OK"}' and I expect to be called with
+            | function                     | args       |
+            | padre#debugger#ProcessExited | [0,"\\d+"] |
+        When I terminate padre
+        Then padre is not running
+
+    Scenario: Log messages from a logpoint in a nodeJS program
+        Given that we have a test program './test_files/test_prog.js' that runs with 'node' debugger
+        When I debug the program with PADRE
+        Then I expect to be called with
+            | function                          | args |
+            | padre#debugger#SignalPADREStarted | []   |
+        When I send a request to PADRE '{"cmd":"logpoint","file":"test_files/test_prog.js","line":17,"message":"b is {b}"}'
+        Then I receive a response '{"status":"PENDING","id":1}'
+        When I send a request to PADRE '{"cmd":"run"}'
+        Then I receive both a response '{"status":"OK","pid":"\\d+"}' and I expect to be called with
+            | function                      | args                    |
+            | padre#debugger#Log            | [4,"Launching process"] |
+            | padre#debugger#JumpToPosition | [".*test_prog.js",22]   |
+            | padre#debugger#BreakpointSet  | [".*test_prog.js",17]   |
+        When I send a request to PADRE '{"cmd":"continue"}'
+        Then I receive both a response '{"status":"OK"}' and I expect to be called with
+            | function                     | args                                      |
+            | padre#debugger#Logpoint      | ["test_files/test_prog.js",17,"b is 123"] |
+            | padre#debugger#ProcessExited | [0,"\\d+"]                                |
+        When I terminate padre
+        Then padre is not running
+
+    Scenario: Break on a function by name in a nodeJS program
+        Given that we have a test program './test_files/test_prog.js' that runs with 'node' debugger
+        When I debug the program with PADRE
+        Then I expect to be called with
+            | function                          | args |
+            | padre#debugger#SignalPADREStarted | []   |
+        When I send a request to PADRE '{"cmd":"run"}'
+        Then I receive both a response '{"status":"OK","pid":"\\d+"}' and I expect to be called with
+            | function                      | args                    |
+            | padre#debugger#Log            | [4,"Launching process"] |
+            | padre#debugger#JumpToPosition | [".*test_prog.js",22]   |
+        When I send a request to PADRE '{"cmd":"breakpointFunction","function":"a"}'
+        Then I receive both a response '{"status":"OK","id":1}' and I expect to be called with
+            | function                     | args                  |
+            | padre#debugger#BreakpointSet | [".*test_prog.js",15] |
+        When I send a request to PADRE '{"cmd":"continue"}'
+        Then I receive both a response '{"status":"OK"}' and I expect to be called with
+            | function                      | args                  |
+            | padre#debugger#JumpToPosition | [".*test_prog.js",16] |
+        When I terminate padre
+        Then padre is not running
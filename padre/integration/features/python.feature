@@ -43,7 +43,7 @@ Feature: Python
             | function                          | args |
             | padre#debugger#SignalPADREStarted | []   |
         When I send a request to PADRE '{"cmd":"breakpoint","file":"`pwd`/test_files/test_prog.py","line":21}'
-        Then I receive both a response '{"status":"PENDING"}' and I expect to be called with
+        Then I receive both a response '{"status":"PENDING","id":1}' and I expect to be called with
             | function           | args                      |
             | padre#debugger#Log | [4, ".*test_prog.py.*21"] |
         When I send a request to PADRE '{"cmd":"run"}'
@@ -53,7 +53,7 @@ Feature: Python
             | padre#debugger#JumpToPosition | [".*test_prog.py",3]    |
             | padre#debugger#BreakpointSet  | [".*test_prog.py",21]   |
         When I send a request to PADRE '{"cmd":"breakpoint","file":"`pwd`/test_files/test_prog.py","line":22}'
-        Then I receive both a response '{"status":"OK"}' and I expect to be called with
+        Then I receive both a response '{"status":"OK","id":2}' and I expect to be called with
             | function                      | args                      |
             | padre#debugger#Log            | [4, ".*test_prog.py.*22"] |
             | padre#debugger#BreakpointSet  | [".*test_prog.py",22]     |
@@ -74,7 +74,7 @@ Feature: Python
             | function                      | args                  |
             | padre#debugger#JumpToPosition | [".*test_prog.py",22] |
         When I send a request to PADRE '{"cmd":"print","variable":"b"}'
-        Then I receive a response '{"status":"OK","variable":"b","value":"123"}'
+        Then I receive a response '{"status":"OK","variable":"b","value":"123","type":"int","variablesReference":0}'
         When I send a request to PADRE '{"cmd":"continue"}'
         Then I receive both a response '{"status":"OK"}' and I expect to be called with
             | function                      | args                   |
@@ -82,3 +82,63 @@ Feature: Python
             | padre#debugger#JumpToPosition | [".*test_prog.py$", 3] |
         When I terminate padre
         Then padre is not running
+
+    Scenario: Restart a Python program keeping its breakpoints
+        Given that we have a test program './test_files/test_prog.py' that runs with 'python3' debugger of type 'python'
+        When I debug the program with PADRE
+        Then I expect to be called with
+            | function                          | args |
+            | padre#debugger#SignalPADREStarted | []   |
+        When I send a request to PADRE '{"cmd":"breakpoint","file":"`pwd`/test_files/test_prog.py","line":21}'
+        Then I receive both a response '{"status":"PENDING","id":1}' and I expect to be called with
+            | function           | args                      |
+            | padre#debugger#Log | [4, ".*test_prog.py.*21"] |
+        When I send a request to PADRE '{"cmd":"run"}'
+        Then I receive both a response '{"status":"OK","pid":"\\d+"}' and I expect to be called with
+            | function                      | args                    |
+            | padre#debugger#Log            | [4,"Launching process"] |
+            | padre#debugger#JumpToPosition | [".*test_prog.py",3]    |
+            | padre#debugger#BreakpointSet  | [".*test_prog.py",21]   |
+        When I send a request to PADRE '{"cmd":"continue"}'
+        Then I receive both a response '{"status":"OK"}' and I expect to be called with
+            | function                      | args                  |
+            | padre#debugger#JumpToPosition | [".*test_prog.py",21] |
+        When I send a request to PADRE '{"cmd":"restart"}'
+        Then I receive both a response '{"status":"OK","pid":"\\d+"}' and I expect to be called with
+            | function                      | args                     |
+            | padre#debugger#Log            | [4,"Restarting process"] |
+            | padre#debugger#ProcessExited  | [9,"\\d+"]              |
+            | padre#debugger#Log            | [4,"Launching process"]  |
+            | padre#debugger#JumpToPosition | [".*test_prog.py",3]     |
+            | padre#debugger#BreakpointSet  | [".*test_prog.py",21]    |
+        When I send a request to PADRE '{"cmd":"continue"}'
+        Then I receive both a response '{"status":"OK"}' and I expect to be called with
+            | function                      | args                  |
+            | padre#debugger#JumpToPosition | [".*test_prog.py",21] |
+        When I terminate padre
+        Then padre is not running
+
+    Scenario: Map the paths of Python source files to local paths
+        Given that we have a test program './test_files/test_prog.py' that runs with 'python3' debugger of type 'python'
+        When I debug the program with PADRE
+        Then I expect to be called with
+            | function                          | args |
+            | padre#debugger#SignalPADREStarted | []   |
+        When I send a request to PADRE '{"cmd":"setConfig","key":"sourceMap","value":[["`pwd`/test_files","/home/me/project"]]}'
+        Then I receive a response '{"status":"OK"}'
+        When I send a request to PADRE '{"cmd":"breakpoint","file":"/home/me/project/test_prog.py","line":21}'
+        Then I receive both a response '{"status":"PENDING","id":1}' and I expect to be called with
+            | function           | args                                 |
+            | padre#debugger#Log | [4, ".*test_files/test_prog.py.*21"] |
+        When I send a request to PADRE '{"cmd":"run"}'
+        Then I receive both a response '{"status":"OK","pid":"\\d+"}' and I expect to be called with
+            | function                      | args                                 |
+            | padre#debugger#Log            | [4,"Launching process"]              |
+            | padre#debugger#JumpToPosition | ["/home/me/project/test_prog.py",3]  |
+            | padre#debugger#BreakpointSet  | ["/home/me/project/test_prog.py",21] |
+        When I send a request to PADRE '{"cmd":"continue"}'
+        Then I receive both a response '{"status":"OK"}' and I expect to be called with
+            | function                      | args                                 |
+            | padre#debugger#JumpToPosition | ["/home/me/project/test_prog.py",21] |
+        When I terminate padre
+        Then padre is not running
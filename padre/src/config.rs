@@ -13,8 +13,16 @@
 //!    to 10 seconds.
 //!  - BreakpointTimeout: Timeout for setting a breakpoint. Defaults to 2 second.
 //!    Only used in LLDB.
-//!  - PrintVariableTimeout: Timeout for setting a breakpoint. Defaults to 2 second.
-//!    Only used in LLDB.
+//!  - PrintVariableTimeout: Timeout for printing or setting a variable or evaluating an
+//!    expression, getting a backtrace, locals or threads or selecting a frame or thread. Defaults
+//!    to 2 second. Only used in LLDB and Python.
+//!  - StepOutTimeout: Timeout for stepping out of a function and getting the return value.
+//!    Defaults to 2 seconds. Only used in LLDB.
+//!
+//! The `sourceMap` can also be got and set with the config requests, it's a list of pairs of path
+//! prefixes, the path the debugger knows files by first and then the local path, e.g.
+//! `[["/build/src","/home/me/src"]]`. Unlike the above it's not per socket and applies to all of
+//! them, it starts as the `--path-map` arguments.
 
 use std::collections::HashMap;
 
@@ -37,6 +45,7 @@ impl<'a> Config<'a> {
         config.insert("ProcessSpawnTimeout", 10);
         config.insert("BreakpointTimeout", 2);
         config.insert("PrintVariableTimeout", 2);
+        config.insert("StepOutTimeout", 2);
         Config { config }
     }
 
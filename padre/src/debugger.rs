@@ -5,9 +5,11 @@
 
 use std::fmt::Debug;
 use std::io;
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 
 use crate::config::Config;
+use crate::notifier::{log_msg, set_source_map, LogLevel};
 use crate::util::{file_is_binary_executable, file_is_text};
 
 use tokio::prelude::*;
@@ -37,6 +39,709 @@ impl FileLocation {
     }
 }
 
+/// Mapping between the paths of source files as the debugger knows them, e.g. on a remote
+/// machine, and the paths of the files locally
+///
+/// Each mapping is a pair of prefixes, the debugger's first and then the local one. The first
+/// matching prefix is replaced.
+#[derive(Clone, Deserialize, Debug, Default, PartialEq)]
+pub struct SourceMap {
+    mappings: Vec<(String, String)>,
+}
+
+impl SourceMap {
+    pub fn new(mappings: Vec<(String, String)>) -> Self {
+        SourceMap { mappings }
+    }
+
+    pub fn mappings(&self) -> &Vec<(String, String)> {
+        &self.mappings
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!(self.mappings)
+    }
+
+    /// Get the local path of a file the debugger reported
+    pub fn to_local(&self, path: &str) -> String {
+        for (debugger_prefix, local_prefix) in &self.mappings {
+            match replace_path_prefix(path, debugger_prefix, local_prefix) {
+                Some(s) => return s,
+                None => {}
+            }
+        }
+        path.to_string()
+    }
+
+    /// Get the path the debugger knows a local file by
+    pub fn to_debugger(&self, path: &str) -> String {
+        for (debugger_prefix, local_prefix) in &self.mappings {
+            match replace_path_prefix(path, local_prefix, debugger_prefix) {
+                Some(s) => return s,
+                None => {}
+            }
+        }
+        path.to_string()
+    }
+
+    pub fn to_debugger_location(&self, file_location: &FileLocation) -> FileLocation {
+        FileLocation::new(
+            self.to_debugger(&file_location.name),
+            file_location.line_num,
+        )
+    }
+
+    /// Rewrite the `file` of each object in a JSON array, e.g. frames in a backtrace, to the
+    /// local path
+    pub fn localise_files(&self, items: &mut serde_json::Value) {
+        match items.as_array_mut() {
+            Some(items) => {
+                for item in items {
+                    let file = match item["file"].as_str() {
+                        Some(file) => self.to_local(file),
+                        None => continue,
+                    };
+                    item["file"] = serde_json::json!(file);
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+/// Replace the prefix of a path if it's a prefix of whole directory names
+fn replace_path_prefix(path: &str, from: &str, to: &str) -> Option<String> {
+    let from = from.trim_end_matches('/');
+    let to = to.trim_end_matches('/');
+
+    if from.is_empty() || !path.starts_with(from) {
+        return None;
+    }
+
+    let rest = &path[from.len()..];
+    match rest.is_empty() || rest.starts_with('/') {
+        true => Some(format!("{}{}", to, rest)),
+        false => None,
+    }
+}
+
+/// Function to set a breakpoint on
+///
+/// The name may be restricted to a module and may be a regex to match several functions where
+/// the debugger supports it.
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq, Hash)]
+pub struct FunctionName {
+    name: String,
+    module: Option<String>,
+    is_regex: bool,
+}
+
+impl FunctionName {
+    pub fn new(name: String, module: Option<String>, is_regex: bool) -> Self {
+        FunctionName {
+            name,
+            module,
+            is_regex,
+        }
+    }
+}
+
+/// Which exceptions, or panics, the debugger should stop on
+#[derive(Clone, Copy, Deserialize, Debug, PartialEq, Eq, Hash)]
+pub enum ExceptionBreakpointMode {
+    None,
+    Uncaught,
+    All,
+}
+
+impl ExceptionBreakpointMode {
+    pub fn from_str(mode: &str) -> Option<Self> {
+        match mode {
+            "none" => Some(ExceptionBreakpointMode::None),
+            "uncaught" => Some(ExceptionBreakpointMode::Uncaught),
+            "all" => Some(ExceptionBreakpointMode::All),
+            _ => None,
+        }
+    }
+}
+
+/// Optional condition and hit count that must both be satisfied for a breakpoint to stop
+///
+/// The hit count is the number of times the breakpoint must be hit (with the condition true)
+/// before it first stops, after which it stops every time.
+#[derive(Clone, Default, Deserialize, Debug, PartialEq, Eq, Hash)]
+pub struct BreakpointCondition {
+    condition: Option<String>,
+    hit_count: Option<u64>,
+}
+
+impl BreakpointCondition {
+    pub fn new(condition: Option<String>, hit_count: Option<u64>) -> Self {
+        BreakpointCondition {
+            condition,
+            hit_count,
+        }
+    }
+}
+
+/// The state of a breakpoint as reported by the debugger
+#[derive(Clone, Debug, PartialEq)]
+pub enum BreakpointStatus {
+    Pending,
+    Resolved,
+    Multiple,
+    Failed,
+}
+
+impl BreakpointStatus {
+    fn as_str(&self) -> &str {
+        match self {
+            BreakpointStatus::Pending => "PENDING",
+            BreakpointStatus::Resolved => "RESOLVED",
+            BreakpointStatus::Multiple => "MULTIPLE",
+            BreakpointStatus::Failed => "FAILED",
+        }
+    }
+}
+
+/// A breakpoint that has been requested by a client
+///
+/// Every breakpoint is given a padre ID that stays the same for the lifetime of the breakpoint,
+/// along with the ID the debugger itself uses for it once known. Breakpoints on functions only
+/// have a location once the debugger has resolved them to a single one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Breakpoint {
+    id: u64,
+    file_location: Option<FileLocation>,
+    condition: BreakpointCondition,
+    log_message: Option<String>,
+    function: Option<FunctionName>,
+    status: BreakpointStatus,
+    native_id: Option<String>,
+}
+
+impl Breakpoint {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn file_location(&self) -> Option<&FileLocation> {
+        match &self.file_location {
+            Some(fl) => Some(fl),
+            None => None,
+        }
+    }
+
+    pub fn condition(&self) -> &BreakpointCondition {
+        &self.condition
+    }
+
+    /// The message template if this is a logpoint rather than a breakpoint that stops
+    pub fn log_message(&self) -> Option<&str> {
+        match &self.log_message {
+            Some(s) => Some(s),
+            None => None,
+        }
+    }
+
+    pub fn function(&self) -> Option<&FunctionName> {
+        match &self.function {
+            Some(function) => Some(function),
+            None => None,
+        }
+    }
+
+    pub fn status(&self) -> &BreakpointStatus {
+        &self.status
+    }
+
+    pub fn native_id(&self) -> Option<&str> {
+        match &self.native_id {
+            Some(s) => Some(s),
+            None => None,
+        }
+    }
+
+    /// Whether this is the breakpoint requested at the location, function breakpoints are never
+    /// found by location
+    fn is_at(&self, file_location: &FileLocation) -> bool {
+        self.function.is_none() && self.file_location.as_ref() == Some(file_location)
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "id": self.id,
+            "file": self.file_location.as_ref().map(|fl| &fl.name),
+            "line": self.file_location.as_ref().map(|fl| fl.line_num),
+            "condition": self.condition.condition,
+            "hitCount": self.condition.hit_count,
+            "logMessage": self.log_message,
+            "function": match &self.function {
+                Some(f) => Some(&f.name),
+                None => None,
+            },
+            "status": self.status.as_str(),
+            "nativeId": self.native_id,
+        })
+    }
+}
+
+/// Registry of all breakpoints shared between padre and the debugger backends
+///
+/// Breakpoints are keyed on the `FileLocation` the client requested, or the `FunctionName` for
+/// breakpoints on functions, the backends update the state and native IDs as the debugger reports
+/// on them.
+#[derive(Debug)]
+pub struct BreakpointRegistry {
+    breakpoints: Vec<Breakpoint>,
+    next_id: u64,
+}
+
+impl BreakpointRegistry {
+    pub fn new() -> Self {
+        BreakpointRegistry {
+            breakpoints: vec![],
+            next_id: 1,
+        }
+    }
+
+    /// Add a breakpoint and return its padre ID, if the breakpoint already exists the
+    /// existing ID is returned and it's marked as pending again with the new condition.
+    pub fn add(&mut self, file_location: FileLocation, condition: BreakpointCondition) -> u64 {
+        self.insert(file_location, condition, None)
+    }
+
+    /// Add a logpoint and return its padre ID, as with `add` any existing ID is reused.
+    pub fn add_logpoint(&mut self, file_location: FileLocation, log_message: String) -> u64 {
+        self.insert(
+            file_location,
+            BreakpointCondition::default(),
+            Some(log_message),
+        )
+    }
+
+    /// Add a breakpoint on a function once the debugger has set it and return its padre ID
+    ///
+    /// The location is where the debugger resolved it to, if it's resolved to just one. Function
+    /// breakpoints never replace breakpoints at a location even when they're at the same one,
+    /// setting the same function again reuses its ID.
+    pub fn add_function(
+        &mut self,
+        function: FunctionName,
+        file_location: Option<FileLocation>,
+        native_id: String,
+        status: BreakpointStatus,
+    ) -> u64 {
+        if let Some(bkpt) = self
+            .breakpoints
+            .iter_mut()
+            .find(|bkpt| bkpt.function.as_ref() == Some(&function))
+        {
+            bkpt.file_location = file_location;
+            bkpt.status = status;
+            bkpt.native_id = Some(native_id);
+            return bkpt.id;
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.breakpoints.push(Breakpoint {
+            id,
+            file_location,
+            condition: BreakpointCondition::default(),
+            log_message: None,
+            function: Some(function),
+            status,
+            native_id: Some(native_id),
+        });
+
+        id
+    }
+
+    fn insert(
+        &mut self,
+        file_location: FileLocation,
+        condition: BreakpointCondition,
+        log_message: Option<String>,
+    ) -> u64 {
+        if let Some(bkpt) = self
+            .breakpoints
+            .iter_mut()
+            .find(|bkpt| bkpt.is_at(&file_location))
+        {
+            bkpt.condition = condition;
+            bkpt.log_message = log_message;
+            bkpt.status = BreakpointStatus::Pending;
+            return bkpt.id;
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.breakpoints.push(Breakpoint {
+            id,
+            file_location: Some(file_location),
+            condition,
+            log_message,
+            function: None,
+            status: BreakpointStatus::Pending,
+            native_id: None,
+        });
+
+        id
+    }
+
+    pub fn get(&self, file_location: &FileLocation) -> Option<&Breakpoint> {
+        self.breakpoints
+            .iter()
+            .find(|bkpt| bkpt.is_at(file_location))
+    }
+
+    pub fn get_function(&self, function: &FunctionName) -> Option<&Breakpoint> {
+        self.breakpoints
+            .iter()
+            .find(|bkpt| bkpt.function.as_ref() == Some(function))
+    }
+
+    pub fn remove(&mut self, file_location: &FileLocation) -> Option<Breakpoint> {
+        let index = self
+            .breakpoints
+            .iter()
+            .position(|bkpt| bkpt.is_at(file_location))?;
+        Some(self.breakpoints.remove(index))
+    }
+
+    pub fn remove_function(&mut self, function: &FunctionName) -> Option<Breakpoint> {
+        let index = self
+            .breakpoints
+            .iter()
+            .position(|bkpt| bkpt.function.as_ref() == Some(function))?;
+        Some(self.breakpoints.remove(index))
+    }
+
+    pub fn set_status(&mut self, file_location: &FileLocation, status: BreakpointStatus) {
+        for bkpt in self.breakpoints.iter_mut() {
+            if bkpt.is_at(file_location) {
+                bkpt.status = status.clone();
+            }
+        }
+    }
+
+    pub fn set_native_id(&mut self, file_location: &FileLocation, native_id: String) {
+        for bkpt in self.breakpoints.iter_mut() {
+            if bkpt.is_at(file_location) {
+                bkpt.native_id = Some(native_id.clone());
+            }
+        }
+    }
+
+    /// Record the debugger's ID for the breakpoint with the padre ID, for breakpoints from
+    /// `pending` which may be on functions rather than locations
+    pub fn set_native_id_by_id(&mut self, id: u64, native_id: String) {
+        for bkpt in self.breakpoints.iter_mut() {
+            if bkpt.id == id {
+                bkpt.native_id = Some(native_id.clone());
+            }
+        }
+    }
+
+    pub fn set_status_by_id(&mut self, id: u64, status: BreakpointStatus) {
+        for bkpt in self.breakpoints.iter_mut() {
+            if bkpt.id == id {
+                bkpt.status = status.clone();
+            }
+        }
+    }
+
+    pub fn set_status_by_native_id(&mut self, native_id: &str, status: BreakpointStatus) {
+        for bkpt in self.breakpoints.iter_mut() {
+            if bkpt.native_id() == Some(native_id) {
+                bkpt.status = status.clone();
+            }
+        }
+    }
+
+    /// Find the requested location of a breakpoint given the location reported by the
+    /// debugger, which may have been given as a full path rather than what was requested.
+    pub fn find_location(&self, file: &str, line_num: u64) -> Option<FileLocation> {
+        self.breakpoints
+            .iter()
+            .filter(|bkpt| bkpt.function.is_none())
+            .filter_map(|bkpt| bkpt.file_location.as_ref())
+            .find(|fl| fl.line_num == line_num && is_same_file(&fl.name, file))
+            .cloned()
+    }
+
+    /// Find a breakpoint on a function that's waiting to be set again at the location the
+    /// debugger reported
+    pub fn find_pending_function(&self, file: &str, line_num: u64) -> Option<FunctionName> {
+        self.breakpoints
+            .iter()
+            .filter(|bkpt| bkpt.status == BreakpointStatus::Pending)
+            .find(|bkpt| match (&bkpt.function, &bkpt.file_location) {
+                (Some(_), Some(fl)) => fl.line_num == line_num && is_same_file(&fl.name, file),
+                _ => false,
+            })
+            .and_then(|bkpt| bkpt.function.clone())
+    }
+
+    /// The debugger's IDs for all breakpoints that have been set in the debugger
+    pub fn native_ids(&self) -> Vec<String> {
+        self.breakpoints
+            .iter()
+            .filter_map(|bkpt| bkpt.native_id.clone())
+            .collect()
+    }
+
+    /// Mark every breakpoint as pending again, for when the debugger has been restarted and
+    /// needs them all setting again
+    pub fn reset(&mut self) {
+        for bkpt in self.breakpoints.iter_mut() {
+            bkpt.status = BreakpointStatus::Pending;
+            bkpt.native_id = None;
+        }
+    }
+
+    /// All breakpoints the debugger has yet to set at a location
+    ///
+    /// Breakpoints on functions are only here once the debugger has been restarted, they're set
+    /// again at the location the function was found at. Until then they're only pending in the
+    /// debugger itself.
+    pub fn pending(&self) -> Vec<Breakpoint> {
+        self.breakpoints
+            .iter()
+            .filter(|bkpt| bkpt.status == BreakpointStatus::Pending && bkpt.file_location.is_some())
+            .cloned()
+            .collect()
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!(self
+            .breakpoints
+            .iter()
+            .map(|bkpt| bkpt.to_json())
+            .collect::<Vec<serde_json::Value>>())
+    }
+}
+
+/// Part of a logpoint message, either literal text or an expression to be evaluated
+#[derive(Clone, Debug, PartialEq)]
+pub enum LogMessagePart {
+    Text(String),
+    Expression(String),
+}
+
+/// Split a logpoint message template into its parts
+///
+/// Expressions are surrounded by braces, e.g. `i is {i}`, and `{{` and `}}` give literal braces.
+/// Braces inside an expression are allowed as long as they balance.
+pub fn parse_log_message(message: &str) -> Vec<LogMessagePart> {
+    let mut parts = vec![];
+    let mut text = String::new();
+    let mut chars = message.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let mut expression = String::new();
+                let mut depth = 1;
+                for c in &mut chars {
+                    match c {
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        _ => {}
+                    }
+                    if depth == 0 {
+                        break;
+                    }
+                    expression.push(c);
+                }
+
+                // Unterminated expressions are just treated as text
+                if depth != 0 {
+                    text.push('{');
+                    text.push_str(&expression);
+                    continue;
+                }
+
+                if !text.is_empty() {
+                    parts.push(LogMessagePart::Text(text));
+                    text = String::new();
+                }
+                parts.push(LogMessagePart::Expression(expression));
+            }
+            _ => text.push(c),
+        }
+    }
+
+    if !text.is_empty() {
+        parts.push(LogMessagePart::Text(text));
+    }
+
+    parts
+}
+
+/// A frame in a backtrace, index 0 is the innermost frame
+///
+/// The file and line are unknown when there's no source for the frame and not all debuggers know
+/// the module.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StackFrame {
+    index: u64,
+    function: String,
+    file: Option<String>,
+    line: Option<u64>,
+    module: Option<String>,
+}
+
+impl StackFrame {
+    pub fn new(
+        index: u64,
+        function: String,
+        file: Option<String>,
+        line: Option<u64>,
+        module: Option<String>,
+    ) -> Self {
+        StackFrame {
+            index,
+            function,
+            file,
+            line,
+            module,
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "index": self.index,
+            "function": self.function,
+            "file": self.file,
+            "line": self.line,
+            "module": self.module,
+        })
+    }
+}
+
+/// A machine instruction in a disassembly, the current instruction is the one at the PC
+///
+/// The symbol and module the instruction is in and its offset from the start of the symbol are
+/// unknown when there are no symbols for it.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Instruction {
+    address: String,
+    instruction: String,
+    symbol: Option<String>,
+    module: Option<String>,
+    offset: Option<u64>,
+    current: bool,
+}
+
+impl Instruction {
+    pub fn new(
+        address: String,
+        instruction: String,
+        symbol: Option<String>,
+        module: Option<String>,
+        offset: Option<u64>,
+        current: bool,
+    ) -> Self {
+        Instruction {
+            address,
+            instruction,
+            symbol,
+            module,
+            offset,
+            current,
+        }
+    }
+
+    pub fn is_current(&self) -> bool {
+        self.current
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "address": self.address,
+            "instruction": self.instruction,
+            "symbol": self.symbol,
+            "module": self.module,
+            "offset": self.offset,
+            "current": self.current,
+        })
+    }
+}
+
+/// A thread of the program, the current thread is the one we're stopped in or have selected
+///
+/// The file and line are where the thread is when known.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ThreadDetails {
+    id: u64,
+    name: String,
+    current: bool,
+    file: Option<String>,
+    line: Option<u64>,
+}
+
+impl ThreadDetails {
+    pub fn new(
+        id: u64,
+        name: String,
+        current: bool,
+        file: Option<String>,
+        line: Option<u64>,
+    ) -> Self {
+        ThreadDetails {
+            id,
+            name,
+            current,
+            file,
+            line,
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "id": self.id,
+            "name": self.name,
+            "current": self.current,
+            "file": self.file,
+            "line": self.line,
+        })
+    }
+}
+
+/// The frame to select, either by index or relative to the currently selected frame
+///
+/// Up moves towards the outermost frame, so increases the index.
+#[derive(Clone, Copy, Deserialize, Debug, PartialEq, Eq, Hash)]
+pub enum FrameSelection {
+    Index(u64),
+    Up,
+    Down,
+}
+
+impl FrameSelection {
+    /// The index of the frame to select given the currently selected one, `None` when moving
+    /// down from the innermost frame
+    pub fn target(&self, current: u64) -> Option<u64> {
+        match self {
+            FrameSelection::Index(index) => Some(*index),
+            FrameSelection::Up => Some(current + 1),
+            FrameSelection::Down => current.checked_sub(1),
+        }
+    }
+}
+
 /// Variable name
 #[derive(Clone, Deserialize, Debug, PartialEq, Eq, Hash)]
 pub struct Variable {
@@ -49,6 +754,123 @@ impl Variable {
     }
 }
 
+/// A variable along with the type and value the debugger describes it with
+///
+/// Compound values have a variables reference that can be used to get their children, it's 0
+/// otherwise.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VariableDetails {
+    name: String,
+    type_: String,
+    value: String,
+    variables_reference: u64,
+}
+
+impl VariableDetails {
+    pub fn new(name: String, type_: String, value: String, variables_reference: u64) -> Self {
+        VariableDetails {
+            name,
+            type_,
+            value,
+            variables_reference,
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "name": self.name,
+            "type": self.type_,
+            "value": self.value,
+            "variablesReference": self.variables_reference,
+        })
+    }
+}
+
+/// Variables references handed out for compound values
+///
+/// Each debugger maps a reference to whatever it needs to get the children of the value, e.g. an
+/// object ID or an expression. References start at 1 and asking for a reference for the same
+/// thing again gives the same reference.
+#[derive(Debug)]
+pub struct VariableReferences {
+    references: Vec<String>,
+}
+
+impl VariableReferences {
+    pub fn new() -> Self {
+        VariableReferences { references: vec![] }
+    }
+
+    pub fn add(&mut self, native: String) -> u64 {
+        match self.references.iter().position(|r| r == &native) {
+            Some(i) => i as u64 + 1,
+            None => {
+                self.references.push(native);
+                self.references.len() as u64
+            }
+        }
+    }
+
+    pub fn get(&self, variables_reference: u64) -> Option<String> {
+        match variables_reference {
+            0 => None,
+            r => self.references.get(r as usize - 1).cloned(),
+        }
+    }
+}
+
+/// A running process to attach to, either by its PID or by its name
+#[derive(Clone, Deserialize, Debug, PartialEq)]
+pub enum AttachTarget {
+    Pid(u64),
+    Name(String),
+}
+
+/// What a watchpoint watches, either a variable or the address an expression evaluates to
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq, Hash)]
+pub enum WatchTarget {
+    Variable(Variable),
+    Expression(String),
+}
+
+/// Whether a watchpoint stops when the memory is read, written or both
+#[derive(Clone, Copy, Deserialize, Debug, PartialEq, Eq, Hash)]
+pub enum WatchMode {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+impl WatchMode {
+    pub fn from_str(mode: &str) -> Option<Self> {
+        match mode {
+            "read" => Some(WatchMode::Read),
+            "write" => Some(WatchMode::Write),
+            "readWrite" => Some(WatchMode::ReadWrite),
+            _ => None,
+        }
+    }
+}
+
+/// A group of registers, which registers are in each depends on the architecture
+#[derive(Clone, Copy, Deserialize, Debug, PartialEq, Eq, Hash)]
+pub enum RegisterGroup {
+    General,
+    Float,
+    Vector,
+}
+
+impl RegisterGroup {
+    pub fn from_str(group: &str) -> Option<Self> {
+        match group {
+            "general" => Some(RegisterGroup::General),
+            "float" => Some(RegisterGroup::Float),
+            "vector" => Some(RegisterGroup::Vector),
+            _ => None,
+        }
+    }
+}
+
 /// All debugger commands
 #[derive(Clone, Deserialize, Debug, PartialEq)]
 pub enum DebuggerCmd {
@@ -59,59 +881,446 @@ pub enum DebuggerCmd {
 #[derive(Clone, Deserialize, Debug, PartialEq)]
 pub enum DebuggerCmdV1 {
     Run,
-    Breakpoint(FileLocation),
+    Restart,
+    Kill,
+    Detach,
+    Attach(AttachTarget),
+    Breakpoint(FileLocation, BreakpointCondition),
+    Logpoint(FileLocation, String),
+    BreakpointFunction(FunctionName),
+    RemoveBreakpoint(FileLocation),
+    RemoveBreakpointFunction(FunctionName),
+    ListBreakpoints,
+    SetExceptionBreakpoints(ExceptionBreakpointMode),
+    Watch(WatchTarget, WatchMode),
     StepIn,
     StepOver,
+    StepOut,
+    StepInstruction,
+    NextInstruction,
+    RunToLine(FileLocation, bool),
     Continue,
+    Pause,
+    Backtrace,
+    Disassemble(Option<String>, Option<u64>),
+    Registers(Option<RegisterGroup>),
+    SetRegister(String, String),
+    SelectFrame(FrameSelection),
+    Threads,
+    SelectThread(u64),
+    Locals,
+    ExpandVariable(u64),
+    Evaluate(String, Option<u64>),
+    SetVariable(Variable, String),
     Print(Variable),
 }
 
 #[derive(Debug)]
 pub struct Debugger {
     debugger: Box<dyn DebuggerV1 + Send>,
+    breakpoints: Arc<Mutex<BreakpointRegistry>>,
+    // The process to attach to rather than launching the program when we run, from the command
+    // line or the last attach request
+    attach: Option<AttachTarget>,
+    source_map: SourceMap,
 }
 
 impl Debugger {
-    pub fn new(debugger: Box<dyn DebuggerV1 + Send>) -> Debugger {
-        Debugger { debugger }
+    pub fn new(
+        debugger: Box<dyn DebuggerV1 + Send>,
+        breakpoints: Arc<Mutex<BreakpointRegistry>>,
+        attach: Option<AttachTarget>,
+        source_map: SourceMap,
+    ) -> Debugger {
+        Debugger {
+            debugger,
+            breakpoints,
+            attach,
+            source_map,
+        }
     }
 
     pub fn stop(&mut self) {
         self.debugger.teardown();
     }
 
+    pub fn source_map(&self) -> &SourceMap {
+        &self.source_map
+    }
+
+    /// Change the mapping of source paths, it's used for all files in requests, responses and
+    /// notifications from now on and the debugger is told about it if it can use it too.
+    pub fn set_source_map(&mut self, source_map: SourceMap) {
+        set_source_map(source_map.clone());
+        self.debugger.set_source_map(&source_map);
+        self.source_map = source_map;
+    }
+
+    /// Handle the command, the locations of files in it are given to the debugger with the
+    /// debugger's paths and files in the response are given back with local paths
     pub fn handle_v1_cmd(
         &mut self,
         cmd: &DebuggerCmdV1,
         config: Arc<Mutex<Config>>,
     ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        let source_map = self.source_map.clone();
+
         match cmd {
-            DebuggerCmdV1::Run => self.debugger.run(config),
-            DebuggerCmdV1::Breakpoint(fl) => self.debugger.breakpoint(fl, config),
+            DebuggerCmdV1::Run => self.run(config),
+            DebuggerCmdV1::Restart => self.restart(config),
+            DebuggerCmdV1::Kill => self.debugger.kill(),
+            DebuggerCmdV1::Detach => self.debugger.detach(),
+            DebuggerCmdV1::Attach(target) => self.attach(target, config),
+            DebuggerCmdV1::Breakpoint(fl, bc) => {
+                self.breakpoint(&source_map.to_debugger_location(fl), bc, config)
+            }
+            DebuggerCmdV1::Logpoint(fl, msg) => {
+                self.logpoint(&source_map.to_debugger_location(fl), msg, config)
+            }
+            DebuggerCmdV1::BreakpointFunction(f) => self.debugger.breakpoint_function(f, config),
+            DebuggerCmdV1::RemoveBreakpoint(fl) => self
+                .debugger
+                .remove_breakpoint(&source_map.to_debugger_location(fl), config),
+            DebuggerCmdV1::RemoveBreakpointFunction(f) => {
+                self.debugger.remove_breakpoint_function(f, config)
+            }
+            DebuggerCmdV1::ListBreakpoints => self.list_breakpoints(),
+            DebuggerCmdV1::SetExceptionBreakpoints(mode) => {
+                self.debugger.set_exception_breakpoints(*mode, config)
+            }
+            DebuggerCmdV1::Watch(target, mode) => self.debugger.watch(target, *mode, config),
             DebuggerCmdV1::StepIn => self.debugger.step_in(),
             DebuggerCmdV1::StepOver => self.debugger.step_over(),
+            DebuggerCmdV1::StepOut => self.debugger.step_out(config),
+            DebuggerCmdV1::StepInstruction => self.debugger.step_instruction(),
+            DebuggerCmdV1::NextInstruction => self.debugger.next_instruction(),
+            DebuggerCmdV1::RunToLine(fl, ignore_breakpoints) => self.debugger.run_to_line(
+                &source_map.to_debugger_location(fl),
+                *ignore_breakpoints,
+                config,
+            ),
             DebuggerCmdV1::Continue => self.debugger.continue_(),
+            DebuggerCmdV1::Pause => self.debugger.pause(),
+            DebuggerCmdV1::Backtrace => {
+                Box::new(self.debugger.backtrace(config).map(move |mut resp| {
+                    source_map.localise_files(&mut resp["frames"]);
+                    resp
+                }))
+            }
+            DebuggerCmdV1::Disassemble(address, count) => {
+                self.debugger
+                    .disassemble(address.as_ref().map(String::as_str), *count, config)
+            }
+            DebuggerCmdV1::Registers(group) => self.debugger.registers(*group, config),
+            DebuggerCmdV1::SetRegister(name, value) => {
+                self.debugger.set_register(name, value, config)
+            }
+            DebuggerCmdV1::SelectFrame(frame) => self.debugger.select_frame(*frame, config),
+            DebuggerCmdV1::Threads => {
+                Box::new(self.debugger.threads(config).map(move |mut resp| {
+                    source_map.localise_files(&mut resp["threads"]);
+                    resp
+                }))
+            }
+            DebuggerCmdV1::SelectThread(id) => self.debugger.select_thread(*id, config),
+            DebuggerCmdV1::Locals => self.debugger.locals(config),
+            DebuggerCmdV1::ExpandVariable(variables_reference) => {
+                self.debugger.expand_variable(*variables_reference, config)
+            }
+            DebuggerCmdV1::Evaluate(expression, frame) => {
+                self.debugger.evaluate(expression, *frame, config)
+            }
+            DebuggerCmdV1::SetVariable(variable, value) => {
+                self.debugger.set_variable(variable, value, config)
+            }
             DebuggerCmdV1::Print(v) => self.debugger.print(v, config),
         }
     }
+
+    /// Launch the program, or attach to the process if we were started to attach to one
+    fn run(
+        &mut self,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match &self.attach {
+            Some(target) => self.debugger.attach(target, config),
+            None => self.debugger.run(config),
+        }
+    }
+
+    /// Attach to the process, remembering it so we attach to it again rather than launching the
+    /// program when we next run
+    fn attach(
+        &mut self,
+        target: &AttachTarget,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        self.attach = Some(target.clone());
+        self.debugger.attach(target, config)
+    }
+
+    /// Restart the program, we don't know how to start a process we attached to though
+    fn restart(
+        &mut self,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match &self.attach {
+            Some(_) => {
+                log_msg(LogLevel::WARN, "Can't restart a process attached to");
+                Box::new(future::lazy(move || {
+                    let resp = serde_json::json!({"status":"ERROR"});
+                    Ok(resp)
+                }))
+            }
+            None => self.debugger.restart(config),
+        }
+    }
+
+    /// Register the breakpoint and ask the debugger to set it
+    ///
+    /// If the breakpoint is already registered we don't set it again, this happens when a client
+    /// reconnects and resends all its breakpoints.
+    fn breakpoint(
+        &mut self,
+        file_location: &FileLocation,
+        condition: &BreakpointCondition,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_existing_breakpoint(file_location, condition, None) {
+            Some(resp) => return Box::new(future::lazy(move || Ok(resp))),
+            None => {}
+        }
+
+        let id = self
+            .breakpoints
+            .lock()
+            .unwrap()
+            .add(file_location.clone(), condition.clone());
+
+        let f = self
+            .debugger
+            .breakpoint(file_location, condition, config)
+            .map(move |mut resp| {
+                resp["id"] = serde_json::json!(id);
+                resp
+            });
+
+        Box::new(f)
+    }
+
+    /// Register the logpoint and ask the debugger to set it, as with breakpoints an existing
+    /// logpoint isn't set again.
+    fn logpoint(
+        &mut self,
+        file_location: &FileLocation,
+        log_message: &str,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        let condition = BreakpointCondition::default();
+        match self.check_existing_breakpoint(file_location, &condition, Some(log_message)) {
+            Some(resp) => return Box::new(future::lazy(move || Ok(resp))),
+            None => {}
+        }
+
+        let id = self
+            .breakpoints
+            .lock()
+            .unwrap()
+            .add_logpoint(file_location.clone(), log_message.to_string());
+
+        let f = self
+            .debugger
+            .logpoint(file_location, log_message, config)
+            .map(move |mut resp| {
+                resp["id"] = serde_json::json!(id);
+                resp
+            });
+
+        Box::new(f)
+    }
+
+    /// Get the response for a breakpoint that's already registered at this location, if any
+    ///
+    /// Changing the condition or log message of an existing breakpoint requires removing it
+    /// first. Breakpoints that failed are tried again.
+    fn check_existing_breakpoint(
+        &self,
+        file_location: &FileLocation,
+        condition: &BreakpointCondition,
+        log_message: Option<&str>,
+    ) -> Option<serde_json::Value> {
+        let breakpoints = self.breakpoints.lock().unwrap();
+        let bkpt = breakpoints.get(file_location)?;
+
+        if bkpt.condition() != condition || bkpt.log_message() != log_message {
+            log_msg(
+                LogLevel::WARN,
+                &format!(
+                    "Breakpoint with a different condition already at {}:{}",
+                    file_location.name, file_location.line_num
+                ),
+            );
+            return Some(serde_json::json!({"status":"ERROR","id":bkpt.id()}));
+        }
+
+        match bkpt.status() {
+            BreakpointStatus::Pending => {
+                Some(serde_json::json!({"status":"PENDING","id":bkpt.id()}))
+            }
+            BreakpointStatus::Resolved | BreakpointStatus::Multiple => {
+                Some(serde_json::json!({"status":"OK","id":bkpt.id()}))
+            }
+            BreakpointStatus::Failed => None,
+        }
+    }
+
+    fn list_breakpoints(
+        &self,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        let mut breakpoints = self.breakpoints.lock().unwrap().to_json();
+        self.source_map.localise_files(&mut breakpoints);
+
+        Box::new(future::lazy(move || {
+            let resp = serde_json::json!({"status":"OK","breakpoints":breakpoints});
+            Ok(resp)
+        }))
+    }
 }
 
 /// Debugger trait that implements the basics
 pub trait DebuggerV1: Debug {
     fn setup(&mut self);
     fn teardown(&mut self);
+    fn set_source_map(&mut self, source_map: &SourceMap);
     fn run(
         &mut self,
         config: Arc<Mutex<Config>>,
     ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
+    fn restart(
+        &mut self,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
+    fn attach(
+        &mut self,
+        target: &AttachTarget,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
+    fn kill(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
+    fn detach(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
     fn breakpoint(
         &mut self,
         file_location: &FileLocation,
+        condition: &BreakpointCondition,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
+    fn logpoint(
+        &mut self,
+        file_location: &FileLocation,
+        log_message: &str,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
+    fn breakpoint_function(
+        &mut self,
+        function: &FunctionName,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
+    fn remove_breakpoint(
+        &mut self,
+        file_location: &FileLocation,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
+    fn remove_breakpoint_function(
+        &mut self,
+        function: &FunctionName,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
+    fn set_exception_breakpoints(
+        &mut self,
+        mode: ExceptionBreakpointMode,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
+    fn watch(
+        &mut self,
+        target: &WatchTarget,
+        mode: WatchMode,
         config: Arc<Mutex<Config>>,
     ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
     fn step_in(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
     fn step_over(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
+    fn step_out(
+        &mut self,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
+    fn step_instruction(
+        &mut self,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
+    fn next_instruction(
+        &mut self,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
+    fn run_to_line(
+        &mut self,
+        file_location: &FileLocation,
+        ignore_breakpoints: bool,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
     fn continue_(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
+    fn pause(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
+    fn backtrace(
+        &mut self,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
+    fn disassemble(
+        &mut self,
+        address: Option<&str>,
+        count: Option<u64>,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
+    fn registers(
+        &mut self,
+        group: Option<RegisterGroup>,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
+    fn set_register(
+        &mut self,
+        name: &str,
+        value: &str,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
+    fn select_frame(
+        &mut self,
+        frame: FrameSelection,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
+    fn threads(
+        &mut self,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
+    fn select_thread(
+        &mut self,
+        id: u64,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
+    fn locals(
+        &mut self,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
+    fn expand_variable(
+        &mut self,
+        variables_reference: u64,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
+    fn evaluate(
+        &mut self,
+        expression: &str,
+        frame: Option<u64>,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
+    fn set_variable(
+        &mut self,
+        variable: &Variable,
+        value: &str,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>;
     fn print(
         &mut self,
         variable: &Variable,
@@ -127,6 +1336,10 @@ pub fn get_debugger(
     debugger_cmd: Option<&str>,
     debugger_type: Option<&str>,
     run_cmd: Vec<String>,
+    attach: Option<AttachTarget>,
+    core: Option<String>,
+    remote: Option<String>,
+    source_map: SourceMap,
 ) -> Debugger {
     let debugger_type = match debugger_type {
         Some(s) => match s.to_ascii_lowercase().as_str() {
@@ -135,7 +1348,7 @@ pub fn get_debugger(
             "node" => DebuggerType::Node,
             _ => panic!("Couldn't understand debugger type {}", s),
         },
-        None => match get_debugger_type(&run_cmd[0]) {
+        None => match run_cmd.get(0).and_then(|cmd| get_debugger_type(cmd)) {
             Some(s) => s,
             None => match debugger_cmd {
                 Some(s) => match s {
@@ -161,15 +1374,49 @@ pub fn get_debugger(
         },
     };
 
+    match (&core, &debugger_type) {
+        (Some(_), DebuggerType::LLDB) | (None, _) => {}
+        (Some(_), _) => panic!("Core files can only be debugged with LLDB"),
+    }
+
+    match (&remote, &debugger_type) {
+        (Some(_), DebuggerType::Python) => panic!("Remote debugging isn't supported for Python"),
+        _ => {}
+    }
+
+    let breakpoints = Arc::new(Mutex::new(BreakpointRegistry::new()));
+
     let mut debugger: Box<dyn DebuggerV1 + Send> = match debugger_type {
-        DebuggerType::LLDB => Box::new(lldb::ImplDebugger::new(debugger_cmd, run_cmd)),
-        DebuggerType::Node => Box::new(node::ImplDebugger::new(debugger_cmd, run_cmd)),
-        DebuggerType::Python => Box::new(python::ImplDebugger::new(debugger_cmd, run_cmd)),
+        DebuggerType::LLDB => Box::new(lldb::ImplDebugger::new(
+            debugger_cmd,
+            run_cmd,
+            breakpoints.clone(),
+            core,
+            remote,
+        )),
+        DebuggerType::Node => Box::new(node::ImplDebugger::new(
+            debugger_cmd,
+            run_cmd,
+            breakpoints.clone(),
+            remote,
+        )),
+        DebuggerType::Python => Box::new(python::ImplDebugger::new(
+            debugger_cmd,
+            run_cmd,
+            breakpoints.clone(),
+        )),
     };
 
     debugger.setup();
 
-    Debugger::new(debugger)
+    let mut debugger = Debugger::new(debugger, breakpoints, attach, SourceMap::default());
+
+    match source_map.mappings().is_empty() {
+        true => {}
+        false => debugger.set_source_map(source_map),
+    }
+
+    debugger
 }
 
 /// Guesses the debugger type
@@ -219,3 +1466,255 @@ fn is_python(cmd: &str) -> bool {
 
     false
 }
+
+/// Checks whether two file names refer to the same file
+///
+/// Debuggers tend to report full paths whereas clients can request relative ones.
+fn is_same_file(a: &str, b: &str) -> bool {
+    if a == b {
+        return true;
+    }
+
+    match (Path::new(a).canonicalize(), Path::new(b).canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse_log_message, BreakpointCondition, BreakpointRegistry, BreakpointStatus, FileLocation,
+        FunctionName, LogMessagePart, SourceMap, VariableReferences,
+    };
+
+    #[test]
+    fn check_parse_log_message() {
+        assert_eq!(
+            parse_log_message("i is {i}, {{literal}} and {a[{b}]}{c}"),
+            vec![
+                LogMessagePart::Text("i is ".to_string()),
+                LogMessagePart::Expression("i".to_string()),
+                LogMessagePart::Text(", {literal} and ".to_string()),
+                LogMessagePart::Expression("a[{b}]".to_string()),
+                LogMessagePart::Expression("c".to_string()),
+            ]
+        );
+        assert_eq!(
+            parse_log_message("unterminated {i"),
+            vec![LogMessagePart::Text("unterminated {i".to_string())]
+        );
+    }
+
+    #[test]
+    fn check_source_map() {
+        let source_map = SourceMap::new(vec![
+            ("/build/src".to_string(), "/home/me/project".to_string()),
+            ("/app/".to_string(), "/home/me/app/".to_string()),
+        ]);
+
+        assert_eq!(
+            source_map.to_local("/build/src/foo.c"),
+            "/home/me/project/foo.c".to_string()
+        );
+        assert_eq!(
+            source_map.to_local("/app/lib/index.js"),
+            "/home/me/app/lib/index.js".to_string()
+        );
+        assert_eq!(
+            source_map.to_local("/build/srcs/foo.c"),
+            "/build/srcs/foo.c".to_string()
+        );
+        assert_eq!(
+            source_map.to_debugger("/home/me/project/foo.c"),
+            "/build/src/foo.c".to_string()
+        );
+        assert_eq!(
+            source_map
+                .to_debugger_location(&FileLocation::new("/home/me/app/index.js".to_string(), 3)),
+            FileLocation::new("/app/index.js".to_string(), 3)
+        );
+
+        let mut frames = serde_json::json!([{"file":"/build/src/foo.c"},{"file":null}]);
+        source_map.localise_files(&mut frames);
+        assert_eq!(
+            frames,
+            serde_json::json!([{"file":"/home/me/project/foo.c"},{"file":null}])
+        );
+    }
+
+    #[test]
+    fn check_variable_references() {
+        let mut references = VariableReferences::new();
+
+        assert_eq!(references.add("a".to_string()), 1);
+        assert_eq!(references.add("a.b".to_string()), 2);
+        assert_eq!(references.add("a".to_string()), 1);
+
+        assert_eq!(references.get(2), Some("a.b".to_string()));
+        assert_eq!(references.get(0), None);
+        assert_eq!(references.get(3), None);
+    }
+
+    #[test]
+    fn check_breakpoint_registry_ids() {
+        let mut registry = BreakpointRegistry::new();
+        let id1 = registry.add(
+            FileLocation::new("test.c".to_string(), 12),
+            BreakpointCondition::default(),
+        );
+        let id2 = registry.add(
+            FileLocation::new("test.c".to_string(), 15),
+            BreakpointCondition::default(),
+        );
+        let id3 = registry.add(
+            FileLocation::new("test.c".to_string(), 12),
+            BreakpointCondition::default(),
+        );
+
+        assert_eq!(id1, 1);
+        assert_eq!(id2, 2);
+        assert_eq!(id3, 1);
+
+        registry.remove(&FileLocation::new("test.c".to_string(), 12));
+        let id4 = registry.add(
+            FileLocation::new("test.c".to_string(), 12),
+            BreakpointCondition::default(),
+        );
+
+        assert_eq!(id4, 3);
+    }
+
+    #[test]
+    fn check_breakpoint_registry_status() {
+        let mut registry = BreakpointRegistry::new();
+        let fl = FileLocation::new("test.c".to_string(), 12);
+        registry.add(fl.clone(), BreakpointCondition::default());
+        registry.add(
+            FileLocation::new("test.c".to_string(), 15),
+            BreakpointCondition::new(Some("i == 3".to_string()), Some(2)),
+        );
+
+        registry.set_native_id(&fl, "4".to_string());
+        registry.set_status_by_native_id("4", BreakpointStatus::Multiple);
+
+        assert_eq!(
+            registry.get(&fl).unwrap().status(),
+            &BreakpointStatus::Multiple
+        );
+        assert_eq!(registry.get(&fl).unwrap().native_id(), Some("4"));
+        assert_eq!(registry.pending().len(), 1);
+        assert_eq!(
+            registry.pending()[0].file_location(),
+            Some(&FileLocation::new("test.c".to_string(), 15))
+        );
+        assert_eq!(
+            registry.to_json(),
+            serde_json::json!([
+                {
+                    "id":1,
+                    "file":"test.c",
+                    "line":12,
+                    "condition":null,
+                    "hitCount":null,
+                    "logMessage":null,
+                    "function":null,
+                    "status":"MULTIPLE",
+                    "nativeId":"4",
+                },
+                {
+                    "id":2,
+                    "file":"test.c",
+                    "line":15,
+                    "condition":"i == 3",
+                    "hitCount":2,
+                    "logMessage":null,
+                    "function":null,
+                    "status":"PENDING",
+                    "nativeId":null,
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn check_breakpoint_registry_reset() {
+        let mut registry = BreakpointRegistry::new();
+        let fl = FileLocation::new("test.c".to_string(), 12);
+        registry.add(fl.clone(), BreakpointCondition::default());
+        registry.set_native_id(&fl, "4".to_string());
+        registry.set_status(&fl, BreakpointStatus::Resolved);
+        let function = FunctionName::new("a".to_string(), None, false);
+        let id = registry.add_function(
+            function.clone(),
+            Some(FileLocation::new("/src/test.c".to_string(), 3)),
+            "5".to_string(),
+            BreakpointStatus::Resolved,
+        );
+
+        assert_eq!(registry.pending().len(), 0);
+        assert_eq!(registry.find_pending_function("/src/test.c", 3), None);
+
+        registry.reset();
+
+        assert_eq!(registry.pending().len(), 2);
+        assert_eq!(registry.get(&fl).unwrap().native_id(), None);
+        assert_eq!(registry.get_function(&function).unwrap().id(), id);
+        assert_eq!(registry.native_ids().len(), 0);
+        assert_eq!(
+            registry.find_pending_function("/src/test.c", 3),
+            Some(function.clone())
+        );
+
+        registry.set_native_id_by_id(id, "6".to_string());
+        registry.set_status_by_id(id, BreakpointStatus::Resolved);
+
+        assert_eq!(registry.pending().len(), 1);
+        assert_eq!(
+            registry.get_function(&function).unwrap().native_id(),
+            Some("6")
+        );
+    }
+
+    #[test]
+    fn check_breakpoint_registry_functions() {
+        let mut registry = BreakpointRegistry::new();
+        let fl = FileLocation::new("test.c".to_string(), 12);
+        registry.add(
+            fl.clone(),
+            BreakpointCondition::new(Some("i == 3".to_string()), None),
+        );
+
+        let function = FunctionName::new("a".to_string(), None, false);
+        let id = registry.add_function(
+            function.clone(),
+            Some(fl.clone()),
+            "2".to_string(),
+            BreakpointStatus::Resolved,
+        );
+        assert_eq!(id, 2);
+        assert_eq!(
+            registry.get(&fl).unwrap().condition(),
+            &BreakpointCondition::new(Some("i == 3".to_string()), None)
+        );
+
+        let regex = FunctionName::new("^b".to_string(), None, true);
+        let id = registry.add_function(
+            regex.clone(),
+            None,
+            "3".to_string(),
+            BreakpointStatus::Multiple,
+        );
+        assert_eq!(id, 3);
+        assert_eq!(
+            registry.get_function(&regex).unwrap().native_id(),
+            Some("3")
+        );
+        assert_eq!(registry.pending().len(), 1);
+
+        registry.remove(&fl);
+        assert_eq!(registry.get_function(&function).unwrap().id(), 2);
+        assert_eq!(registry.remove_function(&regex).unwrap().id(), 3);
+        assert_eq!(registry.get_function(&regex), None);
+    }
+}
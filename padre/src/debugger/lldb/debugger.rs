@@ -8,24 +8,58 @@ use std::process::exit;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-use super::process::{Event, LLDBProcess, Listener};
+use super::process::{Event, LLDBProcess, Listener, VariableValue};
 use crate::config::Config;
-use crate::debugger::{DebuggerV1, FileLocation, Variable};
-use crate::notifier::{log_msg, LogLevel};
+use crate::debugger::{
+    parse_log_message, AttachTarget, BreakpointCondition, BreakpointRegistry, BreakpointStatus,
+    DebuggerV1, ExceptionBreakpointMode, FileLocation, FrameSelection, FunctionName, Instruction,
+    LogMessagePart, RegisterGroup, SourceMap, Variable, VariableDetails, VariableReferences,
+    WatchMode, WatchTarget,
+};
+use crate::notifier::{breakpoint_removed, log_msg, LogLevel};
 
 use bytes::Bytes;
+use regex::Regex;
 use tokio::prelude::*;
 use tokio::sync::mpsc;
 
+// The number of instructions to disassemble from an address when not told how many
+const DEFAULT_DISASSEMBLE_COUNT: u64 = 20;
+
 #[derive(Debug)]
 pub struct ImplDebugger {
     process: Arc<Mutex<LLDBProcess>>,
+    breakpoints: Arc<Mutex<BreakpointRegistry>>,
+    exception_mode: ExceptionBreakpointMode,
+    variable_references: Arc<Mutex<VariableReferences>>,
+    // Whether we're debugging a core file so there's nothing to run
+    core_mode: bool,
+    // The host and port of the `lldb-server` or `gdbserver` to connect to rather than launching
+    // the program locally
+    remote: Option<String>,
 }
 
 impl ImplDebugger {
-    pub fn new(debugger_cmd: String, run_cmd: Vec<String>) -> ImplDebugger {
+    pub fn new(
+        debugger_cmd: String,
+        run_cmd: Vec<String>,
+        breakpoints: Arc<Mutex<BreakpointRegistry>>,
+        core: Option<String>,
+        remote: Option<String>,
+    ) -> ImplDebugger {
+        let core_mode = core.is_some();
         ImplDebugger {
-            process: Arc::new(Mutex::new(LLDBProcess::new(debugger_cmd, run_cmd))),
+            process: Arc::new(Mutex::new(LLDBProcess::new(
+                debugger_cmd,
+                run_cmd,
+                breakpoints.clone(),
+                core,
+            ))),
+            breakpoints,
+            exception_mode: ExceptionBreakpointMode::None,
+            variable_references: Arc::new(Mutex::new(VariableReferences::new())),
+            core_mode,
+            remote,
         }
     }
 }
@@ -43,6 +77,7 @@ impl DebuggerV1 for ImplDebugger {
             .add_listener(Listener::LLDBLaunched, tx);
 
         let process = self.process.clone();
+        let core_mode = self.core_mode;
 
         tokio::spawn(
             rx.take(1)
@@ -51,7 +86,13 @@ impl DebuggerV1 for ImplDebugger {
                         Event::LLDBLaunched => {
                             process.lock().unwrap().write_stdin(Bytes::from(&b"settings set stop-line-count-after 0\n"[..]));
                             process.lock().unwrap().write_stdin(Bytes::from(&b"settings set stop-line-count-before 0\n"[..]));
-                            process.lock().unwrap().write_stdin(Bytes::from(&b"settings set frame-format frame #${frame.index}{ at ${line.file.fullpath}:${line.number}}\\n\n"[..]));
+                            process.lock().unwrap().write_stdin(Bytes::from(&b"settings set frame-format frame #${frame.index}{ at ${line.file.fullpath}:${line.number}}{ in ${function.name}}{ from ${module.file.basename}}{ pc ${frame.pc}}\\n\n"[..]));
+                            process.lock().unwrap().write_stdin(Bytes::from(&b"settings set thread-format thread #${thread.index}: tid = ${thread.id%tid}{, name = '${thread.name}'}{ at ${line.file.fullpath}:${line.number}}\\n\n"[..]));
+                            // Report where the core file's process stopped like we do when a
+                            // running process stops
+                            if core_mode {
+                                process.lock().unwrap().write_stdin(Bytes::from(&b"process status\n"[..]));
+                            }
                         }
                         _ => unreachable!()
                     }
@@ -70,11 +111,42 @@ impl DebuggerV1 for ImplDebugger {
         exit(0);
     }
 
+    /// Set LLDB's own source map too so that it can find the source files it lists
+    fn set_source_map(&mut self, source_map: &SourceMap) {
+        let stmt = match source_map.mappings().is_empty() {
+            true => "settings clear target.source-map\n".to_string(),
+            false => {
+                let mappings: Vec<String> = source_map
+                    .mappings()
+                    .iter()
+                    .map(|(debugger_prefix, local_prefix)| {
+                        format!(
+                            "\"{}\" \"{}\"",
+                            escape_double_quotes(debugger_prefix),
+                            escape_double_quotes(local_prefix)
+                        )
+                    })
+                    .collect();
+                format!("settings set target.source-map {}\n", mappings.join(" "))
+            }
+        };
+
+        self.process.lock().unwrap().write_stdin(Bytes::from(stmt));
+    }
+
     fn run(
         &mut self,
         config: Arc<Mutex<Config>>,
     ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
-        log_msg(LogLevel::INFO, "Launching process");
+        match self.check_core_mode() {
+            Some(f) => return f,
+            _ => {}
+        }
+
+        match &self.remote {
+            Some(remote) => log_msg(LogLevel::INFO, &format!("Connecting to {}", remote)),
+            None => log_msg(LogLevel::INFO, "Launching process"),
+        };
 
         let (tx, rx) = mpsc::channel(1);
 
@@ -84,6 +156,8 @@ impl DebuggerV1 for ImplDebugger {
             .add_listener(Listener::Breakpoint, tx);
 
         let process = self.process.clone();
+        let process2 = self.process.clone();
+        let remote = self.remote.clone();
 
         let f = rx
             .take(1)
@@ -92,7 +166,7 @@ impl DebuggerV1 for ImplDebugger {
                 let lldb_output = lldb_output.0.unwrap();
 
                 match lldb_output {
-                    Event::BreakpointSet(_) | Event::BreakpointMultiple => {}
+                    Event::BreakpointSet(_, _) | Event::BreakpointMultiple(_) => {}
                     _ => {
                         panic!("Don't understand output {:?}", lldb_output);
                     }
@@ -103,15 +177,18 @@ impl DebuggerV1 for ImplDebugger {
             .and_then(move |_| {
                 let (tx, rx) = mpsc::channel(1);
 
-                process
-                    .lock()
-                    .unwrap()
-                    .add_listener(Listener::ProcessLaunched, tx);
+                // The server has already started the program and stopped it when we connect
+                let (listener, stmt) = match remote {
+                    Some(remote) => (
+                        Listener::ProcessAttached,
+                        format!("gdb-remote {}\n", remote),
+                    ),
+                    None => (Listener::ProcessLaunched, "process launch\n".to_string()),
+                };
 
-                process
-                    .lock()
-                    .unwrap()
-                    .write_stdin(Bytes::from("process launch\n"));
+                process.lock().unwrap().add_listener(listener, tx);
+
+                process.lock().unwrap().write_stdin(Bytes::from(stmt));
 
                 rx.take(1).into_future()
             })
@@ -127,6 +204,18 @@ impl DebuggerV1 for ImplDebugger {
                 Event::ProcessLaunched(pid) => {
                     serde_json::json!({"status":"OK","pid":pid.to_string()})
                 }
+                // Carry on to `main` as we would when launching locally
+                Event::ProcessAttached(pid) => {
+                    process2
+                        .lock()
+                        .unwrap()
+                        .write_stdin(Bytes::from("thread continue\n"));
+                    serde_json::json!({"status":"OK","pid":pid.to_string()})
+                }
+                Event::AttachFailed(msg) => {
+                    log_msg(LogLevel::WARN, &msg);
+                    serde_json::json!({"status":"ERROR"})
+                }
                 _ => unreachable!(),
             })
             .map_err(|e| {
@@ -141,9 +230,75 @@ impl DebuggerV1 for ImplDebugger {
         Box::new(f)
     }
 
+    /// Kill the process and launch it again, LLDB keeps the breakpoints in the target so they're
+    /// all set again when it launches.
+    fn restart(
+        &mut self,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_core_mode() {
+            Some(f) => return f,
+            _ => {}
+        }
+
+        match self.remote {
+            Some(_) => {
+                log_msg(LogLevel::WARN, "Can't restart a remote process");
+                return Box::new(future::lazy(move || {
+                    let resp = serde_json::json!({"status":"ERROR"});
+                    Ok(resp)
+                }));
+            }
+            None => {}
+        }
+
+        if !self.process.lock().unwrap().is_process_running() {
+            return self.run(config);
+        }
+
+        log_msg(LogLevel::INFO, "Restarting process");
+
+        let (tx, rx) = mpsc::channel(1);
+
+        self.process
+            .lock()
+            .unwrap()
+            .add_listener(Listener::ProcessLaunched, tx);
+
+        let f = rx
+            .take(1)
+            .into_future()
+            .timeout(Duration::new(
+                config
+                    .lock()
+                    .unwrap()
+                    .get_config("ProcessSpawnTimeout")
+                    .unwrap() as u64,
+                0,
+            ))
+            .map(move |event| match event.0.unwrap() {
+                Event::ProcessLaunched(pid) => {
+                    serde_json::json!({"status":"OK","pid":pid.to_string()})
+                }
+                _ => unreachable!(),
+            })
+            .map_err(|e| {
+                eprintln!("Reading stdin error {:?}", e);
+                io::Error::new(io::ErrorKind::Other, "Timed out spawning process")
+            });
+
+        self.process
+            .lock()
+            .unwrap()
+            .write_stdin(Bytes::from("process kill\nprocess launch\n"));
+
+        Box::new(f)
+    }
+
     fn breakpoint(
         &mut self,
         file_location: &FileLocation,
+        condition: &BreakpointCondition,
         config: Arc<Mutex<Config>>,
     ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
         log_msg(
@@ -154,6 +309,83 @@ impl DebuggerV1 for ImplDebugger {
             ),
         );
 
+        let mut stmt = format!(
+            "breakpoint set --file {} --line {}",
+            file_location.name, file_location.line_num
+        );
+
+        match &condition.condition {
+            Some(c) => stmt += &format!(" --condition \"{}\"", escape_double_quotes(c)),
+            None => {}
+        }
+
+        // LLDB counts the number of hits to skip rather than the one to stop on
+        match condition.hit_count {
+            Some(n) => stmt += &format!(" --ignore-count {}", n - 1),
+            None => {}
+        }
+
+        stmt += "\n";
+
+        self.set_breakpoint(file_location, stmt, config)
+    }
+
+    fn logpoint(
+        &mut self,
+        file_location: &FileLocation,
+        log_message: &str,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        log_msg(
+            LogLevel::INFO,
+            &format!(
+                "Setting logpoint in file {} at line number {}",
+                file_location.name, file_location.line_num
+            ),
+        );
+
+        // Print the message from a Python breakpoint command, without a breakpoint ID the
+        // command is added to the breakpoint we've just set.
+        let mut message = vec![serde_json::json!(format!(
+            "PADRE logpoint {}:{}: ",
+            file_location.name, file_location.line_num
+        ))
+        .to_string()];
+
+        for part in parse_log_message(log_message) {
+            match part {
+                LogMessagePart::Text(t) => message.push(serde_json::json!(t).to_string()),
+                LogMessagePart::Expression(e) => message.push(format!(
+                    "(lambda v: v.GetSummary() or v.GetValue() or str(v))\
+                     (frame.EvaluateExpression({}))",
+                    serde_json::json!(e)
+                )),
+            }
+        }
+
+        let stmt = format!(
+            "breakpoint set --file {} --line {} --auto-continue true\n\
+             breakpoint command add --script-type python\n\
+             print({})\n\
+             DONE\n",
+            file_location.name,
+            file_location.line_num,
+            message.join(" + ")
+        );
+
+        self.set_breakpoint(file_location, stmt, config)
+    }
+
+    fn breakpoint_function(
+        &mut self,
+        function: &FunctionName,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        log_msg(
+            LogLevel::INFO,
+            &format!("Setting breakpoint on function {}", function.name),
+        );
+
         let (tx, rx) = mpsc::channel(1);
 
         self.process
@@ -161,6 +393,9 @@ impl DebuggerV1 for ImplDebugger {
             .unwrap()
             .add_listener(Listener::Breakpoint, tx);
 
+        let breakpoints = self.breakpoints.clone();
+        let function2 = function.clone();
+
         let f = rx
             .take(1)
             .into_future()
@@ -172,54 +407,157 @@ impl DebuggerV1 for ImplDebugger {
                     .unwrap() as u64,
                 0,
             ))
-            .map(move |event| match event.0.unwrap() {
-                Event::BreakpointSet(_) => serde_json::json!({"status":"OK"}),
-                Event::BreakpointPending => serde_json::json!({"status":"PENDING"}),
-                _ => unreachable!(),
+            .map(move |event| {
+                // Only breakpoints with a single location have a location we can track
+                let (native_id, file_location, status) = match event.0.unwrap() {
+                    Event::BreakpointSet(id, file_location) => {
+                        (id, Some(file_location), BreakpointStatus::Resolved)
+                    }
+                    Event::BreakpointMultiple(id) => (id, None, BreakpointStatus::Multiple),
+                    Event::BreakpointPending(id) => (id, None, BreakpointStatus::Pending),
+                    _ => unreachable!(),
+                };
+
+                let id = breakpoints.lock().unwrap().add_function(
+                    function2,
+                    file_location,
+                    native_id.to_string(),
+                    status.clone(),
+                );
+
+                match status {
+                    BreakpointStatus::Pending => serde_json::json!({"status":"PENDING","id":id}),
+                    _ => serde_json::json!({"status":"OK","id":id}),
+                }
             })
-            .map_err(|e| {
+            .map_err(move |e| {
                 eprintln!("Reading stdin error {:?}", e);
                 io::Error::new(io::ErrorKind::Other, "Timed out setting breakpoint")
             });
 
-        let stmt = format!(
-            "breakpoint set --file {} --line {}\n",
-            file_location.name, file_location.line_num
-        );
+        let stmt = break_function_stmt(function);
 
         self.process.lock().unwrap().write_stdin(Bytes::from(stmt));
 
         Box::new(f)
     }
 
-    fn step_in(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
-        self.step("step-in")
-    }
+    fn remove_breakpoint(
+        &mut self,
+        file_location: &FileLocation,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        log_msg(
+            LogLevel::INFO,
+            &format!(
+                "Removing breakpoint in file {} at line number {}",
+                file_location.name, file_location.line_num
+            ),
+        );
 
-    fn step_over(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
-        self.step("step-over")
-    }
+        let id = match self
+            .breakpoints
+            .lock()
+            .unwrap()
+            .get(file_location)
+            .and_then(|bkpt| bkpt.native_id().map(|x| x.to_string()))
+        {
+            Some(id) => id,
+            None => {
+                log_msg(
+                    LogLevel::WARN,
+                    &format!(
+                        "No breakpoint found in file {} at line number {}",
+                        file_location.name, file_location.line_num
+                    ),
+                );
+                return Box::new(future::lazy(move || {
+                    let resp = serde_json::json!({"status":"ERROR"});
+                    Ok(resp)
+                }));
+            }
+        };
 
-    fn continue_(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
-        self.step("continue")
+        let (tx, rx) = mpsc::channel(1);
+
+        self.process
+            .lock()
+            .unwrap()
+            .add_listener(Listener::BreakpointRemoved, tx);
+
+        let breakpoints = self.breakpoints.clone();
+        let location = file_location.clone();
+
+        let f = rx
+            .take(1)
+            .into_future()
+            .timeout(Duration::new(
+                config
+                    .lock()
+                    .unwrap()
+                    .get_config("BreakpointTimeout")
+                    .unwrap() as u64,
+                0,
+            ))
+            .map(move |event| match event.0.unwrap() {
+                Event::BreakpointRemoved(_) => {
+                    breakpoints.lock().unwrap().remove(&location);
+                    breakpoint_removed(&location.name, location.line_num);
+                    serde_json::json!({"status":"OK"})
+                }
+                _ => unreachable!(),
+            })
+            .map_err(|e| {
+                eprintln!("Reading stdin error {:?}", e);
+                io::Error::new(io::ErrorKind::Other, "Timed out removing breakpoint")
+            });
+
+        let stmt = format!("breakpoint delete {}\n", id);
+
+        self.process.lock().unwrap().write_stdin(Bytes::from(stmt));
+
+        Box::new(f)
     }
 
-    fn print(
+    fn remove_breakpoint_function(
         &mut self,
-        variable: &Variable,
+        function: &FunctionName,
         config: Arc<Mutex<Config>>,
     ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
-        match self.check_process() {
-            Some(f) => return f,
-            _ => {}
-        }
+        log_msg(
+            LogLevel::INFO,
+            &format!("Removing breakpoint on function {}", function.name),
+        );
+
+        let id = match self
+            .breakpoints
+            .lock()
+            .unwrap()
+            .get_function(function)
+            .and_then(|bkpt| bkpt.native_id().map(|x| x.to_string()))
+        {
+            Some(id) => id,
+            None => {
+                log_msg(
+                    LogLevel::WARN,
+                    &format!("No breakpoint found on function {}", function.name),
+                );
+                return Box::new(future::lazy(move || {
+                    let resp = serde_json::json!({"status":"ERROR"});
+                    Ok(resp)
+                }));
+            }
+        };
 
         let (tx, rx) = mpsc::channel(1);
 
         self.process
             .lock()
             .unwrap()
-            .add_listener(Listener::PrintVariable, tx);
+            .add_listener(Listener::BreakpointRemoved, tx);
+
+        let breakpoints = self.breakpoints.clone();
+        let function = function.clone();
 
         let f = rx
             .take(1)
@@ -228,75 +566,1684 @@ impl DebuggerV1 for ImplDebugger {
                 config
                     .lock()
                     .unwrap()
-                    .get_config("PrintVariableTimeout")
+                    .get_config("BreakpointTimeout")
                     .unwrap() as u64,
                 0,
             ))
             .map(move |event| match event.0.unwrap() {
-                Event::PrintVariable(variable, value) => serde_json::json!({
-                    "status": "OK",
-                    "variable": variable.name,
-                    "value": value.value(),
-                    "type": value.type_()
-                }),
-                Event::VariableNotFound(variable) => {
-                    log_msg(
-                        LogLevel::WARN,
-                        &format!("variable '{}' doesn't exist here", variable.name),
-                    );
-                    serde_json::json!({"status":"ERROR"})
+                Event::BreakpointRemoved(_) => {
+                    let bkpt = breakpoints.lock().unwrap().remove_function(&function);
+                    match bkpt.as_ref().and_then(|bkpt| bkpt.file_location()) {
+                        Some(fl) => breakpoint_removed(&fl.name, fl.line_num),
+                        None => {}
+                    }
+                    serde_json::json!({"status":"OK"})
                 }
                 _ => unreachable!(),
             })
             .map_err(|e| {
                 eprintln!("Reading stdin error {:?}", e);
-                io::Error::new(io::ErrorKind::Other, "Timed out printing variable")
+                io::Error::new(io::ErrorKind::Other, "Timed out removing breakpoint")
             });
 
-        let stmt = format!("frame variable {}\n", variable.name);
+        let stmt = format!("breakpoint delete {}\n", id);
 
         self.process.lock().unwrap().write_stdin(Bytes::from(stmt));
 
         Box::new(f)
     }
-}
 
-impl ImplDebugger {
-    fn step(
+    /// Set breakpoints on C++ exceptions and Rust panics
+    ///
+    /// LLDB can't tell whether a C++ exception will be caught when it's thrown so for uncaught
+    /// exceptions we stop in `std::terminate` instead, which is called before the stack is
+    /// unwound. Rust panics always stop in `rust_panic` as they're very rarely caught.
+    fn set_exception_breakpoints(
         &mut self,
-        kind: &str,
+        mode: ExceptionBreakpointMode,
+        _: Arc<Mutex<Config>>,
     ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
-        match self.check_process() {
-            Some(f) => return f,
-            _ => {}
+        log_msg(
+            LogLevel::INFO,
+            &format!("Setting exception breakpoints to {:?}", mode),
+        );
+
+        let mut stmt = match self.exception_mode {
+            ExceptionBreakpointMode::None => "".to_string(),
+            _ => "breakpoint delete padre_exception\n".to_string(),
+        };
+
+        match mode {
+            ExceptionBreakpointMode::None => {}
+            ExceptionBreakpointMode::Uncaught => {
+                stmt +=
+                    &exception_breakpoint_stmt("--name std::terminate", "C++ exception uncaught");
+                stmt += &exception_breakpoint_stmt("--name rust_panic", "Rust panic");
+            }
+            ExceptionBreakpointMode::All => {
+                stmt += &exception_breakpoint_stmt("-E c++", "C++ exception thrown");
+                stmt += &exception_breakpoint_stmt("--name rust_panic", "Rust panic");
+            }
         }
 
-        let stmt = format!("thread {}\n", kind);
+        self.exception_mode = mode;
 
         self.process.lock().unwrap().write_stdin(Bytes::from(stmt));
 
-        let f = future::lazy(move || {
+        Box::new(future::lazy(move || {
             let resp = serde_json::json!({"status":"OK"});
             Ok(resp)
-        });
-
-        Box::new(f)
+        }))
     }
 
-    fn check_process(
+    fn watch(
         &mut self,
-    ) -> Option<Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>> {
-        match self.process.lock().unwrap().is_process_running() {
-            false => {
-                log_msg(LogLevel::WARN, "No process running");
-                let f = future::lazy(move || {
-                    let resp = serde_json::json!({"status":"ERROR"});
-                    Ok(resp)
-                });
-
-                Some(Box::new(f))
-            }
-            true => None,
+        target: &WatchTarget,
+        mode: WatchMode,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_process() {
+            Some(f) => return f,
+            _ => {}
         }
+
+        let (tx, rx) = mpsc::channel(1);
+
+        self.process
+            .lock()
+            .unwrap()
+            .add_listener(Listener::Watchpoint, tx);
+
+        let f = rx
+            .take(1)
+            .into_future()
+            .timeout(Duration::new(
+                config
+                    .lock()
+                    .unwrap()
+                    .get_config("BreakpointTimeout")
+                    .unwrap() as u64,
+                0,
+            ))
+            .map(move |event| match event.0.unwrap() {
+                Event::WatchpointSet(id) => serde_json::json!({"status":"OK","id":id}),
+                Event::WatchpointFailed(msg) => {
+                    log_msg(LogLevel::WARN, &format!("Can't set watchpoint: {}", msg));
+                    serde_json::json!({"status":"ERROR"})
+                }
+                _ => unreachable!(),
+            })
+            .map_err(|e| {
+                eprintln!("Reading stdin error {:?}", e);
+                io::Error::new(io::ErrorKind::Other, "Timed out setting watchpoint")
+            });
+
+        let mode = match mode {
+            WatchMode::Read => "read",
+            WatchMode::Write => "write",
+            WatchMode::ReadWrite => "read_write",
+        };
+
+        let stmt = match target {
+            WatchTarget::Variable(v) => {
+                log_msg(
+                    LogLevel::INFO,
+                    &format!("Setting watchpoint on variable {}", v.name),
+                );
+                format!("watchpoint set variable --watch {} {}\n", mode, v.name)
+            }
+            WatchTarget::Expression(e) => {
+                log_msg(
+                    LogLevel::INFO,
+                    &format!("Setting watchpoint on expression {}", e),
+                );
+                format!("watchpoint set expression --watch {} -- {}\n", mode, e)
+            }
+        };
+
+        self.process.lock().unwrap().write_stdin(Bytes::from(stmt));
+
+        Box::new(f)
+    }
+
+    fn step_in(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        self.step("step-in")
+    }
+
+    fn step_over(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        self.step("step-over")
+    }
+
+    fn step_instruction(
+        &mut self,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        self.step("step-inst")
+    }
+
+    fn next_instruction(
+        &mut self,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        self.step("step-inst-over")
+    }
+
+    fn step_out(
+        &mut self,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_core_mode() {
+            Some(f) => return f,
+            _ => {}
+        }
+
+        match self.check_process() {
+            Some(f) => return f,
+            _ => {}
+        }
+
+        let (tx, rx) = mpsc::channel(1);
+
+        self.process
+            .lock()
+            .unwrap()
+            .add_listener(Listener::StepOut, tx);
+
+        let f = rx
+            .take(1)
+            .into_future()
+            .timeout(Duration::new(
+                config.lock().unwrap().get_config("StepOutTimeout").unwrap() as u64,
+                0,
+            ))
+            .map(move |event| match event.0.unwrap() {
+                Event::SteppedOut(Some(value)) => serde_json::json!({
+                    "status": "OK",
+                    "returnValue": value.value(),
+                    "returnType": value.type_()
+                }),
+                Event::SteppedOut(None) => serde_json::json!({"status":"OK"}),
+                _ => unreachable!(),
+            })
+            .map_err(|e| {
+                eprintln!("Reading stdin error {:?}", e);
+                io::Error::new(io::ErrorKind::Other, "Timed out stepping out")
+            });
+
+        self.process
+            .lock()
+            .unwrap()
+            .write_stdin(Bytes::from("thread step-out\n"));
+
+        Box::new(f)
+    }
+
+    /// Continue to a one-shot breakpoint, if we're ignoring breakpoints the client's breakpoints
+    /// are disabled until we next stop
+    ///
+    /// The one-shot breakpoint is set through LLDB's Python API so that LLDB doesn't report it
+    /// like the client's own breakpoints.
+    fn run_to_line(
+        &mut self,
+        file_location: &FileLocation,
+        ignore_breakpoints: bool,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_core_mode() {
+            Some(f) => return f,
+            _ => {}
+        }
+
+        match self.check_process() {
+            Some(f) => return f,
+            _ => {}
+        }
+
+        let (existing_id, ids) = {
+            let breakpoints = self.breakpoints.lock().unwrap();
+            let existing_id = breakpoints
+                .find_location(&file_location.name, file_location.line_num)
+                .and_then(|fl| breakpoints.get(&fl))
+                .and_then(|bkpt| bkpt.native_id().map(|id| id.to_string()));
+            let ids: Vec<String> = breakpoints
+                .native_ids()
+                .into_iter()
+                .filter(|id| Some(id) != existing_id.as_ref())
+                .collect();
+            (existing_id, ids)
+        };
+
+        let mut stmt = "".to_string();
+        let mut stmt_on_stop = "".to_string();
+
+        // Only the client's breakpoints are disabled, not our exception breakpoints
+        if ignore_breakpoints && !ids.is_empty() {
+            stmt += &format!("breakpoint disable {}\n", ids.join(" "));
+            stmt_on_stop += &format!("breakpoint enable {}\n", ids.join(" "));
+        }
+
+        match existing_id {
+            Some(_) => {}
+            None => {
+                stmt += &format!(
+                    "script padre_run_to_line = lldb.target.BreakpointCreateByLocation({}, {}); \
+                     padre_run_to_line.SetOneShot(True)\n",
+                    serde_json::json!(file_location.name),
+                    file_location.line_num
+                );
+                stmt_on_stop +=
+                    "script _ = lldb.target.BreakpointDelete(padre_run_to_line.GetID())\n";
+            }
+        }
+
+        stmt += "process continue\n";
+
+        let (tx, rx) = mpsc::channel(1);
+
+        self.process
+            .lock()
+            .unwrap()
+            .add_listener(Listener::ProcessStopped, tx);
+
+        let process = self.process.clone();
+
+        // Tidy up when we stop, whether or not we reached the line
+        tokio::spawn(
+            rx.take(1)
+                .for_each(move |_| {
+                    process
+                        .lock()
+                        .unwrap()
+                        .write_stdin(Bytes::from(stmt_on_stop.clone()));
+                    Ok(())
+                })
+                .map_err(|e| {
+                    eprintln!("Reading stdin error {:?}", e);
+                }),
+        );
+
+        self.process.lock().unwrap().write_stdin(Bytes::from(stmt));
+
+        let f = future::lazy(move || {
+            let resp = serde_json::json!({"status":"OK"});
+            Ok(resp)
+        });
+
+        Box::new(f)
+    }
+
+    /// Attach to a running process, LLDB stops it when it attaches and waits for a process with
+    /// the name to be launched when attaching by name
+    fn attach(
+        &mut self,
+        target: &AttachTarget,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_core_mode() {
+            Some(f) => return f,
+            _ => {}
+        }
+
+        let stmt = match target {
+            AttachTarget::Pid(pid) => {
+                log_msg(LogLevel::INFO, &format!("Attaching to process {}", pid));
+                format!("process attach --pid {}\n", pid)
+            }
+            AttachTarget::Name(name) => {
+                log_msg(
+                    LogLevel::INFO,
+                    &format!("Waiting to attach to process {}", name),
+                );
+                format!(
+                    "process attach --name \"{}\" --waitfor\n",
+                    escape_double_quotes(name)
+                )
+            }
+        };
+
+        let (tx, rx) = mpsc::channel(1);
+
+        self.process
+            .lock()
+            .unwrap()
+            .add_listener(Listener::ProcessAttached, tx);
+
+        let f = rx
+            .take(1)
+            .into_future()
+            .timeout(Duration::new(
+                config
+                    .lock()
+                    .unwrap()
+                    .get_config("ProcessSpawnTimeout")
+                    .unwrap() as u64,
+                0,
+            ))
+            .map(move |event| match event.0.unwrap() {
+                Event::ProcessAttached(pid) => {
+                    serde_json::json!({"status":"OK","pid":pid.to_string()})
+                }
+                Event::AttachFailed(msg) => {
+                    log_msg(LogLevel::WARN, &msg);
+                    serde_json::json!({"status":"ERROR"})
+                }
+                _ => unreachable!(),
+            })
+            .map_err(|e| {
+                eprintln!("Reading stdin error {:?}", e);
+                io::Error::new(io::ErrorKind::Other, "Timed out attaching to process")
+            });
+
+        self.process.lock().unwrap().write_stdin(Bytes::from(stmt));
+
+        Box::new(f)
+    }
+
+    /// Kill the process, LLDB reports it exiting as normal and it can be run again
+    fn kill(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        self.process_cmd("kill")
+    }
+
+    /// Detach from the process leaving it running, LLDB keeps the breakpoints in the target
+    fn detach(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        self.process_cmd("detach")
+    }
+
+    fn continue_(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        self.step("continue")
+    }
+
+    /// Interrupt the process through LLDB so it works for remote and attached processes too,
+    /// LLDB then reports where it stopped as normal
+    fn pause(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        self.process_cmd("interrupt")
+    }
+
+    /// Get the backtrace of the current thread, the frame format set up in `setup` includes
+    /// everything we need
+    fn backtrace(
+        &mut self,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_process() {
+            Some(f) => return f,
+            _ => {}
+        }
+
+        let (tx, rx) = mpsc::channel(1);
+
+        self.process
+            .lock()
+            .unwrap()
+            .add_listener(Listener::Backtrace, tx);
+
+        let process = self.process.clone();
+
+        let f = rx
+            .take(1)
+            .into_future()
+            .timeout(Duration::new(
+                config
+                    .lock()
+                    .unwrap()
+                    .get_config("PrintVariableTimeout")
+                    .unwrap() as u64,
+                0,
+            ))
+            .map(move |event| match event.0.unwrap() {
+                Event::Backtrace(frames) => {
+                    let frames: Vec<serde_json::Value> =
+                        frames.iter().map(|frame| frame.to_json()).collect();
+                    serde_json::json!({"status":"OK","frames":frames})
+                }
+                _ => unreachable!(),
+            })
+            .map_err(move |e| {
+                eprintln!("Reading stdin error {:?}", e);
+                process
+                    .lock()
+                    .unwrap()
+                    .remove_listener(&Listener::Backtrace);
+                io::Error::new(io::ErrorKind::Other, "Timed out getting backtrace")
+            });
+
+        // Print a marker afterwards so we know when we've got the whole backtrace
+        let stmt = "thread backtrace\nscript print(\"PADRE backtrace end\")\n";
+
+        self.process.lock().unwrap().write_stdin(Bytes::from(stmt));
+
+        Box::new(f)
+    }
+
+    fn disassemble(
+        &mut self,
+        address: Option<&str>,
+        count: Option<u64>,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_process() {
+            Some(f) => return f,
+            _ => {}
+        }
+
+        let (tx, rx) = mpsc::channel(1);
+
+        self.process
+            .lock()
+            .unwrap()
+            .add_listener(Listener::Disassemble, tx);
+
+        let process = self.process.clone();
+        let around_pc = match address {
+            Some(_) => None,
+            None => count,
+        };
+
+        let f = rx
+            .take(1)
+            .into_future()
+            .timeout(Duration::new(
+                config
+                    .lock()
+                    .unwrap()
+                    .get_config("PrintVariableTimeout")
+                    .unwrap() as u64,
+                0,
+            ))
+            .map(move |event| match event.0.unwrap() {
+                Event::Disassembly(instructions) => {
+                    let instructions = match around_pc {
+                        Some(count) => around_current(instructions, count),
+                        None => instructions,
+                    };
+                    let instructions: Vec<serde_json::Value> = instructions
+                        .iter()
+                        .map(|instruction| instruction.to_json())
+                        .collect();
+                    serde_json::json!({"status":"OK","instructions":instructions})
+                }
+                Event::DisassembleFailed(msg) => {
+                    log_msg(LogLevel::WARN, &format!("Can't disassemble: {}", msg));
+                    serde_json::json!({"status":"ERROR","error":msg})
+                }
+                _ => unreachable!(),
+            })
+            .map_err(move |e| {
+                eprintln!("Reading stdin error {:?}", e);
+                process
+                    .lock()
+                    .unwrap()
+                    .remove_listener(&Listener::Disassemble);
+                io::Error::new(io::ErrorKind::Other, "Timed out disassembling")
+            });
+
+        self.process
+            .lock()
+            .unwrap()
+            .write_stdin(Bytes::from(disassemble_stmt(address, count)));
+
+        Box::new(f)
+    }
+
+    fn registers(
+        &mut self,
+        group: Option<RegisterGroup>,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_process() {
+            Some(f) => return f,
+            _ => {}
+        }
+
+        let f = self
+            .read_registers(registers_stmt(), config)
+            .map(move |result| match result {
+                Ok(sets) => {
+                    let registers: Vec<serde_json::Value> = sets
+                        .iter()
+                        .filter(|(set, _)| match group {
+                            Some(group) => in_register_group(set, group),
+                            None => true,
+                        })
+                        .flat_map(|(set, registers)| {
+                            registers.iter().map(move |(name, value)| {
+                                serde_json::json!({
+                                    "name": name,
+                                    "value": value,
+                                    "format": register_format(value),
+                                    "set": set,
+                                })
+                            })
+                        })
+                        .collect();
+                    serde_json::json!({"status":"OK","registers":registers})
+                }
+                Err(error) => serde_json::json!({"status":"ERROR","error":error}),
+            });
+
+        Box::new(f)
+    }
+
+    /// Write the register and respond with its new value
+    fn set_register(
+        &mut self,
+        name: &str,
+        value: &str,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_core_mode() {
+            Some(f) => return f,
+            _ => {}
+        }
+
+        match self.check_process() {
+            Some(f) => return f,
+            _ => {}
+        }
+
+        let (tx, rx) = mpsc::channel(1);
+
+        self.process
+            .lock()
+            .unwrap()
+            .add_listener(Listener::WriteRegister, tx);
+
+        let process = self.process.clone();
+        let requested = name.to_string();
+
+        let f = rx
+            .take(1)
+            .into_future()
+            .timeout(Duration::new(
+                config
+                    .lock()
+                    .unwrap()
+                    .get_config("PrintVariableTimeout")
+                    .unwrap() as u64,
+                0,
+            ))
+            .map(move |event| match event.0.unwrap() {
+                Event::RegisterWritten(name, _) if name != requested => {
+                    log_msg(LogLevel::WARN, &format!("Expected to write {}", requested));
+                    serde_json::json!({"status":"ERROR"})
+                }
+                Event::RegisterWritten(name, value) => serde_json::json!({
+                    "status": "OK",
+                    "name": name,
+                    "format": register_format(&value),
+                    "value": value,
+                }),
+                Event::RegistersFailed(error) => {
+                    log_msg(LogLevel::WARN, &error);
+                    serde_json::json!({"status":"ERROR","error":error})
+                }
+                _ => unreachable!(),
+            })
+            .map_err(move |e| {
+                eprintln!("Reading stdin error {:?}", e);
+                process
+                    .lock()
+                    .unwrap()
+                    .remove_listener(&Listener::WriteRegister);
+                io::Error::new(io::ErrorKind::Other, "Timed out writing register")
+            });
+
+        self.process
+            .lock()
+            .unwrap()
+            .write_stdin(Bytes::from(set_register_stmt(name, value)));
+
+        Box::new(f)
+    }
+
+    fn select_frame(
+        &mut self,
+        frame: FrameSelection,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_process() {
+            Some(f) => return f,
+            _ => {}
+        }
+
+        let (tx, rx) = mpsc::channel(1);
+
+        self.process
+            .lock()
+            .unwrap()
+            .add_listener(Listener::SelectFrame, tx);
+
+        let process = self.process.clone();
+
+        let f = rx
+            .take(1)
+            .into_future()
+            .timeout(Duration::new(
+                config
+                    .lock()
+                    .unwrap()
+                    .get_config("PrintVariableTimeout")
+                    .unwrap() as u64,
+                0,
+            ))
+            .map(move |event| match event.0.unwrap() {
+                Event::FrameSelected(index) => serde_json::json!({"status":"OK","frame":index}),
+                Event::FrameNotFound(msg) => {
+                    log_msg(LogLevel::WARN, &format!("Can't select frame: {}", msg));
+                    serde_json::json!({"status":"ERROR"})
+                }
+                _ => unreachable!(),
+            })
+            .map_err(move |e| {
+                eprintln!("Reading stdin error {:?}", e);
+                process
+                    .lock()
+                    .unwrap()
+                    .remove_listener(&Listener::SelectFrame);
+                io::Error::new(io::ErrorKind::Other, "Timed out selecting frame")
+            });
+
+        let stmt = match frame {
+            FrameSelection::Index(index) => format!("frame select {}\n", index),
+            FrameSelection::Up => "up\n".to_string(),
+            FrameSelection::Down => "down\n".to_string(),
+        };
+
+        self.process.lock().unwrap().write_stdin(Bytes::from(stmt));
+
+        Box::new(f)
+    }
+
+    fn threads(
+        &mut self,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_process() {
+            Some(f) => return f,
+            _ => {}
+        }
+
+        let (tx, rx) = mpsc::channel(1);
+
+        self.process
+            .lock()
+            .unwrap()
+            .add_listener(Listener::Threads, tx);
+
+        let process = self.process.clone();
+
+        let f = rx
+            .take(1)
+            .into_future()
+            .timeout(Duration::new(
+                config
+                    .lock()
+                    .unwrap()
+                    .get_config("PrintVariableTimeout")
+                    .unwrap() as u64,
+                0,
+            ))
+            .map(move |event| match event.0.unwrap() {
+                Event::Threads(threads) => {
+                    let threads: Vec<serde_json::Value> =
+                        threads.iter().map(|thread| thread.to_json()).collect();
+                    serde_json::json!({"status":"OK","threads":threads})
+                }
+                _ => unreachable!(),
+            })
+            .map_err(move |e| {
+                eprintln!("Reading stdin error {:?}", e);
+                process.lock().unwrap().remove_listener(&Listener::Threads);
+                io::Error::new(io::ErrorKind::Other, "Timed out getting threads")
+            });
+
+        // As with backtraces print a marker so we know when we've got all the threads
+        let stmt = "thread list\nscript print(\"PADRE threads end\")\n";
+
+        self.process.lock().unwrap().write_stdin(Bytes::from(stmt));
+
+        Box::new(f)
+    }
+
+    fn select_thread(
+        &mut self,
+        id: u64,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_process() {
+            Some(f) => return f,
+            _ => {}
+        }
+
+        let (tx, rx) = mpsc::channel(1);
+
+        self.process
+            .lock()
+            .unwrap()
+            .add_listener(Listener::SelectThread, tx);
+
+        let process = self.process.clone();
+
+        let f = rx
+            .take(1)
+            .into_future()
+            .timeout(Duration::new(
+                config
+                    .lock()
+                    .unwrap()
+                    .get_config("PrintVariableTimeout")
+                    .unwrap() as u64,
+                0,
+            ))
+            .map(move |event| match event.0.unwrap() {
+                Event::ThreadSelected(id) => serde_json::json!({"status":"OK","thread":id}),
+                Event::ThreadNotFound(msg) => {
+                    log_msg(LogLevel::WARN, &format!("Can't select thread: {}", msg));
+                    serde_json::json!({"status":"ERROR"})
+                }
+                _ => unreachable!(),
+            })
+            .map_err(move |e| {
+                eprintln!("Reading stdin error {:?}", e);
+                process
+                    .lock()
+                    .unwrap()
+                    .remove_listener(&Listener::SelectThread);
+                io::Error::new(io::ErrorKind::Other, "Timed out selecting thread")
+            });
+
+        let stmt = format!("thread select {}\n", id);
+
+        self.process.lock().unwrap().write_stdin(Bytes::from(stmt));
+
+        Box::new(f)
+    }
+
+    fn locals(
+        &mut self,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_process() {
+            Some(f) => return f,
+            _ => {}
+        }
+
+        let f = self
+            .variables("frame variable", Listener::Locals, None, config)
+            .map(|locals| {
+                let locals: Vec<serde_json::Value> =
+                    locals.iter().map(|local| local.to_json()).collect();
+                serde_json::json!({"status":"OK","locals":locals})
+            });
+
+        Box::new(f)
+    }
+
+    /// Get the children of the variable one level deep, the reference is to the path of the
+    /// variable for `frame variable`
+    fn expand_variable(
+        &mut self,
+        variables_reference: u64,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_process() {
+            Some(f) => return f,
+            _ => {}
+        }
+
+        let path = match self
+            .variable_references
+            .lock()
+            .unwrap()
+            .get(variables_reference)
+        {
+            Some(path) => path,
+            None => {
+                log_msg(LogLevel::WARN, "No such variables reference");
+                return Box::new(future::lazy(move || {
+                    Ok(serde_json::json!({"status":"ERROR"}))
+                }));
+            }
+        };
+
+        let f = self
+            .variables(
+                &format!("frame variable -T -D 1 {}", path),
+                Listener::ExpandVariable,
+                Some(path),
+                config,
+            )
+            .map(|variables| {
+                let variables: Vec<serde_json::Value> = variables
+                    .iter()
+                    .map(|variable| variable.to_json())
+                    .collect();
+                serde_json::json!({"status":"OK","variables":variables})
+            });
+
+        Box::new(f)
+    }
+
+    fn evaluate(
+        &mut self,
+        expression: &str,
+        frame: Option<u64>,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_process() {
+            Some(f) => return f,
+            _ => {}
+        }
+
+        let expression = expression.to_string();
+        let variable_references = self.variable_references.clone();
+
+        // We can only expand what `frame variable` can print in the selected frame
+        let expandable = frame.is_none() && is_variable_path(&expression);
+
+        let f =
+            self.evaluate_expression(&expression, frame, config)
+                .map(move |result| match result {
+                    Ok(value) => {
+                        let variables_reference =
+                            match children_path(&expression, value.type_(), value.value()) {
+                                Some(path) if expandable => {
+                                    variable_references.lock().unwrap().add(path)
+                                }
+                                _ => 0,
+                            };
+                        serde_json::json!({
+                            "status": "OK",
+                            "expression": expression,
+                            "type": value.type_(),
+                            "value": value.value(),
+                            "variablesReference": variables_reference,
+                        })
+                    }
+                    Err(error) => serde_json::json!({"status":"ERROR","error":error}),
+                });
+
+        Box::new(f)
+    }
+
+    /// Assign the value with an expression, the value of the assignment is the variable's new
+    /// value
+    fn set_variable(
+        &mut self,
+        variable: &Variable,
+        value: &str,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_process() {
+            Some(f) => return f,
+            _ => {}
+        }
+
+        let variable = variable.name.clone();
+        let variable_references = self.variable_references.clone();
+
+        let f = self
+            .evaluate_expression(&format!("{} = {}", variable, value), None, config)
+            .map(move |result| match result {
+                Ok(value) => {
+                    let variables_reference =
+                        match children_path(&variable, value.type_(), value.value()) {
+                            Some(path) => variable_references.lock().unwrap().add(path),
+                            None => 0,
+                        };
+                    serde_json::json!({
+                        "status": "OK",
+                        "variable": variable,
+                        "type": value.type_(),
+                        "value": value.value(),
+                        "variablesReference": variables_reference,
+                    })
+                }
+                Err(error) => serde_json::json!({"status":"ERROR","error":error}),
+            });
+
+        Box::new(f)
+    }
+
+    fn print(
+        &mut self,
+        variable: &Variable,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_process() {
+            Some(f) => return f,
+            _ => {}
+        }
+
+        let (tx, rx) = mpsc::channel(1);
+
+        self.process
+            .lock()
+            .unwrap()
+            .add_listener(Listener::PrintVariable, tx);
+
+        let variable_references = self.variable_references.clone();
+
+        let f = rx
+            .take(1)
+            .into_future()
+            .timeout(Duration::new(
+                config
+                    .lock()
+                    .unwrap()
+                    .get_config("PrintVariableTimeout")
+                    .unwrap() as u64,
+                0,
+            ))
+            .map(move |event| match event.0.unwrap() {
+                Event::PrintVariable(variable, value) => {
+                    let variables_reference =
+                        match children_path(&variable.name, value.type_(), value.value()) {
+                            Some(path) => variable_references.lock().unwrap().add(path),
+                            None => 0,
+                        };
+                    serde_json::json!({
+                        "status": "OK",
+                        "variable": variable.name,
+                        "value": value.value(),
+                        "type": value.type_(),
+                        "variablesReference": variables_reference,
+                    })
+                }
+                Event::VariableNotFound(variable) => {
+                    log_msg(
+                        LogLevel::WARN,
+                        &format!("variable '{}' doesn't exist here", variable.name),
+                    );
+                    serde_json::json!({"status":"ERROR"})
+                }
+                _ => unreachable!(),
+            })
+            .map_err(|e| {
+                eprintln!("Reading stdin error {:?}", e);
+                io::Error::new(io::ErrorKind::Other, "Timed out printing variable")
+            });
+
+        let stmt = format!("frame variable {}\n", variable.name);
+
+        self.process.lock().unwrap().write_stdin(Bytes::from(stmt));
+
+        Box::new(f)
+    }
+}
+
+impl ImplDebugger {
+    /// Evaluate an expression and wait for its value or the error evaluating it
+    fn evaluate_expression(
+        &mut self,
+        expression: &str,
+        frame: Option<u64>,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = Result<VariableValue, String>, Error = io::Error> + Send> {
+        let (tx, rx) = mpsc::channel(1);
+
+        self.process
+            .lock()
+            .unwrap()
+            .add_listener(Listener::Evaluate, tx);
+
+        let f = rx
+            .take(1)
+            .into_future()
+            .timeout(Duration::new(
+                config
+                    .lock()
+                    .unwrap()
+                    .get_config("PrintVariableTimeout")
+                    .unwrap() as u64,
+                0,
+            ))
+            .map(move |event| match event.0.unwrap() {
+                Event::Evaluated(value) => Ok(value),
+                Event::EvaluateFailed(error) => {
+                    log_msg(LogLevel::WARN, &error);
+                    Err(error)
+                }
+                _ => unreachable!(),
+            })
+            .map_err(|e| {
+                eprintln!("Reading stdin error {:?}", e);
+                io::Error::new(io::ErrorKind::Other, "Timed out evaluating expression")
+            });
+
+        let stmt = evaluate_stmt(expression, frame);
+
+        self.process.lock().unwrap().write_stdin(Bytes::from(stmt));
+
+        Box::new(f)
+    }
+
+    /// Run the statement to read registers and wait for the registers in each register
+    /// set or the error
+    fn read_registers(
+        &mut self,
+        stmt: String,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<
+        dyn Future<Item = Result<Vec<(String, Vec<(String, String)>)>, String>, Error = io::Error>
+            + Send,
+    > {
+        let (tx, rx) = mpsc::channel(1);
+
+        self.process
+            .lock()
+            .unwrap()
+            .add_listener(Listener::Registers, tx);
+
+        let f = rx
+            .take(1)
+            .into_future()
+            .timeout(Duration::new(
+                config
+                    .lock()
+                    .unwrap()
+                    .get_config("PrintVariableTimeout")
+                    .unwrap() as u64,
+                0,
+            ))
+            .map(move |event| match event.0.unwrap() {
+                Event::Registers(sets) => Ok(sets),
+                Event::RegistersFailed(error) => {
+                    log_msg(LogLevel::WARN, &error);
+                    Err(error)
+                }
+                _ => unreachable!(),
+            })
+            .map_err(|e| {
+                eprintln!("Reading stdin error {:?}", e);
+                io::Error::new(io::ErrorKind::Other, "Timed out reading registers")
+            });
+
+        self.process.lock().unwrap().write_stdin(Bytes::from(stmt));
+
+        Box::new(f)
+    }
+
+    /// Print variables with `frame variable` and wait for them, the variables with children
+    /// are given references to their paths.
+    fn variables(
+        &mut self,
+        cmd: &str,
+        listener: Listener,
+        parent_path: Option<String>,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = Vec<VariableDetails>, Error = io::Error> + Send> {
+        let (tx, rx) = mpsc::channel(1);
+
+        self.process
+            .lock()
+            .unwrap()
+            .add_listener(listener.clone(), tx);
+
+        let process = self.process.clone();
+        let variable_references = self.variable_references.clone();
+
+        let f = rx
+            .take(1)
+            .into_future()
+            .timeout(Duration::new(
+                config
+                    .lock()
+                    .unwrap()
+                    .get_config("PrintVariableTimeout")
+                    .unwrap() as u64,
+                0,
+            ))
+            .map(move |event| match event.0.unwrap() {
+                Event::Variables(variables) => variables
+                    .into_iter()
+                    .map(|mut variable| {
+                        let path = match &parent_path {
+                            Some(parent_path) => child_path(parent_path, &variable.name),
+                            None => variable.name.clone(),
+                        };
+                        match children_path(&path, &variable.type_, &variable.value) {
+                            Some(path) => {
+                                variable.variables_reference =
+                                    variable_references.lock().unwrap().add(path)
+                            }
+                            None => {}
+                        }
+                        variable
+                    })
+                    .collect(),
+                _ => unreachable!(),
+            })
+            .map_err(move |e| {
+                eprintln!("Reading stdin error {:?}", e);
+                process.lock().unwrap().remove_listener(&listener);
+                io::Error::new(io::ErrorKind::Other, "Timed out printing variables")
+            });
+
+        // As with backtraces print a marker so we know when we've got all the variables
+        let stmt = format!("{}\nscript print(\"PADRE variables end\")\n", cmd);
+
+        self.process.lock().unwrap().write_stdin(Bytes::from(stmt));
+
+        Box::new(f)
+    }
+
+    fn process_cmd(
+        &mut self,
+        kind: &str,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_core_mode() {
+            Some(f) => return f,
+            _ => {}
+        }
+
+        match self.check_process() {
+            Some(f) => return f,
+            _ => {}
+        }
+
+        let stmt = format!("process {}\n", kind);
+
+        self.process.lock().unwrap().write_stdin(Bytes::from(stmt));
+
+        let f = future::lazy(move || {
+            let resp = serde_json::json!({"status":"OK"});
+            Ok(resp)
+        });
+
+        Box::new(f)
+    }
+
+    fn step(
+        &mut self,
+        kind: &str,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_core_mode() {
+            Some(f) => return f,
+            _ => {}
+        }
+
+        match self.check_process() {
+            Some(f) => return f,
+            _ => {}
+        }
+
+        let stmt = format!("thread {}\n", kind);
+
+        self.process.lock().unwrap().write_stdin(Bytes::from(stmt));
+
+        let f = future::lazy(move || {
+            let resp = serde_json::json!({"status":"OK"});
+            Ok(resp)
+        });
+
+        Box::new(f)
+    }
+
+    /// The process in a core file can be inspected but not run
+    fn check_core_mode(
+        &mut self,
+    ) -> Option<Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>> {
+        match self.core_mode {
+            true => {
+                log_msg(LogLevel::WARN, "Not available in core mode");
+                let f = future::lazy(move || {
+                    let resp = serde_json::json!({"status":"ERROR"});
+                    Ok(resp)
+                });
+
+                Some(Box::new(f))
+            }
+            false => None,
+        }
+    }
+
+    fn check_process(
+        &mut self,
+    ) -> Option<Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>> {
+        match self.process.lock().unwrap().is_process_running() {
+            false => {
+                log_msg(LogLevel::WARN, "No process running");
+                let f = future::lazy(move || {
+                    let resp = serde_json::json!({"status":"ERROR"});
+                    Ok(resp)
+                });
+
+                Some(Box::new(f))
+            }
+            true => None,
+        }
+    }
+
+    /// Send the LLDB statement(s) to set a breakpoint and wait to hear it's been set
+    fn set_breakpoint(
+        &mut self,
+        file_location: &FileLocation,
+        stmt: String,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        let (tx, rx) = mpsc::channel(1);
+
+        self.process
+            .lock()
+            .unwrap()
+            .add_listener(Listener::Breakpoint, tx);
+
+        let breakpoints = self.breakpoints.clone();
+        let breakpoints2 = self.breakpoints.clone();
+        let location = file_location.clone();
+        let location2 = file_location.clone();
+
+        let f = rx
+            .take(1)
+            .into_future()
+            .timeout(Duration::new(
+                config
+                    .lock()
+                    .unwrap()
+                    .get_config("BreakpointTimeout")
+                    .unwrap() as u64,
+                0,
+            ))
+            .map(move |event| {
+                let (id, status) = match event.0.unwrap() {
+                    Event::BreakpointSet(id, _) => (id, BreakpointStatus::Resolved),
+                    Event::BreakpointMultiple(id) => (id, BreakpointStatus::Multiple),
+                    Event::BreakpointPending(id) => (id, BreakpointStatus::Pending),
+                    _ => unreachable!(),
+                };
+
+                let mut breakpoints = breakpoints.lock().unwrap();
+                breakpoints.set_native_id(&location, id.to_string());
+                breakpoints.set_status(&location, status.clone());
+
+                match status {
+                    BreakpointStatus::Pending => serde_json::json!({"status":"PENDING"}),
+                    _ => serde_json::json!({"status":"OK"}),
+                }
+            })
+            .map_err(move |e| {
+                eprintln!("Reading stdin error {:?}", e);
+                breakpoints2
+                    .lock()
+                    .unwrap()
+                    .set_status(&location2, BreakpointStatus::Failed);
+                io::Error::new(io::ErrorKind::Other, "Timed out setting breakpoint")
+            });
+
+        self.process.lock().unwrap().write_stdin(Bytes::from(stmt));
+
+        Box::new(f)
+    }
+}
+
+/// Set a breakpoint named `padre_exception` that reports where the exception came from
+///
+/// We report the first frame with source as the throwing or panicking function itself usually
+/// doesn't have any.
+fn exception_breakpoint_stmt(args: &str, msg: &str) -> String {
+    format!(
+        "breakpoint set {} -N padre_exception\n\
+         breakpoint command add --script-type python\n\
+         l = next((f.GetLineEntry() for f in frame.GetThread().frames \
+         if f.GetLineEntry().GetFileSpec().GetFilename()), frame.GetLineEntry())\n\
+         print(\"PADRE exception %s:%d: %s\" % (l.GetFileSpec().fullpath, l.GetLine(), {}))\n\
+         DONE\n",
+        args,
+        serde_json::json!(msg)
+    )
+}
+
+/// Disassemble `count` instructions from an address, or the current function which we cut down
+/// to the instructions around the PC with `around_current` when given a count
+///
+/// As with backtraces we print a marker afterwards so we know when we've got all the
+/// instructions.
+fn disassemble_stmt(address: Option<&str>, count: Option<u64>) -> String {
+    let stmt = match address {
+        Some(address) => format!(
+            "disassemble --start-address {} --count {}",
+            address,
+            count.unwrap_or(DEFAULT_DISASSEMBLE_COUNT)
+        ),
+        None => "disassemble --frame".to_string(),
+    };
+
+    format!("{}\nscript print(\"PADRE disassemble end\")\n", stmt)
+}
+
+/// The `count` instructions with the current one in the middle, or as near it as the start or
+/// end of the function allows
+///
+/// We can't disassemble from a few instructions before the PC directly as instructions can have
+/// different lengths, so we might start in the middle of one.
+fn around_current(instructions: Vec<Instruction>, count: u64) -> Vec<Instruction> {
+    let count = count as usize;
+    let current = instructions
+        .iter()
+        .position(|instruction| instruction.is_current())
+        .unwrap_or(0);
+    let start = std::cmp::min(
+        current.saturating_sub(count / 2),
+        instructions.len().saturating_sub(count),
+    );
+
+    instructions.into_iter().skip(start).take(count).collect()
+}
+
+/// Read the registers of each register set in the selected frame
+///
+/// As when evaluating we go through LLDB's Python API so the registers come back as JSON on one
+/// line.
+fn registers_stmt() -> String {
+    "script import json; f = lldb.frame; \
+     print(\"PADRE registers \" + json.dumps(dict(error=\"No such frame\") if not f.IsValid() \
+     else dict(sets=[dict(name=s.GetName(), \
+     registers=[dict(name=r.GetName(), value=r.GetValue()) for r in s]) \
+     for s in f.GetRegisters()])))\n"
+        .to_string()
+}
+
+/// Write a register with `register write` in the selected frame and read it back
+///
+/// As when evaluating we go through LLDB's Python API so the error or the new value comes back
+/// as JSON on one line.
+fn set_register_stmt(name: &str, value: &str) -> String {
+    let cmd = format!(
+        "register write \"{}\" \"{}\"",
+        escape_double_quotes(name),
+        escape_double_quotes(value)
+    );
+
+    format!(
+        "script import json; r = lldb.SBCommandReturnObject(); \
+         _ = lldb.debugger.GetCommandInterpreter().HandleCommand({0}, r); \
+         v = lldb.frame.FindRegister({1}); \
+         print(\"PADRE register written \" + json.dumps(dict(name={1}, \
+         error=r.GetError() or (None if v.IsValid() else \"No register named \" + {1}), \
+         value=v.GetValue())))\n",
+        serde_json::json!(cmd),
+        serde_json::json!(name)
+    )
+}
+
+/// Whether a register set is in the group, LLDB names the sets differently for each
+/// architecture
+fn in_register_group(set: &str, group: RegisterGroup) -> bool {
+    let set = set.to_ascii_lowercase();
+    let keywords: &[&str] = match group {
+        RegisterGroup::General => &["general"],
+        RegisterGroup::Float => &["floating point"],
+        RegisterGroup::Vector => &["vector", "sse", "avx", "neon", "sve"],
+    };
+    keywords.iter().any(|keyword| set.contains(keyword))
+}
+
+/// The format LLDB gives a register's value in
+fn register_format(value: &str) -> &'static str {
+    if value.starts_with('{') {
+        "vector"
+    } else if value.starts_with("0x") {
+        "hex"
+    } else if value.parse::<i64>().is_ok() {
+        "decimal"
+    } else if value.parse::<f64>().is_ok() {
+        "float"
+    } else {
+        "unknown"
+    }
+}
+
+/// Evaluate an expression as `expression --` does in the selected frame or the one given
+///
+/// We go through LLDB's Python API so we can use any frame without selecting it and so the error
+/// or the value comes back as JSON on one line.
+fn evaluate_stmt(expression: &str, frame: Option<u64>) -> String {
+    let frame = match frame {
+        Some(index) => format!("lldb.thread.GetFrameAtIndex({})", index),
+        None => "lldb.frame".to_string(),
+    };
+
+    format!(
+        "script import json; f = {}; v = f.EvaluateExpression({}) if f.IsValid() else None; \
+         print(\"PADRE evaluate \" + json.dumps(dict(error=\"No such frame\") if v is None \
+         else dict(error=v.GetError().GetCString() if v.GetError().Fail() else None, \
+         type=v.GetTypeName(), \
+         value=\" \".join(s for s in (v.GetValue(), v.GetSummary()) if s) \
+         or str(v).split(\" = \", 1)[-1])))\n",
+        frame,
+        serde_json::json!(expression)
+    )
+}
+
+/// The LLDB statement to set a breakpoint on a function by name or regex, optionally only in
+/// one module
+fn break_function_stmt(function: &FunctionName) -> String {
+    let mut stmt = match function.is_regex {
+        true => format!(
+            "breakpoint set --func-regex \"{}\"",
+            escape_double_quotes(&function.name)
+        ),
+        false => format!(
+            "breakpoint set --name \"{}\"",
+            escape_double_quotes(&function.name)
+        ),
+    };
+
+    match &function.module {
+        Some(m) => stmt += &format!(" --shlib \"{}\"", escape_double_quotes(m)),
+        None => {}
+    }
+
+    stmt + "\n"
+}
+
+/// Escape a string to be passed to LLDB as a double quoted command argument, newlines are
+/// escaped too so the argument can't end the command
+fn escape_double_quotes(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// The path to get the children of a variable with, if it has any
+///
+/// Structures and arrays have their children printed in braces and we dereference pointers apart
+/// from null pointers and strings.
+fn children_path(path: &str, type_: &str, value: &str) -> Option<String> {
+    if value.starts_with('{') {
+        return Some(path.to_string());
+    }
+
+    let address = value.split_whitespace().next().unwrap_or("");
+    let is_null = match u64::from_str_radix(address.trim_start_matches("0x"), 16) {
+        Ok(address) => address == 0,
+        Err(_) => true,
+    };
+
+    match type_.ends_with('*') && !type_.contains("char") && !is_null {
+        true => Some(format!("*{}", path)),
+        false => None,
+    }
+}
+
+/// Whether an expression is a path to a variable that `frame variable` can print, such as
+/// `*a.b->c[1]`
+fn is_variable_path(expression: &str) -> bool {
+    lazy_static! {
+        static ref RE_VARIABLE_PATH: Regex =
+            Regex::new("^\\*?[A-Za-z_]\\w*(?:(?:\\.|->)[A-Za-z_]\\w*|\\[\\d+\\])*$").unwrap();
+    }
+
+    RE_VARIABLE_PATH.is_match(expression)
+}
+
+/// The path of the child of a variable for `frame variable`
+fn child_path(parent_path: &str, child: &str) -> String {
+    if child.starts_with('[') {
+        return format!("{}{}", parent_path, child);
+    }
+
+    match parent_path.strip_prefix('*') {
+        Some(pointer_path) => format!("{}->{}", pointer_path, child),
+        None => format!("{}.{}", parent_path, child),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        around_current, break_function_stmt, child_path, children_path, disassemble_stmt,
+        evaluate_stmt, in_register_group, is_variable_path, register_format, set_register_stmt,
+    };
+    use crate::debugger::{FunctionName, Instruction, RegisterGroup};
+
+    #[test]
+    fn check_break_function_stmt() {
+        assert_eq!(
+            break_function_stmt(&FunctionName::new(
+                "ns::f(int, char)".to_string(),
+                Some("libfoo.so".to_string()),
+                false
+            )),
+            "breakpoint set --name \"ns::f(int, char)\" --shlib \"libfoo.so\"\n"
+        );
+        assert_eq!(
+            break_function_stmt(&FunctionName::new("^a\"\nb".to_string(), None, true)),
+            "breakpoint set --func-regex \"^a\\\"\\nb\"\n"
+        );
+    }
+
+    #[test]
+    fn check_children_path() {
+        assert_eq!(
+            children_path("a", "Foo", "{\n  x = 1\n}"),
+            Some("a".to_string())
+        );
+        assert_eq!(
+            children_path("p", "Foo *", "0x00007ffeefbff6e8"),
+            Some("*p".to_string())
+        );
+        assert_eq!(children_path("p", "Foo *", "0x0000000000000000"), None);
+        assert_eq!(
+            children_path("s", "const char *", "0x0000000100000f9e \"abc\""),
+            None
+        );
+        assert_eq!(children_path("i", "int", "1"), None);
+    }
+
+    #[test]
+    fn check_is_variable_path() {
+        assert!(is_variable_path("a"));
+        assert!(is_variable_path("*a.b->c[1]"));
+        assert!(!is_variable_path("a + 1"));
+        assert!(!is_variable_path("foo()"));
+    }
+
+    #[test]
+    fn check_child_path() {
+        assert_eq!(child_path("a", "b"), "a.b");
+        assert_eq!(child_path("a.b", "[3]"), "a.b[3]");
+        assert_eq!(child_path("*p", "x"), "p->x");
+    }
+
+    #[test]
+    fn check_evaluate_stmt_uses_frame() {
+        let stmt = evaluate_stmt("s.name", None);
+        assert!(stmt.starts_with("script import json; f = lldb.frame; "));
+        assert!(stmt.contains("f.EvaluateExpression(\"s.name\")"));
+        assert!(stmt.ends_with("\n"));
+
+        let stmt = evaluate_stmt("a + b", Some(2));
+        assert!(stmt.contains("f = lldb.thread.GetFrameAtIndex(2); "));
+    }
+
+    #[test]
+    fn check_disassemble_stmt() {
+        assert_eq!(
+            disassemble_stmt(None, None),
+            "disassemble --frame\nscript print(\"PADRE disassemble end\")\n"
+        );
+        assert!(disassemble_stmt(None, Some(5)).starts_with("disassemble --frame\n"));
+        assert!(disassemble_stmt(Some("0x100003f60"), None)
+            .starts_with("disassemble --start-address 0x100003f60 --count 20\n"));
+    }
+
+    #[test]
+    fn check_around_current() {
+        let instructions = |current: usize| -> Vec<Instruction> {
+            (0..10)
+                .map(|i| {
+                    Instruction::new(
+                        format!("0x{:x}", i),
+                        "nop".to_string(),
+                        None,
+                        None,
+                        None,
+                        i == current,
+                    )
+                })
+                .collect()
+        };
+        let addresses = |instructions: Vec<Instruction>| -> Vec<String> {
+            instructions
+                .iter()
+                .map(|instruction| {
+                    instruction.to_json()["address"]
+                        .as_str()
+                        .unwrap()
+                        .to_string()
+                })
+                .collect()
+        };
+
+        assert_eq!(
+            addresses(around_current(instructions(5), 3)),
+            vec!["0x4", "0x5", "0x6"]
+        );
+        assert_eq!(
+            addresses(around_current(instructions(0), 2)),
+            vec!["0x0", "0x1"]
+        );
+        assert_eq!(
+            addresses(around_current(instructions(9), 2)),
+            vec!["0x8", "0x9"]
+        );
+        assert_eq!(around_current(instructions(5), 20).len(), 10);
+    }
+
+    #[test]
+    fn check_set_register_stmt() {
+        let stmt = set_register_stmt("rax", "0x5");
+        assert!(stmt.contains(".HandleCommand(\"register write \\\"rax\\\" \\\"0x5\\\"\", r); "));
+        assert!(stmt.contains("v = lldb.frame.FindRegister(\"rax\"); "));
+        assert!(stmt.ends_with("\n"));
+        assert_eq!(stmt.matches('\n').count(), 1);
+
+        let stmt = set_register_stmt("rax", "1\nprocess kill");
+        assert_eq!(stmt.matches('\n').count(), 1);
+    }
+
+    #[test]
+    fn check_register_groups_and_formats() {
+        assert!(in_register_group(
+            "General Purpose Registers",
+            RegisterGroup::General
+        ));
+        assert!(in_register_group(
+            "Floating Point Registers",
+            RegisterGroup::Float
+        ));
+        assert!(in_register_group(
+            "Advanced Vector Extensions",
+            RegisterGroup::Vector
+        ));
+        assert!(!in_register_group(
+            "General Purpose Registers",
+            RegisterGroup::Vector
+        ));
+
+        assert_eq!(register_format("0x0000000000401126"), "hex");
+        assert_eq!(register_format("{0x00 0x01}"), "vector");
+        assert_eq!(register_format("3"), "decimal");
+        assert_eq!(register_format("1.5"), "float");
     }
 }
@@ -3,13 +3,20 @@
 //! This module performs the basic setup of and interfacing with LLDB. It will
 //! analyse the output of the text and work out what is happening then.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::io::BufReader;
+use std::process::exit;
 use std::sync::{Arc, Mutex};
 
-use crate::debugger::{FileLocation, Variable};
-use crate::notifier::{breakpoint_set, jump_to_position, log_msg, signal_exited, LogLevel};
-use crate::util::{check_and_spawn_process, read_output, setup_stdin};
+use crate::debugger::{
+    BreakpointRegistry, BreakpointStatus, FileLocation, Instruction, StackFrame, ThreadDetails,
+    Variable, VariableDetails,
+};
+use crate::notifier::{
+    breakpoint_set, exception_thrown, jump_to_address, jump_to_position, jump_to_thread_position,
+    log_msg, logpoint_hit, signal_exited, watchpoint_hit, LogLevel,
+};
+use crate::util::{check_and_spawn_process, file_exists, read_output, setup_stdin};
 
 use bytes::Bytes;
 use regex::Regex;
@@ -20,16 +27,46 @@ use tokio_process::{Child, ChildStderr, ChildStdout};
 /// You can register to listen for one of the following events:
 /// - LLDBLaunched: LLDB has started up initially
 /// - ProcessLaunched: LLDB has launched a process for debugging
+/// - ProcessAttached: LLDB has attached to a running process
 /// - ProcessExited: The process spawned by LLDB has exited
 /// - Breakpoint: A breakpoint event has happened
+/// - BreakpointRemoved: A breakpoint has been deleted
 /// - PrintVariable: A variable has been requested to print and this is the response
+/// - Watchpoint: A watchpoint has been requested and this is the response
+/// - StepOut: We've stepped out of a function
+/// - ProcessStopped: The process has stopped or exited
+/// - Backtrace: A backtrace has been requested and this is the response
+/// - Disassemble: A disassembly has been requested and this is the response
+/// - Registers: Registers have been requested to be read and this is the response
+/// - WriteRegister: A register has been requested to be written and this is its new value
+/// - SelectFrame: A frame has been requested to be selected and this is the response
+/// - Locals: The locals of the selected frame have been requested and this is the response
+/// - ExpandVariable: The children of a variable have been requested and this is the response
+/// - Evaluate: An expression has been requested to be evaluated and this is the response
+/// - Threads: The threads have been requested and this is the response
+/// - SelectThread: A thread has been requested to be selected and this is the response
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
 pub enum Listener {
     LLDBLaunched,
     ProcessLaunched,
+    ProcessAttached,
     ProcessExited,
     Breakpoint,
+    BreakpointRemoved,
     PrintVariable,
+    Watchpoint,
+    StepOut,
+    ProcessStopped,
+    Backtrace,
+    Disassemble,
+    Registers,
+    WriteRegister,
+    SelectFrame,
+    Locals,
+    ExpandVariable,
+    Evaluate,
+    Threads,
+    SelectThread,
 }
 
 /// An LLDB event is something that can be registered for being listened to and can be triggered
@@ -39,13 +76,52 @@ pub enum Event {
     LLDBLaunched,
     // (PID)
     ProcessLaunched(u64),
+    // (PID)
+    ProcessAttached(u64),
+    // (Error message)
+    AttachFailed(String),
     // (PID, Exit code)
     ProcessExited(u64, i64),
-    BreakpointSet(FileLocation),
-    BreakpointMultiple,
-    BreakpointPending,
+    // (Breakpoint ID, Location)
+    BreakpointSet(u64, FileLocation),
+    // (Breakpoint ID)
+    BreakpointMultiple(u64),
+    // (Breakpoint ID)
+    BreakpointPending(u64),
+    // (Number of breakpoints deleted)
+    BreakpointRemoved(u64),
     PrintVariable(Variable, VariableValue),
     VariableNotFound(Variable),
+    // (Watchpoint ID)
+    WatchpointSet(u64),
+    // (Error message)
+    WatchpointFailed(String),
+    // (Return value if known)
+    SteppedOut(Option<VariableValue>),
+    ProcessStopped,
+    Backtrace(Vec<StackFrame>),
+    Disassembly(Vec<Instruction>),
+    // (Error message)
+    DisassembleFailed(String),
+    // (Register set name, (register name, value) of each register in the set)
+    Registers(Vec<(String, Vec<(String, String)>)>),
+    // (Error message)
+    RegistersFailed(String),
+    // (Register name, value)
+    RegisterWritten(String, String),
+    // (Frame index)
+    FrameSelected(u64),
+    // (Error message)
+    FrameNotFound(String),
+    Variables(Vec<VariableDetails>),
+    Evaluated(VariableValue),
+    // (Error message)
+    EvaluateFailed(String),
+    Threads(Vec<ThreadDetails>),
+    // (Thread index)
+    ThreadSelected(u64),
+    // (Error message)
+    ThreadNotFound(String),
 }
 
 /// The value of a variable
@@ -69,10 +145,20 @@ impl VariableValue {
     }
 }
 
+/// A watchpoint that has been hit, we report it once we know where the process stopped
+#[derive(Debug)]
+struct WatchpointHit {
+    id: u64,
+    old_value: Option<String>,
+    new_value: Option<String>,
+}
+
 #[derive(Debug)]
 pub struct LLDBProcess {
     debugger_cmd: Option<String>,
     run_cmd: Option<Vec<String>>,
+    // The core file to load with the program rather than running it
+    core: Option<String>,
     lldb_process: Option<Child>,
     lldb_stdin_tx: Option<Sender<Bytes>>,
     analyser: Arc<Mutex<Analyser>>,
@@ -80,13 +166,19 @@ pub struct LLDBProcess {
 
 impl LLDBProcess {
     /// Create a new LLDBProcess
-    pub fn new(debugger_cmd: String, run_cmd: Vec<String>) -> Self {
+    pub fn new(
+        debugger_cmd: String,
+        run_cmd: Vec<String>,
+        breakpoints: Arc<Mutex<BreakpointRegistry>>,
+        core: Option<String>,
+    ) -> Self {
         LLDBProcess {
             debugger_cmd: Some(debugger_cmd),
             run_cmd: Some(run_cmd),
+            core,
             lldb_process: None,
             lldb_stdin_tx: None,
-            analyser: Arc::new(Mutex::new(Analyser::new())),
+            analyser: Arc::new(Mutex::new(Analyser::new(breakpoints))),
         }
     }
 
@@ -96,11 +188,33 @@ impl LLDBProcess {
     /// - Sets up a `ReadOutput` from `util.rs` in order to read stdout and stderr;
     /// - Sets up a thread to read stdin and forward it onto LLDB stdin;
     /// - Checks that LLDB and the program to be ran both exist, otherwise panics.
+    ///
+    /// When debugging a core file LLDB loads it along with the program with
+    /// `target create --core`.
     pub fn setup(&mut self) {
-        let mut lldb_process = check_and_spawn_process(
-            vec![self.debugger_cmd.take().unwrap()],
-            self.run_cmd.take().unwrap(),
-        );
+        let attaching = match &self.run_cmd {
+            Some(run_cmd) => run_cmd.is_empty(),
+            None => false,
+        };
+
+        let mut debugger_cmd = vec![self.debugger_cmd.take().unwrap()];
+
+        match &self.core {
+            Some(core) => {
+                if !file_exists(core) {
+                    let msg = format!("Can't load core file as {} does not exist", core);
+                    log_msg(LogLevel::CRITICAL, &msg);
+                    println!("{}", msg);
+
+                    exit(1);
+                }
+                debugger_cmd.push("--core".to_string());
+                debugger_cmd.push(core.clone());
+            }
+            None => {}
+        }
+
+        let mut lldb_process = check_and_spawn_process(debugger_cmd, self.run_cmd.take().unwrap());
 
         self.setup_stdout(
             lldb_process
@@ -124,6 +238,11 @@ impl LLDBProcess {
 
         self.lldb_stdin_tx = Some(stdin_tx);
         self.lldb_process = Some(lldb_process);
+
+        // LLDB doesn't tell us it's started when there's no program to set as the target
+        if attaching {
+            self.analyser.lock().unwrap().lldb_started();
+        }
     }
 
     pub fn teardown(&mut self) {
@@ -146,6 +265,10 @@ impl LLDBProcess {
         self.analyser.lock().unwrap().add_listener(kind, sender);
     }
 
+    pub fn remove_listener(&mut self, kind: &Listener) {
+        self.analyser.lock().unwrap().remove_listener(kind);
+    }
+
     pub fn is_process_running(&self) -> bool {
         self.analyser.lock().unwrap().is_process_running()
     }
@@ -185,30 +308,94 @@ pub struct Analyser {
     stderr: String,
     process_pid: Option<u64>,
     listeners: HashMap<Listener, Sender<Event>>,
+    // The listeners waiting on commands that can fail, in the order the commands were sent
+    error_listeners: VecDeque<Listener>,
+    breakpoints: Arc<Mutex<BreakpointRegistry>>,
+    watchpoint_hit: Option<WatchpointHit>,
+    return_value: Option<VariableValue>,
+    backtrace: Vec<StackFrame>,
+    disassembly: Vec<Instruction>,
+    // The (module, symbol) the instructions being disassembled are in
+    disassembly_symbol: Option<(String, String)>,
+    // (Name, type, value) of each variable printed by `frame variable`
+    variables: Vec<(String, String, String)>,
+    // How many braces are still open in the value of the last variable printed
+    variable_depth: i64,
+    threads: Vec<ThreadDetails>,
+    // The thread LLDB reported stopping in, until we know where it stopped
+    stopped_thread: Option<u64>,
 }
 
 impl Analyser {
-    pub fn new() -> Self {
+    pub fn new(breakpoints: Arc<Mutex<BreakpointRegistry>>) -> Self {
         Analyser {
             stdout: "".to_string(),
             stderr: "".to_string(),
             process_pid: None,
             listeners: HashMap::new(),
+            error_listeners: VecDeque::new(),
+            breakpoints,
+            watchpoint_hit: None,
+            return_value: None,
+            backtrace: vec![],
+            disassembly: vec![],
+            disassembly_symbol: None,
+            variables: vec![],
+            variable_depth: 0,
+            threads: vec![],
+            stopped_thread: None,
         }
     }
 
     pub fn add_listener(&mut self, kind: Listener, sender: Sender<Event>) {
+        match kind {
+            Listener::ProcessAttached
+            | Listener::Watchpoint
+            | Listener::Disassemble
+            | Listener::SelectFrame
+            | Listener::SelectThread => {
+                self.error_listeners.retain(|k| k != &kind);
+                self.error_listeners.push_back(kind.clone());
+            }
+            _ => {}
+        }
         self.listeners.insert(kind, sender);
     }
 
+    /// Stop listening, e.g. when a request has timed out, and drop anything collected for it
+    ///
+    /// Listening for some events changes how we read LLDB's output so we mustn't leave them
+    /// behind.
+    pub fn remove_listener(&mut self, kind: &Listener) {
+        self.listeners.remove(kind);
+        match kind {
+            Listener::Backtrace => self.backtrace.clear(),
+            Listener::Disassemble => {
+                self.disassembly.clear();
+                self.disassembly_symbol = None;
+            }
+            Listener::Locals | Listener::ExpandVariable => {
+                self.variables.clear();
+                self.variable_depth = 0;
+            }
+            Listener::Threads => self.threads.clear(),
+            _ => {}
+        }
+    }
+
     pub fn analyse_stdout(&mut self, s: &str) {
         self.stdout.push_str(s);
 
         lazy_static! {
             static ref RE_LLDB_STARTED: Regex =
                 Regex::new("^Current executable set to '.*' (.*)\\.$").unwrap();
+            static ref RE_CORE_LOADED: Regex =
+                Regex::new("^Core file '.*' \\(.*\\) was loaded\\.$").unwrap();
             static ref RE_PROCESS_STARTED: Regex =
                 Regex::new("^Process (\\d+) launched: '.*' \\((.*)\\)$").unwrap();
+            static ref RE_PROCESS_STOPPED: Regex = Regex::new("^Process (\\d+) stopped$").unwrap();
+            static ref RE_PROCESS_DETACHED: Regex =
+                Regex::new("^Process (\\d+) detached$").unwrap();
             static ref RE_PROCESS_EXITED: Regex =
                 Regex::new("^Process (\\d+) exited with status = (\\d+) \\(0x[0-9a-f]*\\) *$")
                     .unwrap();
@@ -223,9 +410,51 @@ impl Analyser {
                 Regex::new("Breakpoint (\\d+): (\\d+) locations\\.$").unwrap();
             static ref RE_BREAKPOINT_PENDING: Regex =
                 Regex::new("Breakpoint (\\d+): no locations \\(pending\\)\\.$").unwrap();
+            static ref RE_BREAKPOINT_LOCATIONS_ADDED: Regex =
+                Regex::new("^(\\d+) locations? added to breakpoint (\\d+)$").unwrap();
+            static ref RE_BREAKPOINT_DELETED: Regex =
+                Regex::new("^(\\d+) breakpoints deleted; \\d+ breakpoint locations disabled\\.$")
+                    .unwrap();
+            static ref RE_LOGPOINT: Regex =
+                Regex::new("PADRE logpoint (.*):(\\d+): (.*)$").unwrap();
+            static ref RE_EXCEPTION: Regex =
+                Regex::new("PADRE exception (.*):(\\d+): (.*)$").unwrap();
+            static ref RE_WATCHPOINT_CREATED: Regex =
+                Regex::new("^Watchpoint created: Watchpoint (\\d+): ").unwrap();
+            static ref RE_WATCHPOINT_HIT: Regex = Regex::new("^Watchpoint (\\d+) hit:$").unwrap();
+            static ref RE_WATCHPOINT_VALUE: Regex =
+                Regex::new("^(old |new )?value: (.*)$").unwrap();
+            static ref RE_RETURN_VALUE: Regex =
+                Regex::new("^Return value: \\((.*)\\) \\$\\d+ = (.*)$").unwrap();
             static ref RE_STOPPED_AT_POSITION: Regex = Regex::new(" *frame #\\d.*$").unwrap();
             static ref RE_JUMP_TO_POSITION: Regex =
-                Regex::new("^ *frame #\\d at (\\S+):(\\d+)$").unwrap();
+                Regex::new("^ *frame #\\d+ at (\\S+):(\\d+)(?: |$)").unwrap();
+            static ref RE_STOPPED_AT_ADDRESS: Regex =
+                Regex::new("^[ *]*frame #\\d+.* pc (0x[0-9a-fA-F]+)$").unwrap();
+            static ref RE_BACKTRACE_FRAME: Regex = Regex::new(
+                "^[ *]*frame #(\\d+)(?: at (\\S+):(\\d+))?(?: in (.*?))?(?: from (\\S+))?(?: pc \\S+)?$"
+            )
+            .unwrap();
+            static ref RE_BACKTRACE_END: Regex = Regex::new("^PADRE backtrace end$").unwrap();
+            static ref RE_DISASSEMBLY_SYMBOL: Regex = Regex::new("^(\\S+?)`(.+):$").unwrap();
+            static ref RE_INSTRUCTION: Regex = Regex::new(
+                "^(->)? +(?:\\S+\\[)?(0x[0-9a-fA-F]+)\\]?(?: <\\+(\\d+)>)?: +(.*?) *$"
+            )
+            .unwrap();
+            static ref RE_DISASSEMBLY_END: Regex = Regex::new("^PADRE disassemble end$").unwrap();
+            static ref RE_FRAME_INDEX: Regex = Regex::new("^ *frame #(\\d+)").unwrap();
+            static ref RE_VARIABLE: Regex = Regex::new("^( *)\\((.*)\\) (\\S+) = (.*)$").unwrap();
+            static ref RE_VARIABLES_END: Regex = Regex::new("^PADRE variables end$").unwrap();
+            static ref RE_THREAD: Regex = Regex::new(
+                "^([ *]) thread #(\\d+): tid = \\w+(?:, name = '(.*?)')?(?: at (\\S+):(\\d+))?$"
+            )
+            .unwrap();
+            static ref RE_THREADS_END: Regex = Regex::new("^PADRE threads end$").unwrap();
+            static ref RE_STOPPED_THREAD: Regex = Regex::new("^\\* thread #(\\d+)(?:,|$)").unwrap();
+            static ref RE_REGISTERS: Regex = Regex::new("^PADRE registers (\\{.*\\})$").unwrap();
+            static ref RE_EVALUATED: Regex = Regex::new("^PADRE evaluate (\\{.*\\})$").unwrap();
+            static ref RE_REGISTER_WRITTEN: Regex =
+                Regex::new("^PADRE register written (\\{.*\\})$").unwrap();
             static ref RE_PRINTED_VARIABLE: Regex =
                 Regex::new("^\\((.*)\\) ([\\S+]*) = .*$").unwrap();
             static ref RE_PROCESS_NOT_RUNNING: Regex =
@@ -239,11 +468,26 @@ impl Analyser {
                 self.lldb_started();
             }
 
+            // LLDB doesn't set the executable separately when loading a core file
+            for _ in RE_CORE_LOADED.captures_iter(line) {
+                self.lldb_started();
+            }
+
             for cap in RE_PROCESS_STARTED.captures_iter(line) {
                 let pid = cap[1].parse::<u64>().unwrap();
                 self.process_started(pid);
             }
 
+            for cap in RE_PROCESS_STOPPED.captures_iter(line) {
+                let pid = cap[1].parse::<u64>().unwrap();
+                self.process_stopped(pid);
+            }
+
+            for cap in RE_PROCESS_DETACHED.captures_iter(line) {
+                let pid = cap[1].parse::<u64>().unwrap();
+                self.process_detached(pid);
+            }
+
             for cap in RE_PROCESS_EXITED.captures_iter(line) {
                 let pid = cap[1].parse::<u64>().unwrap();
                 let exit_code = cap[2].parse::<i64>().unwrap();
@@ -254,45 +498,261 @@ impl Analyser {
 
             for cap in RE_BREAKPOINT.captures_iter(line) {
                 found_breakpoint = true;
+                let id = cap[1].parse::<u64>().unwrap();
                 let file = cap[2].to_string();
                 let line = cap[3].parse::<u64>().unwrap();
-                self.found_breakpoint(file, line);
+                self.found_breakpoint(id, file, line);
             }
 
             if !found_breakpoint {
                 for cap in RE_BREAKPOINT_2.captures_iter(line) {
                     found_breakpoint = true;
+                    let id = cap[1].parse::<u64>().unwrap();
                     let file = cap[2].to_string();
                     let line = cap[3].parse::<u64>().unwrap();
-                    self.found_breakpoint(file, line);
+                    self.found_breakpoint(id, file, line);
                 }
             }
 
             if !found_breakpoint {
-                for _ in RE_BREAKPOINT_MULTIPLE.captures_iter(line) {
+                for cap in RE_BREAKPOINT_MULTIPLE.captures_iter(line) {
                     found_breakpoint = true;
-                    self.found_multiple_breakpoints();
+                    let id = cap[1].parse::<u64>().unwrap();
+                    self.found_multiple_breakpoints(id);
                 }
             }
 
             if !found_breakpoint {
-                for _ in RE_BREAKPOINT_PENDING.captures_iter(line) {
-                    self.found_pending_breakpoint();
+                for cap in RE_BREAKPOINT_PENDING.captures_iter(line) {
+                    let id = cap[1].parse::<u64>().unwrap();
+                    self.found_pending_breakpoint(id);
+                }
+            }
+
+            for cap in RE_BREAKPOINT_LOCATIONS_ADDED.captures_iter(line) {
+                let num_locations = cap[1].parse::<u64>().unwrap();
+                let id = cap[2].parse::<u64>().unwrap();
+                self.breakpoint_locations_added(id, num_locations);
+            }
+
+            for cap in RE_BREAKPOINT_DELETED.captures_iter(line) {
+                let num_deleted = cap[1].parse::<u64>().unwrap();
+                self.breakpoint_removed(num_deleted);
+            }
+
+            for cap in RE_LOGPOINT.captures_iter(line) {
+                let file = cap[1].to_string();
+                let line = cap[2].parse::<u64>().unwrap();
+                logpoint_hit(&file, line, &cap[3]);
+            }
+
+            for cap in RE_EXCEPTION.captures_iter(line) {
+                let file = cap[1].to_string();
+                let line = cap[2].parse::<u64>().unwrap();
+                exception_thrown(&file, line, &cap[3]);
+            }
+
+            for cap in RE_WATCHPOINT_CREATED.captures_iter(line) {
+                let id = cap[1].parse::<u64>().unwrap();
+                self.watchpoint_set(id);
+            }
+
+            for cap in RE_WATCHPOINT_HIT.captures_iter(line) {
+                let id = cap[1].parse::<u64>().unwrap();
+                self.watchpoint_hit = Some(WatchpointHit {
+                    id,
+                    old_value: None,
+                    new_value: None,
+                });
+            }
+
+            for cap in RE_WATCHPOINT_VALUE.captures_iter(line) {
+                match &mut self.watchpoint_hit {
+                    Some(hit) => match cap.get(1).map(|m| m.as_str()) {
+                        Some("old ") => hit.old_value = Some(cap[2].to_string()),
+                        _ => hit.new_value = Some(cap[2].to_string()),
+                    },
+                    None => {}
                 }
             }
 
-            for _ in RE_STOPPED_AT_POSITION.captures_iter(line) {
+            for cap in RE_RETURN_VALUE.captures_iter(line) {
+                self.return_value =
+                    Some(VariableValue::new(cap[1].to_string(), cap[2].to_string()));
+            }
+
+            for cap in RE_EVALUATED.captures_iter(line) {
+                self.evaluated(&cap[1]);
+            }
+
+            for cap in RE_REGISTERS.captures_iter(line) {
+                self.printed_registers(&cap[1]);
+            }
+
+            for cap in RE_REGISTER_WRITTEN.captures_iter(line) {
+                self.register_written(&cap[1]);
+            }
+
+            for cap in RE_STOPPED_THREAD.captures_iter(line) {
+                self.stopped_thread = Some(cap[1].parse::<u64>().unwrap());
+            }
+
+            // Frames are printed in backtraces too, only the frame after the thread we've stopped
+            // in or a frame we've selected is where we are now. We look for these before any
+            // output we're collecting so we don't miss the process stopping meanwhile.
+            if RE_STOPPED_AT_POSITION.is_match(line)
+                && (self.stopped_thread.is_some()
+                    || self.listeners.contains_key(&Listener::SelectFrame))
+            {
+                // Selecting a frame or thread prints it but the process hasn't stopped again
+                let thread = self.stopped_thread.take();
+                let mut stopped_thread = None;
+                if thread.is_none() {
+                    for cap in RE_FRAME_INDEX.captures_iter(line) {
+                        let index = cap[1].parse::<u64>().unwrap();
+                        self.frame_selected(index);
+                    }
+                } else if self.listeners.contains_key(&Listener::SelectThread) {
+                    match thread {
+                        Some(thread) => self.thread_selected(thread),
+                        None => {}
+                    }
+                } else {
+                    stopped_thread = thread;
+                    self.report_watchpoint_hit();
+                    self.stopped();
+                }
+
                 let mut found = false;
                 for cap in RE_JUMP_TO_POSITION.captures_iter(line) {
                     found = true;
                     let file = cap[1].to_string();
                     let line = cap[2].parse::<u64>().unwrap();
-                    self.jump_to_position(file, line);
+                    self.jump_to_position(file, line, stopped_thread);
                 }
 
                 if !found {
-                    self.jump_to_unknown_position();
+                    let address = RE_STOPPED_AT_ADDRESS
+                        .captures(line)
+                        .map(|cap| cap[1].to_string());
+                    self.jump_to_unknown_position(address, stopped_thread);
                 }
+
+                continue;
+            }
+
+            // Values of structures and arrays carry on over several lines until their braces
+            // balance, when expanding a variable we want the children indented under it.
+            let indent = match (
+                self.listeners.contains_key(&Listener::Locals),
+                self.listeners.contains_key(&Listener::ExpandVariable),
+            ) {
+                (true, _) => Some(""),
+                (false, true) => Some("  "),
+                (false, false) => None,
+            };
+
+            match indent {
+                Some(indent) => {
+                    if RE_VARIABLES_END.is_match(line) {
+                        self.printed_variables();
+                        continue;
+                    }
+
+                    let mut found = false;
+                    for cap in RE_VARIABLE.captures_iter(line) {
+                        if &cap[1] == indent && self.variable_depth <= 0 {
+                            found = true;
+                            self.variable_depth = brace_depth(&cap[4]);
+                            self.variables.push((
+                                cap[3].to_string(),
+                                cap[2].to_string(),
+                                cap[4].to_string(),
+                            ));
+                        }
+                    }
+
+                    // Anything else that isn't part of a value isn't ours
+                    if !found && self.variable_depth > 0 {
+                        match self.variables.last_mut() {
+                            Some(variable) => {
+                                self.variable_depth += brace_depth(line);
+                                variable.2.push('\n');
+                                variable
+                                    .2
+                                    .push_str(line.strip_prefix(indent).unwrap_or(line));
+                            }
+                            None => {}
+                        }
+                    }
+
+                    continue;
+                }
+                None => {}
+            }
+
+            if self.listeners.contains_key(&Listener::Disassemble) {
+                for cap in RE_DISASSEMBLY_SYMBOL.captures_iter(line) {
+                    self.disassembly_symbol = Some((cap[1].to_string(), cap[2].to_string()));
+                }
+
+                for cap in RE_INSTRUCTION.captures_iter(line) {
+                    let (module, symbol) = match &self.disassembly_symbol {
+                        Some((module, symbol)) => (Some(module.clone()), Some(symbol.clone())),
+                        None => (None, None),
+                    };
+                    self.disassembly.push(Instruction::new(
+                        cap[2].to_string(),
+                        cap[4].to_string(),
+                        symbol,
+                        module,
+                        cap.get(3).map(|m| m.as_str().parse::<u64>().unwrap()),
+                        cap.get(1).is_some(),
+                    ));
+                }
+
+                for _ in RE_DISASSEMBLY_END.captures_iter(line) {
+                    self.disassembled();
+                }
+
+                continue;
+            }
+
+            // Frames printed in a backtrace look like where we've stopped
+            if self.listeners.contains_key(&Listener::Backtrace) {
+                for cap in RE_BACKTRACE_FRAME.captures_iter(line) {
+                    self.backtrace.push(StackFrame::new(
+                        cap[1].parse::<u64>().unwrap(),
+                        cap.get(4).map_or("", |m| m.as_str()).to_string(),
+                        cap.get(2).map(|m| m.as_str().to_string()),
+                        cap.get(3).map(|m| m.as_str().parse::<u64>().unwrap()),
+                        cap.get(5).map(|m| m.as_str().to_string()),
+                    ));
+                }
+
+                for _ in RE_BACKTRACE_END.captures_iter(line) {
+                    self.printed_backtrace();
+                }
+
+                continue;
+            }
+
+            if self.listeners.contains_key(&Listener::Threads) {
+                for cap in RE_THREAD.captures_iter(line) {
+                    self.threads.push(ThreadDetails::new(
+                        cap[2].parse::<u64>().unwrap(),
+                        cap.get(3).map_or("", |m| m.as_str()).to_string(),
+                        &cap[1] == "*",
+                        cap.get(4).map(|m| m.as_str().to_string()),
+                        cap.get(5).map(|m| m.as_str().parse::<u64>().unwrap()),
+                    ));
+                }
+
+                for _ in RE_THREADS_END.captures_iter(line) {
+                    self.printed_threads();
+                }
+
+                continue;
             }
 
             for cap in RE_PRINTED_VARIABLE.captures_iter(line) {
@@ -315,20 +775,51 @@ impl Analyser {
         lazy_static! {
             static ref RE_VARIABLE_NOT_FOUND: Regex =
                 Regex::new("error: no variable named '([^']*)' found in this frame$").unwrap();
+            static ref RE_ERROR: Regex = Regex::new("^error: (.*)$").unwrap();
         }
 
         let s = self.stderr.clone();
 
         for line in s.split("\n") {
+            let mut found = false;
             for cap in RE_VARIABLE_NOT_FOUND.captures_iter(line) {
+                found = true;
                 let variable = cap[1].to_string();
                 self.variable_not_found(variable);
             }
+
+            if found {
+                continue;
+            }
+
+            for cap in RE_ERROR.captures_iter(line) {
+                self.command_failed(cap[1].to_string());
+            }
         }
 
         self.clear_analyser();
     }
 
+    /// LLDB runs commands in order so an error is from the first command still waiting to hear
+    /// whether it failed, errors from any other commands are ignored
+    fn command_failed(&mut self, msg: String) {
+        while let Some(kind) = self.error_listeners.pop_front() {
+            if !self.listeners.contains_key(&kind) {
+                continue;
+            }
+
+            match kind {
+                Listener::ProcessAttached => self.attach_failed(msg),
+                Listener::Watchpoint => self.watchpoint_failed(msg),
+                Listener::Disassemble => self.disassemble_failed(msg),
+                Listener::SelectFrame => self.frame_not_found(msg),
+                Listener::SelectThread => self.thread_not_found(msg),
+                _ => unreachable!(),
+            }
+            return;
+        }
+    }
+
     fn clear_analyser(&mut self) {
         self.stdout = "".to_string();
         self.stderr = "".to_string();
@@ -360,8 +851,40 @@ impl Analyser {
         }
     }
 
+    /// LLDB doesn't say it's launched a process it attaches to so we find out about the process
+    /// when it first stops
+    fn process_stopped(&mut self, pid: u64) {
+        match self.process_pid {
+            Some(_) => return,
+            None => self.process_pid = Some(pid),
+        }
+
+        match self.listeners.remove(&Listener::ProcessAttached) {
+            Some(listener) => {
+                listener.send(Event::ProcessAttached(pid)).wait().unwrap();
+            }
+            None => {}
+        }
+    }
+
+    fn attach_failed(&mut self, msg: String) {
+        match self.listeners.remove(&Listener::ProcessAttached) {
+            Some(listener) => {
+                listener.send(Event::AttachFailed(msg)).wait().unwrap();
+            }
+            None => {}
+        }
+    }
+
+    fn process_detached(&mut self, pid: u64) {
+        self.process_pid = None;
+        self.stopped();
+        log_msg(LogLevel::INFO, &format!("Detached from process {}", pid));
+    }
+
     fn process_exited(&mut self, pid: u64, exit_code: i64) {
         self.process_pid = None;
+        self.stopped();
         signal_exited(pid, exit_code);
         match self.listeners.remove(&Listener::ProcessExited) {
             Some(listener) => {
@@ -374,13 +897,13 @@ impl Analyser {
         }
     }
 
-    fn found_breakpoint(&mut self, file: String, line: u64) {
+    fn found_breakpoint(&mut self, id: u64, file: String, line: u64) {
         breakpoint_set(&file, line);
         let file_location = FileLocation::new(file, line);
         match self.listeners.remove(&Listener::Breakpoint) {
             Some(listener) => {
                 listener
-                    .send(Event::BreakpointSet(file_location))
+                    .send(Event::BreakpointSet(id, file_location))
                     .wait()
                     .unwrap();
             }
@@ -388,30 +911,310 @@ impl Analyser {
         }
     }
 
-    fn found_multiple_breakpoints(&mut self) {
+    fn found_multiple_breakpoints(&mut self, id: u64) {
         match self.listeners.remove(&Listener::Breakpoint) {
             Some(listener) => {
-                listener.send(Event::BreakpointMultiple).wait().unwrap();
+                listener.send(Event::BreakpointMultiple(id)).wait().unwrap();
             }
             None => {}
         }
     }
 
-    fn found_pending_breakpoint(&mut self) {
+    fn found_pending_breakpoint(&mut self, id: u64) {
         match self.listeners.remove(&Listener::Breakpoint) {
             Some(listener) => {
-                listener.send(Event::BreakpointPending).wait().unwrap();
+                listener.send(Event::BreakpointPending(id)).wait().unwrap();
+            }
+            None => {}
+        }
+    }
+
+    fn breakpoint_locations_added(&mut self, id: u64, num_locations: u64) {
+        let status = match num_locations {
+            1 => BreakpointStatus::Resolved,
+            _ => BreakpointStatus::Multiple,
+        };
+        self.breakpoints
+            .lock()
+            .unwrap()
+            .set_status_by_native_id(&id.to_string(), status);
+    }
+
+    fn breakpoint_removed(&mut self, num_deleted: u64) {
+        match self.listeners.remove(&Listener::BreakpointRemoved) {
+            Some(listener) => {
+                listener
+                    .send(Event::BreakpointRemoved(num_deleted))
+                    .wait()
+                    .unwrap();
+            }
+            None => {}
+        }
+    }
+
+    fn watchpoint_set(&mut self, id: u64) {
+        match self.listeners.remove(&Listener::Watchpoint) {
+            Some(listener) => {
+                listener.send(Event::WatchpointSet(id)).wait().unwrap();
+            }
+            None => {}
+        }
+    }
+
+    fn watchpoint_failed(&mut self, msg: String) {
+        match self.listeners.remove(&Listener::Watchpoint) {
+            Some(listener) => {
+                listener.send(Event::WatchpointFailed(msg)).wait().unwrap();
+            }
+            None => {}
+        }
+    }
+
+    fn printed_variables(&mut self) {
+        self.variable_depth = 0;
+        let variables: Vec<VariableDetails> = self
+            .variables
+            .drain(..)
+            .map(|(name, type_, value)| VariableDetails::new(name, type_, value, 0))
+            .collect();
+        let listener = match self.listeners.remove(&Listener::Locals) {
+            Some(listener) => Some(listener),
+            None => self.listeners.remove(&Listener::ExpandVariable),
+        };
+        match listener {
+            Some(listener) => {
+                listener.send(Event::Variables(variables)).wait().unwrap();
+            }
+            None => {}
+        }
+    }
+
+    /// The result of an expression is printed as JSON with either the error or its value
+    fn evaluated(&mut self, result: &str) {
+        let result: serde_json::Value = match serde_json::from_str(result) {
+            Ok(result) => result,
+            Err(_) => return,
+        };
+
+        let event = match result["error"].as_str() {
+            Some(error) => Event::EvaluateFailed(error.trim().to_string()),
+            None => Event::Evaluated(VariableValue::new(
+                result["type"].as_str().unwrap_or("").to_string(),
+                result["value"].as_str().unwrap_or("").to_string(),
+            )),
+        };
+
+        match self.listeners.remove(&Listener::Evaluate) {
+            Some(listener) => {
+                listener.send(event).wait().unwrap();
+            }
+            None => {}
+        }
+    }
+
+    fn printed_registers(&mut self, result: &str) {
+        let result: serde_json::Value = match serde_json::from_str(result) {
+            Ok(result) => result,
+            Err(_) => return,
+        };
+
+        let event = match result["error"].as_str() {
+            Some(error) => Event::RegistersFailed(error.trim().to_string()),
+            None => {
+                let sets = match result["sets"].as_array() {
+                    Some(sets) => sets,
+                    None => return,
+                };
+                Event::Registers(
+                    sets.iter()
+                        .map(|set| {
+                            let registers = match set["registers"].as_array() {
+                                Some(registers) => registers
+                                    .iter()
+                                    .map(|register| {
+                                        (
+                                            register["name"].as_str().unwrap_or("").to_string(),
+                                            register["value"].as_str().unwrap_or("").to_string(),
+                                        )
+                                    })
+                                    .collect(),
+                                None => vec![],
+                            };
+                            (set["name"].as_str().unwrap_or("").to_string(), registers)
+                        })
+                        .collect(),
+                )
+            }
+        };
+
+        match self.listeners.remove(&Listener::Registers) {
+            Some(listener) => {
+                listener.send(event).wait().unwrap();
+            }
+            None => {}
+        }
+    }
+
+    /// Writing a register prints JSON with the register's name and either the error or its new
+    /// value
+    fn register_written(&mut self, result: &str) {
+        let result: serde_json::Value = match serde_json::from_str(result) {
+            Ok(result) => result,
+            Err(_) => return,
+        };
+
+        let event = match result["error"].as_str() {
+            Some(error) => {
+                Event::RegistersFailed(error.trim().trim_start_matches("error: ").to_string())
+            }
+            None => Event::RegisterWritten(
+                result["name"].as_str().unwrap_or("").to_string(),
+                result["value"].as_str().unwrap_or("").to_string(),
+            ),
+        };
+
+        match self.listeners.remove(&Listener::WriteRegister) {
+            Some(listener) => {
+                listener.send(event).wait().unwrap();
+            }
+            None => {}
+        }
+    }
+
+    fn printed_threads(&mut self) {
+        let threads = self.threads.drain(..).collect();
+        match self.listeners.remove(&Listener::Threads) {
+            Some(listener) => {
+                listener.send(Event::Threads(threads)).wait().unwrap();
+            }
+            None => {}
+        }
+    }
+
+    fn thread_selected(&mut self, index: u64) {
+        match self.listeners.remove(&Listener::SelectThread) {
+            Some(listener) => {
+                listener.send(Event::ThreadSelected(index)).wait().unwrap();
+            }
+            None => {}
+        }
+    }
+
+    fn thread_not_found(&mut self, msg: String) {
+        match self.listeners.remove(&Listener::SelectThread) {
+            Some(listener) => {
+                listener.send(Event::ThreadNotFound(msg)).wait().unwrap();
+            }
+            None => {}
+        }
+    }
+
+    fn frame_selected(&mut self, index: u64) {
+        match self.listeners.remove(&Listener::SelectFrame) {
+            Some(listener) => {
+                listener.send(Event::FrameSelected(index)).wait().unwrap();
+            }
+            None => {}
+        }
+    }
+
+    fn frame_not_found(&mut self, msg: String) {
+        match self.listeners.remove(&Listener::SelectFrame) {
+            Some(listener) => {
+                listener.send(Event::FrameNotFound(msg)).wait().unwrap();
             }
             None => {}
         }
     }
 
-    fn jump_to_position(&mut self, file: String, line: u64) {
-        jump_to_position(&file, line);
+    /// The process has stopped or exited, if we were stepping out of a function report it with
+    /// the return value if LLDB gave us one.
+    fn stopped(&mut self) {
+        let return_value = self.return_value.take();
+        match self.listeners.remove(&Listener::StepOut) {
+            Some(listener) => {
+                listener
+                    .send(Event::SteppedOut(return_value))
+                    .wait()
+                    .unwrap();
+            }
+            None => {}
+        }
+        match self.listeners.remove(&Listener::ProcessStopped) {
+            Some(listener) => {
+                listener.send(Event::ProcessStopped).wait().unwrap();
+            }
+            None => {}
+        }
     }
 
-    fn jump_to_unknown_position(&mut self) {
-        log_msg(LogLevel::WARN, "Stopped at unknown position");
+    fn printed_backtrace(&mut self) {
+        let frames = self.backtrace.drain(..).collect();
+        match self.listeners.remove(&Listener::Backtrace) {
+            Some(listener) => {
+                listener.send(Event::Backtrace(frames)).wait().unwrap();
+            }
+            None => {}
+        }
+    }
+
+    fn disassembled(&mut self) {
+        let instructions = self.disassembly.drain(..).collect();
+        self.disassembly_symbol = None;
+        match self.listeners.remove(&Listener::Disassemble) {
+            Some(listener) => {
+                listener
+                    .send(Event::Disassembly(instructions))
+                    .wait()
+                    .unwrap();
+            }
+            None => {}
+        }
+    }
+
+    fn disassemble_failed(&mut self, msg: String) {
+        self.disassembly.clear();
+        self.disassembly_symbol = None;
+        match self.listeners.remove(&Listener::Disassemble) {
+            Some(listener) => {
+                listener.send(Event::DisassembleFailed(msg)).wait().unwrap();
+            }
+            None => {}
+        }
+    }
+
+    fn report_watchpoint_hit(&mut self) {
+        match self.watchpoint_hit.take() {
+            Some(hit) => {
+                let old_value = match &hit.old_value {
+                    Some(v) => Some(v.as_str()),
+                    None => None,
+                };
+                let new_value = match &hit.new_value {
+                    Some(v) => v.as_str(),
+                    None => "",
+                };
+                watchpoint_hit(hit.id, old_value, new_value);
+            }
+            None => {}
+        }
+    }
+
+    /// Jump to where we are, when we've stopped we say which thread stopped
+    fn jump_to_position(&mut self, file: String, line: u64, thread: Option<u64>) {
+        match thread {
+            Some(thread) => jump_to_thread_position(&file, line, thread),
+            None => jump_to_position(&file, line),
+        }
+    }
+
+    /// When there's no source for where we've stopped we say which address we're at so that the
+    /// instructions can be shown instead
+    fn jump_to_unknown_position(&mut self, address: Option<String>, thread: Option<u64>) {
+        match address {
+            Some(address) => jump_to_address(&address, thread),
+            None => log_msg(LogLevel::WARN, "Stopped at unknown position"),
+        }
     }
 
     fn printed_variable(&mut self, variable: String, variable_type: String, data: &str) {
@@ -459,3 +1262,185 @@ impl Analyser {
         }
     }
 }
+
+/// The number of braces opened less the number closed in part of a value, ignoring any in
+/// strings and characters
+fn brace_depth(s: &str) -> i64 {
+    let mut depth = 0;
+    let mut quote = None;
+    let mut escaped = false;
+
+    for c in s.chars() {
+        match quote {
+            Some(q) => {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == q {
+                    quote = None;
+                }
+            }
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            },
+        }
+    }
+
+    depth
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::{brace_depth, Analyser, Event, Listener};
+    use crate::debugger::{BreakpointRegistry, StackFrame, VariableDetails};
+
+    use tokio::prelude::*;
+    use tokio::sync::mpsc;
+
+    #[test]
+    fn check_stop_while_getting_backtrace() {
+        let mut analyser = Analyser::new(Arc::new(Mutex::new(BreakpointRegistry::new())));
+        let (tx, backtrace_rx) = mpsc::channel(1);
+        analyser.add_listener(Listener::Backtrace, tx);
+        let (tx, stopped_rx) = mpsc::channel(1);
+        analyser.add_listener(Listener::ProcessStopped, tx);
+
+        analyser.analyse_stdout("* thread #1: tid = 0x1234\n  * frame #0 at /tmp/a.c:3 in main\n");
+        analyser.analyse_stdout(
+            "Process 42 stopped\n\
+             * thread #1, name = 'a', stop reason = breakpoint 2.1\n    \
+             frame #0 at /tmp/a.c:5 in main pc 0x1010\n",
+        );
+        analyser.analyse_stdout("PADRE backtrace end\n");
+
+        assert_eq!(
+            stopped_rx.wait().next().unwrap().unwrap(),
+            Event::ProcessStopped
+        );
+        assert_eq!(
+            backtrace_rx.wait().next().unwrap().unwrap(),
+            Event::Backtrace(vec![StackFrame::new(
+                0,
+                "main".to_string(),
+                Some("/tmp/a.c".to_string()),
+                Some(3),
+                None
+            )])
+        );
+    }
+
+    #[test]
+    fn check_timed_out_listener_removed() {
+        let mut analyser = Analyser::new(Arc::new(Mutex::new(BreakpointRegistry::new())));
+        let (tx, _rx) = mpsc::channel(1);
+        analyser.add_listener(Listener::Threads, tx);
+
+        analyser.analyse_stdout("* thread #1: tid = 0x1234, name = 'a'\n");
+        analyser.remove_listener(&Listener::Threads);
+
+        assert!(!analyser.listeners.contains_key(&Listener::Threads));
+        assert!(analyser.threads.is_empty());
+    }
+
+    #[test]
+    fn check_locals_stop_at_end_of_value() {
+        let mut analyser = Analyser::new(Arc::new(Mutex::new(BreakpointRegistry::new())));
+        let (tx, rx) = mpsc::channel(1);
+        analyser.add_listener(Listener::Locals, tx);
+
+        analyser.analyse_stdout(
+            "(int) i = 3\n\
+             (Foo) f = {\n  \
+             s = 0x0000000100000f9e \"}\"\n\
+             }\n\
+             Process 42 resuming\n\
+             PADRE variables end\n",
+        );
+
+        assert_eq!(
+            rx.wait().next().unwrap().unwrap(),
+            Event::Variables(vec![
+                VariableDetails::new("i".to_string(), "int".to_string(), "3".to_string(), 0),
+                VariableDetails::new(
+                    "f".to_string(),
+                    "Foo".to_string(),
+                    "{\n  s = 0x0000000100000f9e \"}\"\n}".to_string(),
+                    0
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn check_brace_depth() {
+        assert_eq!(brace_depth("{"), 1);
+        assert_eq!(brace_depth("{...}"), 0);
+        assert_eq!(brace_depth("0x1 \"{\\\"\""), 0);
+        assert_eq!(brace_depth("'}'"), 0);
+        assert_eq!(brace_depth("}"), -1);
+    }
+
+    #[test]
+    fn check_error_goes_to_first_command_waiting() {
+        let mut analyser = Analyser::new(Arc::new(Mutex::new(BreakpointRegistry::new())));
+        let (tx, _watchpoint_rx) = mpsc::channel(1);
+        analyser.add_listener(Listener::Watchpoint, tx);
+        let (tx, frame_rx) = mpsc::channel(1);
+        analyser.add_listener(Listener::SelectFrame, tx);
+        let (tx, thread_rx) = mpsc::channel(1);
+        analyser.add_listener(Listener::SelectThread, tx);
+
+        // The watchpoint was set so only the frame selection is waiting before the thread's
+        analyser.analyse_stdout("Watchpoint created: Watchpoint 1: addr = 0x1000\n");
+        analyser.analyse_stderr("error: Frame index (9) out of range.\n");
+
+        assert_eq!(
+            frame_rx.wait().next().unwrap().unwrap(),
+            Event::FrameNotFound("Frame index (9) out of range.".to_string())
+        );
+        assert!(analyser.listeners.contains_key(&Listener::SelectThread));
+
+        analyser.analyse_stderr("error: invalid thread #9.\n");
+
+        assert_eq!(
+            thread_rx.wait().next().unwrap().unwrap(),
+            Event::ThreadNotFound("invalid thread #9.".to_string())
+        );
+    }
+
+    #[test]
+    fn check_register_written() {
+        let mut analyser = Analyser::new(Arc::new(Mutex::new(BreakpointRegistry::new())));
+        let (tx, rx) = mpsc::channel(1);
+        analyser.add_listener(Listener::WriteRegister, tx);
+
+        analyser.analyse_stdout(
+            "PADRE register written {\"name\": \"rax\", \"error\": null, \"value\": \"0x05\"}\n",
+        );
+
+        assert_eq!(
+            rx.wait().next().unwrap().unwrap(),
+            Event::RegisterWritten("rax".to_string(), "0x05".to_string())
+        );
+
+        let (tx, rx) = mpsc::channel(1);
+        analyser.add_listener(Listener::WriteRegister, tx);
+
+        analyser.analyse_stdout(
+            "PADRE register written {\"name\": \"rax\", \
+             \"error\": \"error: Failed to write register 'rax' with value 'x'\\n\", \
+             \"value\": \"0x05\"}\n",
+        );
+
+        assert_eq!(
+            rx.wait().next().unwrap().unwrap(),
+            Event::RegistersFailed("Failed to write register 'rax' with value 'x'".to_string())
+        );
+    }
+}
@@ -2,12 +2,20 @@
 //!
 //! Analyses the messages that come from the WebSocket connection to Node Debugger
 
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 
 use super::ws::WSHandler;
-use crate::debugger::FileLocation;
-use crate::notifier::{breakpoint_set, jump_to_position, log_msg, signal_exited, LogLevel};
-
+use crate::debugger::{
+    parse_log_message, BreakpointCondition, BreakpointRegistry, BreakpointStatus, FileLocation,
+    FrameSelection, LogMessagePart, StackFrame, ThreadDetails,
+};
+use crate::notifier::{
+    breakpoint_set, exception_thrown, jump_to_position, log_msg, logpoint_hit, signal_exited,
+    LogLevel,
+};
+
+use regex::Regex;
 use tokio::prelude::*;
 use websocket::OwnedMessage;
 
@@ -31,23 +39,40 @@ impl Script {
     pub fn get_script_id(&self) -> &str {
         &self.script_id
     }
+
+    pub fn get_file(&self) -> &str {
+        &self.file
+    }
 }
 
 #[derive(Debug)]
 pub struct Analyser {
     scripts: Vec<Script>,
-    pending_breakpoints: Vec<FileLocation>,
+    breakpoints: Arc<Mutex<BreakpointRegistry>>,
     ws_handler: Arc<Mutex<WSHandler>>,
     pid: Option<u64>,
+    call_frames: Vec<serde_json::Value>,
+    selected_frame: u64,
+    // Worker thread IDs, titles and session IDs
+    workers: Vec<(u64, String, String)>,
+    // Script ID and line number (0-indexed) we're running to while ignoring breakpoints
+    run_to_line: Option<(String, u64)>,
 }
 
 impl Analyser {
-    pub fn new(ws_handler: Arc<Mutex<WSHandler>>) -> Self {
+    pub fn new(
+        ws_handler: Arc<Mutex<WSHandler>>,
+        breakpoints: Arc<Mutex<BreakpointRegistry>>,
+    ) -> Self {
         Analyser {
             scripts: vec![],
-            pending_breakpoints: vec![],
+            breakpoints,
             ws_handler,
             pid: None,
+            call_frames: vec![],
+            selected_frame: 0,
+            workers: vec![],
+            run_to_line: None,
         }
     }
 
@@ -60,22 +85,28 @@ impl Analyser {
         };
 
         match method.as_ref() {
-            "Runtime.consoleAPICalled" => {}
+            "Runtime.consoleAPICalled" => self.analyse_console_api_called(msg),
             "Runtime.executionContextCreated" => {}
             "Runtime.executionContextDestroyed" => {
                 match self.pid {
                     Some(pid) => signal_exited(pid, 0),
                     None => {}
                 };
-                self.ws_handler.lock().unwrap().close()
+                self.ws_handler.lock().unwrap().disconnect()
             }
-            "Runtime.exceptionThrown" => println!("TODO: Code {:?}", msg),
+            "Runtime.exceptionThrown" => self.analyse_exception_thrown(msg),
             "Debugger.paused" => self.analyse_debugger_paused(msg),
-            "Debugger.resumed" => {}
+            "Debugger.resumed" => {
+                self.call_frames = vec![];
+                self.selected_frame = 0;
+            }
             "Debugger.scriptFailedToParse" => {
                 log_msg(LogLevel::WARN, &format!("Can't parse script: {:?}", msg))
             }
             "Debugger.scriptParsed" => self.analyse_script_parsed(msg),
+            "NodeWorker.attachedToWorker" => self.analyse_attached_to_worker(msg),
+            "NodeWorker.detachedFromWorker" => self.analyse_detached_from_worker(msg),
+            "NodeWorker.receivedMessageFromWorker" => {}
             _ => panic!("Can't understand message type: {:?}", method),
         }
     }
@@ -89,14 +120,168 @@ impl Analyser {
         None
     }
 
-    pub fn add_pending_breakpoint(&mut self, bkpt: FileLocation) {
-        self.pending_breakpoints.push(bkpt);
+    pub fn get_script_from_id(&self, script_id: &str) -> Option<&Script> {
+        for script in &self.scripts {
+            if &script.script_id == script_id {
+                return Some(script);
+            }
+        }
+        None
+    }
+
+    /// The ID of the selected call frame if we're paused
+    pub fn get_call_frame_id(&self) -> Option<String> {
+        self.get_call_frame_id_at(self.selected_frame)
+    }
+
+    /// The ID of the call frame at the index given if we're paused and it exists
+    pub fn get_call_frame_id_at(&self, index: u64) -> Option<String> {
+        match self.call_frames.get(index as usize) {
+            Some(call_frame) => call_frame["callFrameId"].as_str().map(|s| s.to_string()),
+            None => None,
+        }
+    }
+
+    /// The backtrace from when we last paused
+    pub fn get_backtrace(&self) -> Vec<StackFrame> {
+        self.call_frames
+            .iter()
+            .enumerate()
+            .map(|(index, call_frame)| {
+                let mut file = call_frame["url"].as_str().unwrap_or("").to_string();
+                if file.len() > 7 && &file[0..7] == "file://" {
+                    file = file[7..].to_string()
+                }
+
+                // Node doesn't always give the URL so fall back to the script
+                if file.is_empty() {
+                    match call_frame["location"]["scriptId"]
+                        .as_str()
+                        .and_then(|script_id| self.get_script_from_id(script_id))
+                    {
+                        Some(script) => file = script.get_file().to_string(),
+                        None => {}
+                    }
+                }
+
+                let file = match file.is_empty() {
+                    true => None,
+                    false => Some(file),
+                };
+
+                let line = call_frame["location"]["lineNumber"]
+                    .as_u64()
+                    .map(|line| line + 1);
+
+                StackFrame::new(
+                    index as u64,
+                    call_frame["functionName"]
+                        .as_str()
+                        .unwrap_or("")
+                        .to_string(),
+                    file,
+                    line,
+                    None,
+                )
+            })
+            .collect()
+    }
+
+    /// The IDs of the objects for each scope of the selected call frame apart from the global
+    /// scope, `None` if we're not paused
+    pub fn get_scope_object_ids(&self) -> Option<Vec<String>> {
+        let call_frame = self.call_frames.get(self.selected_frame as usize)?;
+
+        let object_ids = match call_frame["scopeChain"].as_array() {
+            Some(scopes) => scopes
+                .iter()
+                .filter(|scope| scope["type"] != "global")
+                .filter_map(|scope| scope["object"]["objectId"].as_str())
+                .map(|object_id| object_id.to_string())
+                .collect(),
+            None => vec![],
+        };
+
+        Some(object_ids)
+    }
+
+    /// The scope numbers and object IDs of the scopes of the selected call frame that Node can set
+    /// variables in, `None` if we're not paused
+    pub fn get_settable_scopes(&self) -> Option<Vec<(u64, String)>> {
+        let call_frame = self.call_frames.get(self.selected_frame as usize)?;
+
+        let scopes = match call_frame["scopeChain"].as_array() {
+            Some(scopes) => scopes
+                .iter()
+                .enumerate()
+                .filter(|(_, scope)| {
+                    scope["type"] == "local"
+                        || scope["type"] == "closure"
+                        || scope["type"] == "catch"
+                })
+                .filter_map(|(scope_number, scope)| {
+                    scope["object"]["objectId"]
+                        .as_str()
+                        .map(|object_id| (scope_number as u64, object_id.to_string()))
+                })
+                .collect(),
+            None => vec![],
+        };
+
+        Some(scopes)
+    }
+
+    /// Select a call frame for evaluating in and jump to it, returns the index of the frame
+    /// selected or `None` if there's no such frame
+    pub fn select_frame(&mut self, frame: FrameSelection) -> Option<u64> {
+        let index = frame.target(self.selected_frame)?;
+        let stack_frame = self.get_backtrace().into_iter().nth(index as usize)?;
+
+        self.selected_frame = index;
+
+        match (&stack_frame.file, stack_frame.line) {
+            (Some(file), Some(line)) => jump_to_position(file, line),
+            _ => log_msg(LogLevel::WARN, "Frame has no known position"),
+        }
+
+        Some(index)
+    }
+
+    /// The main thread, with where it's paused if it is, followed by the worker threads
+    pub fn get_threads(&self) -> Vec<ThreadDetails> {
+        let (file, line) = match self.get_backtrace().into_iter().next() {
+            Some(stack_frame) => (stack_frame.file, stack_frame.line),
+            None => (None, None),
+        };
+
+        let mut threads = vec![ThreadDetails::new(0, "main".to_string(), true, file, line)];
+
+        for (id, title, _) in &self.workers {
+            threads.push(ThreadDetails::new(*id, title.clone(), false, None, None));
+        }
+
+        threads
+    }
+
+    /// Continue to the line again whenever we pause on a breakpoint until we reach it
+    pub fn ignore_breakpoints_until(&mut self, script_id: String, line_num: u64) {
+        self.run_to_line = Some((script_id, line_num));
     }
 
     pub fn set_pid(&mut self, pid: u64) {
         self.pid = Some(pid);
     }
 
+    /// Forget everything about the last Node process, scripts are parsed again when it's run
+    pub fn reset(&mut self) {
+        self.scripts.clear();
+        self.pid = None;
+        self.call_frames.clear();
+        self.selected_frame = 0;
+        self.workers.clear();
+        self.run_to_line = None;
+    }
+
     fn analyse_script_parsed(&mut self, mut msg: serde_json::Value) {
         let mut is_internal = true;
 
@@ -121,64 +306,184 @@ impl Analyser {
             }
         };
 
-        // TODO: drain_filter if/when it's stable in Rust
-        let mut i = 0;
-
-        while i != self.pending_breakpoints.len() {
-            if self.pending_breakpoints[i].name == file {
-                let bkpt = self.pending_breakpoints.remove(i);
-
-                let msg = OwnedMessage::Text(format!(
-                    "{{\
-                     \"method\":\"Debugger.setBreakpoint\",\
-                     \"params\":{{\
-                     \"location\":{{\
-                     \"scriptId\":\"{}\",\
-                     \"lineNumber\":{}\
-                     }}\
-                     }}\
-                     }}",
-                    script_id,
-                    bkpt.line_num - 1
-                ));
+        let pending_breakpoints = self.breakpoints.lock().unwrap().pending();
+
+        for bkpt in pending_breakpoints {
+            let file_location = match bkpt.file_location() {
+                Some(fl) => fl,
+                None => continue,
+            };
+
+            // Scripts on a remote machine don't exist locally
+            let bkpt_file = match Path::new(&file_location.name).canonicalize() {
+                Ok(s) => s.to_string_lossy().to_string(),
+                Err(_) => file_location.name.clone(),
+            };
+
+            if bkpt_file == file {
+                let msg = OwnedMessage::Text(
+                    set_breakpoint_msg(
+                        &script_id,
+                        file_location,
+                        bkpt.condition(),
+                        bkpt.log_message(),
+                    )
+                    .to_string(),
+                );
 
                 let file = file.clone();
 
                 let ws_handler = self.ws_handler.clone();
+                let breakpoints = self.breakpoints.clone();
+                let breakpoints2 = self.breakpoints.clone();
+                let id = bkpt.id();
+                let bkpt = file_location.clone();
 
                 tokio::spawn(
                     ws_handler
                         .lock()
                         .unwrap()
                         .send_and_receive_message(msg)
-                        .map(move |response| {
+                        .map(move |mut response| {
                             if response["error"].is_null() {
+                                let breakpoint_id = serde_json::from_value(
+                                    response["result"]["breakpointId"].take(),
+                                )
+                                .unwrap();
                                 breakpoint_set(&file, bkpt.line_num);
+                                let mut breakpoints = breakpoints.lock().unwrap();
+                                breakpoints.set_native_id_by_id(id, breakpoint_id);
+                                breakpoints.set_status_by_id(id, BreakpointStatus::Resolved);
                             } else {
                                 log_msg(
-                                    LogLevel::CRITICAL,
+                                    LogLevel::ERROR,
                                     &format!("Can't set breakpoint {:?}", bkpt),
                                 );
-                                panic!("Can't set breakpoint, panicking");
+                                breakpoints
+                                    .lock()
+                                    .unwrap()
+                                    .set_status_by_id(id, BreakpointStatus::Failed);
                             }
                         })
-                        .map_err(|e| {
+                        .map_err(move |e| {
                             log_msg(
-                                LogLevel::CRITICAL,
+                                LogLevel::ERROR,
                                 &format!("Can't set breakpoint, error: {}", e),
                             );
-                            panic!("Can't set breakpoint, panicking");
+                            breakpoints2
+                                .lock()
+                                .unwrap()
+                                .set_status_by_id(id, BreakpointStatus::Failed);
                         }),
                 );
-            } else {
-                i += 1;
             }
         }
 
         self.scripts.push(Script::new(file, script_id, is_internal));
     }
 
-    fn analyse_debugger_paused(&self, mut msg: serde_json::Value) {
+    fn analyse_attached_to_worker(&mut self, msg: serde_json::Value) {
+        let worker_info = &msg["params"]["workerInfo"];
+
+        let id = match worker_info["workerId"].as_str() {
+            Some(id) => match id.parse::<u64>() {
+                Ok(id) => id,
+                Err(_) => return,
+            },
+            None => return,
+        };
+
+        let title = worker_info["title"].as_str().unwrap_or("").to_string();
+        let session_id = msg["params"]["sessionId"]
+            .as_str()
+            .unwrap_or("")
+            .to_string();
+
+        self.workers.push((id, title, session_id));
+    }
+
+    fn analyse_detached_from_worker(&mut self, msg: serde_json::Value) {
+        match msg["params"]["sessionId"].as_str() {
+            Some(session_id) => self.workers.retain(|(_, _, s)| s != session_id),
+            None => {}
+        }
+    }
+
+    fn analyse_console_api_called(&self, msg: serde_json::Value) {
+        lazy_static! {
+            static ref RE_LOGPOINT: Regex =
+                Regex::new("^PADRE logpoint (.*):(\\d+): (.*)$").unwrap();
+        }
+
+        match msg["params"]["args"][0]["value"].as_str() {
+            Some(s) => {
+                for cap in RE_LOGPOINT.captures_iter(s) {
+                    let file = cap[1].to_string();
+                    let line = cap[2].parse::<u64>().unwrap();
+                    logpoint_hit(&file, line, &cap[3]);
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn analyse_exception_thrown(&self, msg: serde_json::Value) {
+        let details = &msg["params"]["exceptionDetails"];
+
+        let mut file = details["url"].as_str().unwrap_or("").to_string();
+        if file.len() > 7 && &file[0..7] == "file://" {
+            file = file[7..].to_string()
+        }
+
+        let line_num = details["lineNumber"].as_u64().unwrap_or(0) + 1;
+
+        let description = match details["exception"]["description"].as_str() {
+            Some(s) => s,
+            None => details["text"].as_str().unwrap_or(""),
+        };
+
+        exception_thrown(&file, line_num, exception_message(description));
+    }
+
+    fn analyse_debugger_paused(&mut self, mut msg: serde_json::Value) {
+        match self.run_to_line.take() {
+            Some((script_id, line_num)) => {
+                let location = &msg["params"]["callFrames"][0]["location"];
+                let reached = location["scriptId"] == script_id.as_str()
+                    && location["lineNumber"] == line_num;
+                let hit_breakpoint = match msg["params"]["hitBreakpoints"].as_array() {
+                    Some(a) => !a.is_empty(),
+                    None => false,
+                };
+
+                // Node forgets the location to continue to when it pauses elsewhere
+                if hit_breakpoint && !reached {
+                    let msg = OwnedMessage::Text(
+                        continue_to_location_msg(&script_id, line_num).to_string(),
+                    );
+                    self.run_to_line = Some((script_id, line_num));
+                    tokio::spawn(
+                        self.ws_handler
+                            .lock()
+                            .unwrap()
+                            .send_and_receive_message(msg)
+                            .map(|_| {})
+                            .map_err(|e| {
+                                log_msg(LogLevel::ERROR, &format!("Can't resume, error: {}", e));
+                            }),
+                    );
+                    return;
+                }
+            }
+            None => {}
+        }
+
+        self.call_frames = match msg["params"]["callFrames"].as_array() {
+            Some(call_frames) => call_frames.clone(),
+            None => vec![],
+        };
+        self.selected_frame = 0;
+
         let file: String =
             match serde_json::from_value(msg["params"]["callFrames"][0]["url"].take()) {
                 Ok(s) => {
@@ -194,6 +499,18 @@ impl Analyser {
                 }
             };
 
+        // Node doesn't give the URL for scripts that ran before we attached
+        let file = match file.as_str() {
+            "" => match msg["params"]["callFrames"][0]["location"]["scriptId"].as_str() {
+                Some(script_id) => match self.get_script_from_id(script_id) {
+                    Some(script) => script.file.clone(),
+                    None => file,
+                },
+                None => file,
+            },
+            _ => file,
+        };
+
         let line_num: u64 = match serde_json::from_value(
             msg["params"]["callFrames"][0]["location"]["lineNumber"].take(),
         ) {
@@ -206,16 +523,98 @@ impl Analyser {
             }
         };
 
+        match msg["params"]["reason"].as_str() {
+            Some("exception") | Some("promiseRejection") => {
+                let description = msg["params"]["data"]["description"].as_str().unwrap_or("");
+                exception_thrown(&file, line_num, exception_message(description));
+            }
+            _ => {}
+        }
+
         jump_to_position(&file, line_num);
     }
 }
 
+/// Build the `Debugger.continueToLocation` message for a line (0-indexed) in a script
+pub fn continue_to_location_msg(script_id: &str, line_num: u64) -> serde_json::Value {
+    serde_json::json!({
+        "method": "Debugger.continueToLocation",
+        "params": {
+            "location": {
+                "scriptId": script_id,
+                "lineNumber": line_num,
+            },
+            "targetCallFrames": "any",
+        },
+    })
+}
+
+/// Node describes exceptions with the message followed by the stack trace, we just want the
+/// message
+pub fn exception_message(description: &str) -> &str {
+    description.lines().next().unwrap_or("")
+}
+
+/// Build the `Debugger.setBreakpoint` message for a breakpoint or logpoint in a script
+///
+/// Node has no notion of a hit count so we keep a count per breakpoint in a global and only
+/// stop once it has been reached. Logpoints log the message in the condition and never stop.
+pub fn set_breakpoint_msg(
+    script_id: &str,
+    file_location: &FileLocation,
+    condition: &BreakpointCondition,
+    log_message: Option<&str>,
+) -> serde_json::Value {
+    let hits = format!(
+        "(globalThis.__padreHits = globalThis.__padreHits || {{}}, \
+         globalThis.__padreHits[{key}] = (globalThis.__padreHits[{key}] || 0) + 1)",
+        key = serde_json::json!(format!("{}:{}", file_location.name, file_location.line_num)),
+    );
+
+    let expression = match (log_message, &condition.condition, condition.hit_count) {
+        (Some(msg), _, _) => logpoint_expression(file_location, msg),
+        (None, Some(c), Some(n)) => format!("({}) && {} >= {}", c, hits, n),
+        (None, Some(c), None) => c.clone(),
+        (None, None, Some(n)) => format!("{} >= {}", hits, n),
+        (None, None, None) => "".to_string(),
+    };
+
+    serde_json::json!({
+        "method": "Debugger.setBreakpoint",
+        "params": {
+            "location": {
+                "scriptId": script_id,
+                "lineNumber": file_location.line_num - 1,
+            },
+            "condition": expression,
+        },
+    })
+}
+
+fn logpoint_expression(file_location: &FileLocation, log_message: &str) -> String {
+    let mut message = vec![serde_json::json!(format!(
+        "PADRE logpoint {}:{}: ",
+        file_location.name, file_location.line_num
+    ))
+    .to_string()];
+
+    for part in parse_log_message(log_message) {
+        match part {
+            LogMessagePart::Text(t) => message.push(serde_json::json!(t).to_string()),
+            LogMessagePart::Expression(e) => message.push(format!("String(({}))", e)),
+        }
+    }
+
+    format!("console.log({}), false", message.join(" + "))
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::{Arc, Mutex};
 
     use super::super::ws::WSHandler;
     use super::Analyser;
+    use crate::debugger::{BreakpointCondition, BreakpointRegistry, FileLocation};
 
     #[test]
     fn check_internal_script_parsed() {
@@ -243,7 +642,7 @@ mod tests {
             }
         );
         let ws = Arc::new(Mutex::new(WSHandler::new()));
-        let mut analyser = Analyser::new(ws);
+        let mut analyser = Analyser::new(ws, Arc::new(Mutex::new(BreakpointRegistry::new())));
 
         analyser.analyse_message(msg);
 
@@ -303,7 +702,7 @@ mod tests {
         );
 
         let ws = Arc::new(Mutex::new(WSHandler::new()));
-        let mut analyser = Analyser::new(ws);
+        let mut analyser = Analyser::new(ws, Arc::new(Mutex::new(BreakpointRegistry::new())));
 
         analyser.analyse_message(msg);
 
@@ -316,7 +715,7 @@ mod tests {
     #[test]
     fn test_get_existing_script_from_filename() {
         let ws = Arc::new(Mutex::new(WSHandler::new()));
-        let mut analyser = Analyser::new(ws);
+        let mut analyser = Analyser::new(ws, Arc::new(Mutex::new(BreakpointRegistry::new())));
         let script = super::Script::new("exists.js".to_string(), "52".to_string(), false);
         let expected_script = super::Script::new("exists.js".to_string(), "52".to_string(), false);
         analyser.scripts.push(script);
@@ -329,7 +728,57 @@ mod tests {
     #[test]
     fn test_get_no_script_from_filename() {
         let ws = Arc::new(Mutex::new(WSHandler::new()));
-        let analyser = Analyser::new(ws);
+        let analyser = Analyser::new(ws, Arc::new(Mutex::new(BreakpointRegistry::new())));
         assert_eq!(analyser.get_script_from_filename("not_exists.js"), None);
     }
+
+    #[test]
+    fn check_set_breakpoint_msg() {
+        let fl = FileLocation::new("test.js".to_string(), 12);
+
+        let msg = super::set_breakpoint_msg("52", &fl, &BreakpointCondition::default(), None);
+        assert_eq!(
+            msg,
+            serde_json::json!({
+                "method": "Debugger.setBreakpoint",
+                "params": {
+                    "location": {"scriptId": "52", "lineNumber": 11},
+                    "condition": "",
+                },
+            })
+        );
+
+        let msg = super::set_breakpoint_msg(
+            "52",
+            &fl,
+            &BreakpointCondition::new(Some("i > 3".to_string()), None),
+            None,
+        );
+        assert_eq!(msg["params"]["condition"], "i > 3");
+
+        let msg = super::set_breakpoint_msg(
+            "52",
+            &fl,
+            &BreakpointCondition::new(Some("i > 3".to_string()), Some(2)),
+            None,
+        );
+        assert_eq!(
+            msg["params"]["condition"],
+            "(i > 3) && (globalThis.__padreHits = globalThis.__padreHits || {}, \
+             globalThis.__padreHits[\"test.js:12\"] = \
+             (globalThis.__padreHits[\"test.js:12\"] || 0) + 1) >= 2"
+        );
+    }
+
+    #[test]
+    fn check_set_logpoint_msg() {
+        let fl = FileLocation::new("test.js".to_string(), 12);
+
+        let msg =
+            super::set_breakpoint_msg("52", &fl, &BreakpointCondition::default(), Some("i is {i}"));
+        assert_eq!(
+            msg["params"]["condition"],
+            "console.log(\"PADRE logpoint test.js:12: \" + \"i is \" + String((i))), false"
+        );
+    }
 }
@@ -10,12 +10,17 @@ use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
-use super::analyser::Analyser;
-use super::process::Process;
+use super::analyser::{continue_to_location_msg, exception_message, set_breakpoint_msg, Analyser};
+use super::process::{inspector_websocket_url, Process};
 use super::ws::WSHandler;
 use crate::config::Config;
-use crate::debugger::{DebuggerV1, FileLocation, Variable};
-use crate::notifier::{breakpoint_set, log_msg, LogLevel};
+use crate::debugger::{
+    AttachTarget, BreakpointCondition, BreakpointRegistry, BreakpointStatus, DebuggerV1,
+    ExceptionBreakpointMode, FileLocation, FrameSelection, FunctionName, RegisterGroup, SourceMap,
+    Variable, VariableDetails, VariableReferences, WatchMode, WatchTarget,
+};
+use crate::notifier::{breakpoint_removed, breakpoint_set, log_msg, signal_exited, LogLevel};
+use crate::util::find_process_by_name;
 
 use tokio::prelude::*;
 use tokio::sync::mpsc;
@@ -26,43 +31,186 @@ pub struct ImplDebugger {
     process: Arc<Mutex<Process>>,
     ws_handler: Arc<Mutex<WSHandler>>,
     analyser: Arc<Mutex<Analyser>>,
+    breakpoints: Arc<Mutex<BreakpointRegistry>>,
+    exception_mode: ExceptionBreakpointMode,
+    variable_references: Arc<Mutex<VariableReferences>>,
+    // The inspector to connect to rather than running the program, either its websocket URL or
+    // the host and port it's listening on
+    remote: Option<String>,
 }
 
 impl ImplDebugger {
-    pub fn new(debugger_cmd: String, run_cmd: Vec<String>) -> ImplDebugger {
+    pub fn new(
+        debugger_cmd: String,
+        run_cmd: Vec<String>,
+        breakpoints: Arc<Mutex<BreakpointRegistry>>,
+        remote: Option<String>,
+    ) -> ImplDebugger {
         let process = Arc::new(Mutex::new(Process::new(debugger_cmd, run_cmd)));
         let ws_handler = Arc::new(Mutex::new(WSHandler::new()));
-        let analyser = Arc::new(Mutex::new(Analyser::new(ws_handler.clone())));
+        let analyser = Arc::new(Mutex::new(Analyser::new(
+            ws_handler.clone(),
+            breakpoints.clone(),
+        )));
         ImplDebugger {
             process,
             ws_handler,
             analyser,
+            breakpoints,
+            exception_mode: ExceptionBreakpointMode::None,
+            variable_references: Arc::new(Mutex::new(VariableReferences::new())),
+            remote,
         }
     }
-}
 
-impl DebuggerV1 for ImplDebugger {
-    fn setup(&mut self) {}
+    /// Get the properties of each object as variables, properties of later objects follow on from
+    /// earlier ones
+    fn get_properties(
+        &mut self,
+        object_ids: Vec<String>,
+    ) -> Box<dyn Future<Item = Vec<serde_json::Value>, Error = io::Error> + Send> {
+        let fs: Vec<_> = object_ids
+            .iter()
+            .map(|object_id| {
+                let msg = OwnedMessage::Text(
+                    serde_json::json!({
+                        "method": "Runtime.getProperties",
+                        "params": {
+                            "objectId": object_id,
+                            "ownProperties": true,
+                        },
+                    })
+                    .to_string(),
+                );
+                self.ws_handler
+                    .lock()
+                    .unwrap()
+                    .send_and_receive_message(msg)
+            })
+            .collect();
 
-    fn teardown(&mut self) {
-        exit(0);
+        let variable_references = self.variable_references.clone();
+
+        let f = future::join_all(fs).map(move |responses| {
+            let mut variables = vec![];
+            for response in responses {
+                match response["result"]["result"].as_array() {
+                    Some(properties) => {
+                        for property in properties {
+                            let variable = variable_details(
+                                property,
+                                &mut variable_references.lock().unwrap(),
+                            );
+                            variables.push(variable.to_json());
+                        }
+                    }
+                    None => {}
+                }
+            }
+            variables
+        });
+
+        Box::new(f)
     }
 
-    fn run(
+    /// Stop debugging the Node process, the breakpoints are all marked as pending ready for
+    /// running again as they're lost with the connection
+    fn disconnect(&mut self) {
+        self.ws_handler.lock().unwrap().disconnect();
+        self.analyser.lock().unwrap().reset();
+        self.breakpoints.lock().unwrap().reset();
+    }
+
+    /// We can only connect to a remote process, it can't be started or stopped from here
+    fn check_remote(
+        &self,
+        action: &str,
+    ) -> Option<Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>> {
+        match self.remote {
+            Some(_) => {
+                log_msg(
+                    LogLevel::WARN,
+                    &format!("Can't {} a remote process", action),
+                );
+                Some(Box::new(future::lazy(move || {
+                    let resp = serde_json::json!({"status":"ERROR"});
+                    Ok(resp)
+                })))
+            }
+            None => None,
+        }
+    }
+
+    /// There are no machine instructions or registers to debug
+    fn no_machine_code(
+        &self,
+        action: &str,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        log_msg(LogLevel::WARN, &format!("Can't {} in Node", action));
+
+        Box::new(future::lazy(move || {
+            let resp = serde_json::json!({"status":"ERROR"});
+            Ok(resp)
+        }))
+    }
+
+    /// Kill Node and stop debugging it
+    fn kill_process(&mut self) {
+        self.disconnect();
+
+        match self.process.lock().unwrap().kill() {
+            // Report it as killed by SIGKILL like LLDB does
+            Some(pid) => signal_exited(pid, 9),
+            None => {}
+        }
+    }
+
+    /// Connect to the inspector of a Node process running elsewhere, we're given the websocket's
+    /// URL or can ask the inspector for it
+    fn connect_remote(
         &mut self,
+        remote: &str,
         config: Arc<Mutex<Config>>,
     ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
-        log_msg(LogLevel::INFO, "Launching process");
+        log_msg(LogLevel::INFO, &format!("Connecting to {}", remote));
 
-        let (tx, rx) = mpsc::channel(1);
+        let uri = match remote.starts_with("ws://") {
+            true => Some(remote.to_string()),
+            false => inspector_websocket_url(remote),
+        };
 
-        self.process.lock().unwrap().run(tx);
+        let uri = match uri {
+            Some(uri) => uri,
+            None => {
+                log_msg(
+                    LogLevel::WARN,
+                    &format!("Can't find the inspector at {}", remote),
+                );
+                return Box::new(future::lazy(move || {
+                    let resp = serde_json::json!({"status":"ERROR"});
+                    Ok(resp)
+                }));
+            }
+        };
+
+        let (mut tx, rx) = mpsc::channel(1);
+        tx.try_send(uri).unwrap();
+
+        self.connect(rx, config)
+    }
 
+    /// Connect to the inspector's websocket once we know where it is and start debugging
+    fn connect(
+        &mut self,
+        rx: mpsc::Receiver<String>,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
         let process = self.process.clone();
         let analyser = self.analyser.clone();
         let analyser2 = self.analyser.clone();
         let ws_handler = self.ws_handler.clone();
         let ws_handler2 = self.ws_handler.clone();
+        let exception_mode = self.exception_mode;
 
         let f = rx
             .take(1)
@@ -92,12 +240,21 @@ impl DebuggerV1 for ImplDebugger {
                     .send_and_receive_message(msg);
                 let msg = OwnedMessage::Text("{\"method\":\"Debugger.enable\"}".to_string());
                 let f2 = ws_handler2.lock().unwrap().send_and_receive_message(msg);
+                let msg =
+                    OwnedMessage::Text(set_pause_on_exceptions_msg(exception_mode).to_string());
+                let f3 = ws_handler2.lock().unwrap().send_and_receive_message(msg);
                 let msg = OwnedMessage::Text(
                     "{\"method\":\"Runtime.runIfWaitingForDebugger\"}".to_string(),
                 );
-                let f3 = ws_handler2.lock().unwrap().send_and_receive_message(msg);
+                let f4 = ws_handler2.lock().unwrap().send_and_receive_message(msg);
+                // Only for finding worker threads so we don't fail if it's not supported
+                let msg = OwnedMessage::Text(
+                    "{\"method\":\"NodeWorker.enable\",\"params\":{\"waitForDebuggerOnStart\":false}}"
+                        .to_string(),
+                );
+                let f5 = ws_handler2.lock().unwrap().send_and_receive_message(msg);
 
-                f1.join(f2).join(f3)
+                f1.join5(f2, f3, f4, f5)
             })
             .timeout(Duration::new(
                 config
@@ -107,19 +264,22 @@ impl DebuggerV1 for ImplDebugger {
                     .unwrap() as u64,
                 0,
             ))
-            .map(move |responses| {
-                let resp1 = (responses.0).0;
-                let resp2 = (responses.0).1;
-                let resp3 = responses.1;
+            .map(move |(resp1, resp2, resp3, resp4, _)| {
                 if !resp1["error"].is_null()
                     || !resp2["error"].is_null()
                     || !resp3["error"].is_null()
+                    || !resp4["error"].is_null()
                 {
                     serde_json::json!({"status":"ERROR"})
                 } else {
-                    let pid = process.lock().unwrap().get_pid();
-                    analyser2.lock().unwrap().set_pid(pid);
-                    serde_json::json!({"status":"OK","pid":pid})
+                    // We don't know the PID of a remote process
+                    match process.lock().unwrap().get_pid() {
+                        Some(pid) => {
+                            analyser2.lock().unwrap().set_pid(pid);
+                            serde_json::json!({"status":"OK","pid":pid})
+                        }
+                        None => serde_json::json!({"status":"OK"}),
+                    }
                 }
             })
             .map_err(|e| {
@@ -130,54 +290,66 @@ impl DebuggerV1 for ImplDebugger {
         Box::new(f)
     }
 
-    fn breakpoint(
+    /// Set a breakpoint or logpoint if Node has parsed the script, otherwise it's left pending
+    /// until the script is parsed
+    fn set_breakpoint(
         &mut self,
         file_location: &FileLocation,
-        _: Arc<Mutex<Config>>,
+        condition: &BreakpointCondition,
+        log_message: Option<&str>,
     ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
-        let full_file_name = Path::new(&file_location.name).canonicalize();
+        // Scripts on a remote machine don't exist locally so we take the path as it is
+        let full_file_name = match (Path::new(&file_location.name).canonicalize(), &self.remote) {
+            (Ok(s), _) => Ok(s.to_string_lossy().to_string()),
+            (Err(_), Some(_)) => Ok(file_location.name.clone()),
+            (Err(e), None) => Err(e),
+        };
         let f = match full_file_name {
-            Ok(s) => {
-                let filename = s.to_string_lossy().to_string();
-                let mut analyser = self.analyser.lock().unwrap();
+            Ok(filename) => {
+                let analyser = self.analyser.lock().unwrap();
                 match analyser.get_script_from_filename(&filename) {
                     Some(script) => {
-                        let msg = OwnedMessage::Text(format!(
-                            "{{\
-                             \"method\":\"Debugger.setBreakpoint\",\
-                             \"params\":{{\
-                             \"location\":{{\
-                             \"scriptId\":\"{}\",\
-                             \"lineNumber\":{}\
-                             }}\
-                             }}\
-                             }}",
-                            script.get_script_id(),
-                            file_location.line_num - 1
-                        ));
+                        let msg = OwnedMessage::Text(
+                            set_breakpoint_msg(
+                                script.get_script_id(),
+                                file_location,
+                                condition,
+                                log_message,
+                            )
+                            .to_string(),
+                        );
 
                         let line_num = file_location.line_num;
+                        let breakpoints = self.breakpoints.clone();
+                        let location = file_location.clone();
 
                         self.ws_handler
                             .lock()
                             .unwrap()
                             .send_and_receive_message(msg)
-                            .map(move |response| {
+                            .map(move |mut response| {
                                 if response["error"].is_null() {
+                                    let breakpoint_id = serde_json::from_value(
+                                        response["result"]["breakpointId"].take(),
+                                    )
+                                    .unwrap();
                                     breakpoint_set(&filename, line_num);
+                                    let mut breakpoints = breakpoints.lock().unwrap();
+                                    breakpoints.set_native_id(&location, breakpoint_id);
+                                    breakpoints.set_status(&location, BreakpointStatus::Resolved);
 
                                     serde_json::json!({"status":"OK"})
                                 } else {
+                                    breakpoints
+                                        .lock()
+                                        .unwrap()
+                                        .set_status(&location, BreakpointStatus::Failed);
+
                                     serde_json::json!({"status":"ERROR"})
                                 }
                             })
                     }
                     None => {
-                        analyser.add_pending_breakpoint(FileLocation::new(
-                            filename,
-                            file_location.line_num,
-                        ));
-
                         return Box::new(future::lazy(move || {
                             let resp = serde_json::json!({"status":"PENDING"});
                             Ok(resp)
@@ -191,6 +363,11 @@ impl DebuggerV1 for ImplDebugger {
                     &format!("Can't find file {}: {}", file_location.name, e),
                 );
 
+                self.breakpoints
+                    .lock()
+                    .unwrap()
+                    .set_status(file_location, BreakpointStatus::Failed);
+
                 return Box::new(future::lazy(move || {
                     let resp = serde_json::json!({"status":"ERROR"});
                     Ok(resp)
@@ -200,36 +377,377 @@ impl DebuggerV1 for ImplDebugger {
 
         Box::new(f)
     }
+}
 
-    fn step_in(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
-        let msg = OwnedMessage::Text("{\"method\":\"Debugger.stepInto\"}".to_string());
+impl DebuggerV1 for ImplDebugger {
+    fn setup(&mut self) {}
+
+    fn teardown(&mut self) {
+        exit(0);
+    }
+
+    fn set_source_map(&mut self, _source_map: &SourceMap) {}
+
+    fn run(
+        &mut self,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.remote.clone() {
+            Some(remote) => return self.connect_remote(&remote, config),
+            None => {}
+        }
+
+        log_msg(LogLevel::INFO, "Launching process");
+
+        let (tx, rx) = mpsc::channel(1);
+
+        self.process.lock().unwrap().run(tx);
+
+        self.connect(rx, config)
+    }
+
+    /// Attach to a running Node program through its inspector and pause it
+    fn attach(
+        &mut self,
+        target: &AttachTarget,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        let pid = match target {
+            AttachTarget::Pid(pid) => Some(*pid),
+            AttachTarget::Name(name) => find_process_by_name(name),
+        };
+
+        let pid = match pid {
+            Some(pid) => pid,
+            None => {
+                log_msg(LogLevel::WARN, "Can't find process to attach to");
+                return Box::new(future::lazy(move || {
+                    let resp = serde_json::json!({"status":"ERROR"});
+                    Ok(resp)
+                }));
+            }
+        };
+
+        log_msg(LogLevel::INFO, &format!("Attaching to process {}", pid));
+
+        let uri = match self.process.lock().unwrap().attach(pid) {
+            Some(uri) => uri,
+            None => {
+                log_msg(
+                    LogLevel::WARN,
+                    &format!("Can't open the inspector of process {}", pid),
+                );
+                return Box::new(future::lazy(move || {
+                    let resp = serde_json::json!({"status":"ERROR"});
+                    Ok(resp)
+                }));
+            }
+        };
+
+        let (mut tx, rx) = mpsc::channel(1);
+        tx.try_send(uri).unwrap();
+
+        let ws_handler = self.ws_handler.clone();
+
+        let f = self.connect(rx, config).and_then(move |resp| {
+            if resp["status"] != "OK" {
+                return future::Either::A(future::ok(resp));
+            }
+
+            let msg = OwnedMessage::Text("{\"method\":\"Debugger.pause\"}".to_string());
+            let f = ws_handler
+                .lock()
+                .unwrap()
+                .send_and_receive_message(msg)
+                .map(move |_| resp);
+
+            future::Either::B(f)
+        });
+
+        Box::new(f)
+    }
+
+    /// Kill Node and run it again, the new process has none of the breakpoints so they're all
+    /// set again as the scripts are parsed.
+    fn restart(
+        &mut self,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_remote("restart") {
+            Some(f) => return f,
+            None => {}
+        }
+
+        log_msg(LogLevel::INFO, "Restarting process");
+
+        self.kill_process();
+
+        self.run(config)
+    }
+
+    fn kill(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_remote("kill") {
+            Some(f) => return f,
+            None => {}
+        }
+
+        if !self.ws_handler.lock().unwrap().is_connected() {
+            log_msg(LogLevel::WARN, "No process running");
+            return Box::new(future::lazy(move || {
+                let resp = serde_json::json!({"status":"ERROR"});
+                Ok(resp)
+            }));
+        }
+
+        self.kill_process();
+
+        let f = future::lazy(move || {
+            let resp = serde_json::json!({"status":"OK"});
+            Ok(resp)
+        });
+
+        Box::new(f)
+    }
+
+    /// Close the connection to the inspector, Node removes the breakpoints and carries on running
+    /// the program when the debugger disconnects
+    fn detach(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        if !self.ws_handler.lock().unwrap().is_connected() {
+            log_msg(LogLevel::WARN, "No process running");
+            return Box::new(future::lazy(move || {
+                let resp = serde_json::json!({"status":"ERROR"});
+                Ok(resp)
+            }));
+        }
+
+        self.disconnect();
+
+        let f = future::lazy(move || {
+            let resp = serde_json::json!({"status":"OK"});
+            Ok(resp)
+        });
+
+        Box::new(f)
+    }
+
+    fn breakpoint(
+        &mut self,
+        file_location: &FileLocation,
+        condition: &BreakpointCondition,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        self.set_breakpoint(file_location, condition, None)
+    }
+
+    fn logpoint(
+        &mut self,
+        file_location: &FileLocation,
+        log_message: &str,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        self.set_breakpoint(
+            file_location,
+            &BreakpointCondition::default(),
+            Some(log_message),
+        )
+    }
+
+    fn breakpoint_function(
+        &mut self,
+        function: &FunctionName,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        log_msg(
+            LogLevel::INFO,
+            &format!("Setting breakpoint on function {}", function.name),
+        );
+
+        if function.is_regex {
+            log_msg(
+                LogLevel::WARN,
+                "Can't set breakpoints on functions by regex in Node",
+            );
+            return Box::new(future::lazy(move || {
+                let resp = serde_json::json!({"status":"ERROR"});
+                Ok(resp)
+            }));
+        }
+
+        let expression = match &function.module {
+            Some(m) => format!("require({}).{}", serde_json::json!(m), function.name),
+            None => function.name.clone(),
+        };
+
+        // Look the function up in the current frame if we're paused as most functions are local
+        // to a module, otherwise globally.
+        let msg = match self.analyser.lock().unwrap().get_call_frame_id() {
+            Some(call_frame_id) => serde_json::json!({
+                "method": "Debugger.evaluateOnCallFrame",
+                "params": {
+                    "callFrameId": call_frame_id,
+                    "expression": expression,
+                },
+            }),
+            None => serde_json::json!({
+                "method": "Runtime.evaluate",
+                "params": {
+                    "expression": expression,
+                    "includeCommandLineAPI": true,
+                },
+            }),
+        };
+
+        let ws_handler = self.ws_handler.clone();
+        let analyser = self.analyser.clone();
+        let breakpoints = self.breakpoints.clone();
+        let function = function.clone();
 
         let f = self
             .ws_handler
             .lock()
             .unwrap()
-            .send_and_receive_message(msg)
-            .map(|response| {
-                if response["error"].is_null() {
-                    serde_json::json!({"status":"OK"})
-                } else {
-                    serde_json::json!({"status":"ERROR"})
+            .send_and_receive_message(OwnedMessage::Text(msg.to_string()))
+            .and_then(move |response| {
+                let object_id = match response["result"]["result"]["type"].as_str() {
+                    Some("function") => response["result"]["result"]["objectId"].clone(),
+                    _ => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::Other,
+                            format!("Can't find function {}", function.name),
+                        ))
+                    }
+                };
+
+                let msg = serde_json::json!({
+                    "method": "Runtime.getProperties",
+                    "params": {"objectId": object_id},
+                });
+                let f1 = ws_handler
+                    .lock()
+                    .unwrap()
+                    .send_and_receive_message(OwnedMessage::Text(msg.to_string()));
+
+                let msg = serde_json::json!({
+                    "method": "Debugger.setBreakpointOnFunctionCall",
+                    "params": {"objectId": object_id},
+                });
+                let f2 = ws_handler
+                    .lock()
+                    .unwrap()
+                    .send_and_receive_message(OwnedMessage::Text(msg.to_string()));
+
+                Ok(f1.join(f2).map(move |responses| (function, responses)))
+            })
+            .flatten()
+            .map(move |(function, (properties, response))| {
+                if !response["error"].is_null() {
+                    return serde_json::json!({"status":"ERROR"});
+                }
+
+                let breakpoint_id = response["result"]["breakpointId"]
+                    .as_str()
+                    .unwrap()
+                    .to_string();
+
+                let location = properties["result"]["internalProperties"]
+                    .as_array()
+                    .and_then(|props| {
+                        props
+                            .iter()
+                            .find(|prop| prop["name"] == "[[FunctionLocation]]")
+                    })
+                    .map(|prop| prop["value"]["value"].clone());
+
+                let file_location = location.and_then(|location| {
+                    let analyser = analyser.lock().unwrap();
+                    let script = analyser.get_script_from_id(location["scriptId"].as_str()?)?;
+                    Some(FileLocation::new(
+                        script.get_file().to_string(),
+                        location["lineNumber"].as_u64()? + 1,
+                    ))
+                });
+
+                match &file_location {
+                    Some(fl) => breakpoint_set(&fl.name, fl.line_num),
+                    None => {}
                 }
+
+                let id = breakpoints.lock().unwrap().add_function(
+                    function,
+                    file_location,
+                    breakpoint_id,
+                    BreakpointStatus::Resolved,
+                );
+                serde_json::json!({"status":"OK","id":id})
+            })
+            .or_else(|e| {
+                log_msg(LogLevel::ERROR, &format!("{}", e));
+                Ok(serde_json::json!({"status":"ERROR"}))
             });
 
         Box::new(f)
     }
 
-    fn step_over(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
-        let msg = OwnedMessage::Text("{\"method\":\"Debugger.stepOver\"}".to_string());
+    fn remove_breakpoint(
+        &mut self,
+        file_location: &FileLocation,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        let breakpoint = self.breakpoints.lock().unwrap().get(file_location).cloned();
+
+        let breakpoint_id = match breakpoint {
+            Some(bkpt) => match bkpt.native_id() {
+                Some(id) => id.to_string(),
+                None => {
+                    // Never made it to Node so nothing to tell it
+                    self.breakpoints.lock().unwrap().remove(file_location);
+                    breakpoint_removed(&file_location.name, file_location.line_num);
+
+                    return Box::new(future::lazy(move || {
+                        let resp = serde_json::json!({"status":"OK"});
+                        Ok(resp)
+                    }));
+                }
+            },
+            None => {
+                log_msg(
+                    LogLevel::WARN,
+                    &format!(
+                        "No breakpoint found in file {} at line number {}",
+                        file_location.name, file_location.line_num
+                    ),
+                );
+
+                return Box::new(future::lazy(move || {
+                    let resp = serde_json::json!({"status":"ERROR"});
+                    Ok(resp)
+                }));
+            }
+        };
+
+        let msg = OwnedMessage::Text(
+            serde_json::json!({
+                "method": "Debugger.removeBreakpoint",
+                "params": {
+                    "breakpointId": breakpoint_id,
+                },
+            })
+            .to_string(),
+        );
+
+        let breakpoints = self.breakpoints.clone();
+        let location = file_location.clone();
 
         let f = self
             .ws_handler
             .lock()
             .unwrap()
             .send_and_receive_message(msg)
-            .map(|response| {
+            .map(move |response| {
                 if response["error"].is_null() {
+                    breakpoints.lock().unwrap().remove(&location);
+                    breakpoint_removed(&location.name, location.line_num);
+
                     serde_json::json!({"status":"OK"})
                 } else {
                     serde_json::json!({"status":"ERROR"})
@@ -239,16 +757,58 @@ impl DebuggerV1 for ImplDebugger {
         Box::new(f)
     }
 
-    fn continue_(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
-        let msg = OwnedMessage::Text("{\"method\":\"Debugger.resume\"}".to_string());
+    fn remove_breakpoint_function(
+        &mut self,
+        function: &FunctionName,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        let breakpoint_id = match self
+            .breakpoints
+            .lock()
+            .unwrap()
+            .get_function(function)
+            .and_then(|bkpt| bkpt.native_id().map(|x| x.to_string()))
+        {
+            Some(id) => id,
+            None => {
+                log_msg(
+                    LogLevel::WARN,
+                    &format!("No breakpoint found on function {}", function.name),
+                );
+
+                return Box::new(future::lazy(move || {
+                    let resp = serde_json::json!({"status":"ERROR"});
+                    Ok(resp)
+                }));
+            }
+        };
+
+        let msg = OwnedMessage::Text(
+            serde_json::json!({
+                "method": "Debugger.removeBreakpoint",
+                "params": {
+                    "breakpointId": breakpoint_id,
+                },
+            })
+            .to_string(),
+        );
+
+        let breakpoints = self.breakpoints.clone();
+        let function = function.clone();
 
         let f = self
             .ws_handler
             .lock()
             .unwrap()
             .send_and_receive_message(msg)
-            .map(|response| {
+            .map(move |response| {
                 if response["error"].is_null() {
+                    let bkpt = breakpoints.lock().unwrap().remove_function(&function);
+                    match bkpt.as_ref().and_then(|bkpt| bkpt.file_location()) {
+                        Some(fl) => breakpoint_removed(&fl.name, fl.line_num),
+                        None => {}
+                    }
+
                     serde_json::json!({"status":"OK"})
                 } else {
                     serde_json::json!({"status":"ERROR"})
@@ -258,40 +818,620 @@ impl DebuggerV1 for ImplDebugger {
         Box::new(f)
     }
 
-    fn print(
+    fn set_exception_breakpoints(
         &mut self,
-        variable: &Variable,
+        mode: ExceptionBreakpointMode,
         _: Arc<Mutex<Config>>,
     ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
-        let msg = OwnedMessage::Text(format!(
-            "{{\
-             \"method\":\"Debugger.evaluateOnCallFrame\",\
-             \"params\":{{\
-             \"callFrameId\":\"{{\\\"ordinal\\\":0,\\\"injectedScriptId\\\":1}}\",\
-             \"expression\":\"{}\",\
-             \"returnByValue\":true\
-             }}\
-             }}",
-            variable.name,
-        ));
+        self.exception_mode = mode;
 
-        let variable = variable.name.clone();
+        // Set when we start running otherwise
+        if !self.ws_handler.lock().unwrap().is_connected() {
+            return Box::new(future::lazy(move || {
+                let resp = serde_json::json!({"status":"OK"});
+                Ok(resp)
+            }));
+        }
+
+        let msg = OwnedMessage::Text(set_pause_on_exceptions_msg(mode).to_string());
 
         let f = self
             .ws_handler
             .lock()
             .unwrap()
             .send_and_receive_message(msg)
-            .map(move |response| {
+            .map(|response| {
                 if response["error"].is_null() {
-                    let mut json = response;
+                    serde_json::json!({"status":"OK"})
+                } else {
+                    serde_json::json!({"status":"ERROR"})
+                }
+            });
+
+        Box::new(f)
+    }
+
+    fn watch(
+        &mut self,
+        _: &WatchTarget,
+        _: WatchMode,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        log_msg(LogLevel::WARN, "Can't set watchpoints in Node");
+
+        let f = future::lazy(move || {
+            let resp = serde_json::json!({"status":"ERROR"});
+            Ok(resp)
+        });
+
+        Box::new(f)
+    }
+
+    fn step_in(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        let msg = OwnedMessage::Text("{\"method\":\"Debugger.stepInto\"}".to_string());
+
+        let f = self
+            .ws_handler
+            .lock()
+            .unwrap()
+            .send_and_receive_message(msg)
+            .map(|response| {
+                if response["error"].is_null() {
+                    serde_json::json!({"status":"OK"})
+                } else {
+                    serde_json::json!({"status":"ERROR"})
+                }
+            });
+
+        Box::new(f)
+    }
+
+    fn step_over(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        let msg = OwnedMessage::Text("{\"method\":\"Debugger.stepOver\"}".to_string());
+
+        let f = self
+            .ws_handler
+            .lock()
+            .unwrap()
+            .send_and_receive_message(msg)
+            .map(|response| {
+                if response["error"].is_null() {
+                    serde_json::json!({"status":"OK"})
+                } else {
+                    serde_json::json!({"status":"ERROR"})
+                }
+            });
+
+        Box::new(f)
+    }
+
+    fn step_out(
+        &mut self,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        let msg = OwnedMessage::Text("{\"method\":\"Debugger.stepOut\"}".to_string());
+
+        let f = self
+            .ws_handler
+            .lock()
+            .unwrap()
+            .send_and_receive_message(msg)
+            .map(|response| {
+                if response["error"].is_null() {
+                    serde_json::json!({"status":"OK"})
+                } else {
+                    serde_json::json!({"status":"ERROR"})
+                }
+            });
+
+        Box::new(f)
+    }
+
+    fn step_instruction(
+        &mut self,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        self.no_machine_code("step by instruction")
+    }
+
+    fn next_instruction(
+        &mut self,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        self.no_machine_code("step by instruction")
+    }
+
+    fn run_to_line(
+        &mut self,
+        file_location: &FileLocation,
+        ignore_breakpoints: bool,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        let full_file_name = match (Path::new(&file_location.name).canonicalize(), &self.remote) {
+            (Ok(s), _) => Some(s.to_string_lossy().to_string()),
+            (Err(_), Some(_)) => Some(file_location.name.clone()),
+            (Err(_), None) => None,
+        };
+
+        let script_id = match full_file_name {
+            Some(filename) => {
+                match self
+                    .analyser
+                    .lock()
+                    .unwrap()
+                    .get_script_from_filename(&filename)
+                {
+                    Some(script) => Some(script.get_script_id().to_string()),
+                    None => None,
+                }
+            }
+            None => None,
+        };
+
+        let script_id = match script_id {
+            Some(s) => s,
+            None => {
+                log_msg(
+                    LogLevel::WARN,
+                    &format!("Can't find script for file {}", file_location.name),
+                );
+                return Box::new(future::lazy(move || {
+                    let resp = serde_json::json!({"status":"ERROR"});
+                    Ok(resp)
+                }));
+            }
+        };
+
+        // Deactivating breakpoints would deactivate the location we're continuing to as well so
+        // we continue on from any breakpoints instead
+        if ignore_breakpoints {
+            self.analyser
+                .lock()
+                .unwrap()
+                .ignore_breakpoints_until(script_id.clone(), file_location.line_num - 1);
+        }
+
+        let msg = continue_to_location_msg(&script_id, file_location.line_num - 1);
+
+        let f = self
+            .ws_handler
+            .lock()
+            .unwrap()
+            .send_and_receive_message(OwnedMessage::Text(msg.to_string()))
+            .map(|response| {
+                if response["error"].is_null() {
+                    serde_json::json!({"status":"OK"})
+                } else {
+                    serde_json::json!({"status":"ERROR"})
+                }
+            });
+
+        Box::new(f)
+    }
+
+    fn continue_(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        let msg = OwnedMessage::Text("{\"method\":\"Debugger.resume\"}".to_string());
+
+        let f = self
+            .ws_handler
+            .lock()
+            .unwrap()
+            .send_and_receive_message(msg)
+            .map(|response| {
+                if response["error"].is_null() {
+                    serde_json::json!({"status":"OK"})
+                } else {
+                    serde_json::json!({"status":"ERROR"})
+                }
+            });
+
+        Box::new(f)
+    }
+
+    fn pause(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        let msg = OwnedMessage::Text("{\"method\":\"Debugger.pause\"}".to_string());
+
+        let f = self
+            .ws_handler
+            .lock()
+            .unwrap()
+            .send_and_receive_message(msg)
+            .map(|response| {
+                if response["error"].is_null() {
+                    serde_json::json!({"status":"OK"})
+                } else {
+                    serde_json::json!({"status":"ERROR"})
+                }
+            });
+
+        Box::new(f)
+    }
+
+    fn backtrace(
+        &mut self,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        let frames: Vec<serde_json::Value> = self
+            .analyser
+            .lock()
+            .unwrap()
+            .get_backtrace()
+            .iter()
+            .map(|frame| frame.to_json())
+            .collect();
+
+        let f = future::lazy(move || {
+            let resp = match frames.is_empty() {
+                true => {
+                    log_msg(LogLevel::WARN, "Not paused, no backtrace");
+                    serde_json::json!({"status":"ERROR"})
+                }
+                false => serde_json::json!({"status":"OK","frames":frames}),
+            };
+            Ok(resp)
+        });
+
+        Box::new(f)
+    }
+
+    fn disassemble(
+        &mut self,
+        _: Option<&str>,
+        _: Option<u64>,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        self.no_machine_code("disassemble")
+    }
+
+    fn registers(
+        &mut self,
+        _: Option<RegisterGroup>,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        self.no_machine_code("read registers")
+    }
+
+    fn set_register(
+        &mut self,
+        _: &str,
+        _: &str,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        self.no_machine_code("set registers")
+    }
+
+    fn select_frame(
+        &mut self,
+        frame: FrameSelection,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        let index = self.analyser.lock().unwrap().select_frame(frame);
+
+        let f = future::lazy(move || {
+            let resp = match index {
+                Some(index) => serde_json::json!({"status":"OK","frame":index}),
+                None => {
+                    log_msg(LogLevel::WARN, "No such frame");
+                    serde_json::json!({"status":"ERROR"})
+                }
+            };
+            Ok(resp)
+        });
+
+        Box::new(f)
+    }
+
+    fn threads(
+        &mut self,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        let threads: Vec<serde_json::Value> = self
+            .analyser
+            .lock()
+            .unwrap()
+            .get_threads()
+            .iter()
+            .map(|thread| thread.to_json())
+            .collect();
+
+        let f = future::lazy(move || Ok(serde_json::json!({"status":"OK","threads":threads})));
+
+        Box::new(f)
+    }
+
+    /// Only the main thread can be debugged, worker threads are only listed
+    fn select_thread(
+        &mut self,
+        id: u64,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        let found = self
+            .analyser
+            .lock()
+            .unwrap()
+            .get_threads()
+            .iter()
+            .any(|thread| thread.id == id);
+
+        let f = future::lazy(move || {
+            let resp = match (id, found) {
+                (0, _) => serde_json::json!({"status":"OK","thread":id}),
+                (_, true) => {
+                    log_msg(LogLevel::WARN, "Debugging worker threads isn't supported");
+                    serde_json::json!({"status":"ERROR"})
+                }
+                (_, false) => {
+                    log_msg(LogLevel::WARN, &format!("No thread {}", id));
+                    serde_json::json!({"status":"ERROR"})
+                }
+            };
+            Ok(resp)
+        });
+
+        Box::new(f)
+    }
+
+    /// Get the properties of each scope object of the selected frame, innermost scope first
+    fn locals(
+        &mut self,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        let object_ids = match self.analyser.lock().unwrap().get_scope_object_ids() {
+            Some(object_ids) => object_ids,
+            None => {
+                log_msg(LogLevel::WARN, "Not paused, no locals");
+                return Box::new(future::lazy(move || {
+                    Ok(serde_json::json!({"status":"ERROR"}))
+                }));
+            }
+        };
+
+        let f = self
+            .get_properties(object_ids)
+            .map(|locals| serde_json::json!({"status":"OK","locals":locals}));
+
+        Box::new(f)
+    }
+
+    /// Get the properties of the object
+    fn expand_variable(
+        &mut self,
+        variables_reference: u64,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        let object_id = match self
+            .variable_references
+            .lock()
+            .unwrap()
+            .get(variables_reference)
+        {
+            Some(object_id) => object_id,
+            None => {
+                log_msg(LogLevel::WARN, "No such variables reference");
+                return Box::new(future::lazy(move || {
+                    Ok(serde_json::json!({"status":"ERROR"}))
+                }));
+            }
+        };
+
+        let f = self
+            .get_properties(vec![object_id])
+            .map(|variables| serde_json::json!({"status":"OK","variables":variables}));
+
+        Box::new(f)
+    }
+
+    /// Evaluate the expression on the selected call frame or the one given
+    ///
+    /// Expressions that throw, including syntax errors, are reported with Node's exception
+    /// message.
+    fn evaluate(
+        &mut self,
+        expression: &str,
+        frame: Option<u64>,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        let call_frame_id = {
+            let analyser = self.analyser.lock().unwrap();
+            match frame {
+                Some(index) => analyser.get_call_frame_id_at(index),
+                None => analyser.get_call_frame_id(),
+            }
+        };
+
+        let call_frame_id = match call_frame_id {
+            Some(call_frame_id) => call_frame_id,
+            None => {
+                let error = match frame {
+                    Some(index) => format!("No frame {}", index),
+                    None => "Not paused".to_string(),
+                };
+                log_msg(
+                    LogLevel::WARN,
+                    &format!("{}, can't evaluate expression", error),
+                );
+                return Box::new(future::lazy(move || {
+                    Ok(serde_json::json!({"status":"ERROR","error":error}))
+                }));
+            }
+        };
+
+        let expression = expression.to_string();
+        let variable_references = self.variable_references.clone();
+
+        let f = evaluate_on_call_frame(&self.ws_handler, &call_frame_id, &expression).map(
+            move |result| match result {
+                Ok(remote_object) => {
+                    let mut resp =
+                        remote_object_json(remote_object, &mut variable_references.lock().unwrap());
+                    resp["status"] = serde_json::json!("OK");
+                    resp["expression"] = serde_json::json!(expression);
+                    resp
+                }
+                Err(error) => serde_json::json!({"status":"ERROR","error":error}),
+            },
+        );
+
+        Box::new(f)
+    }
+
+    /// Set a variable in the selected call frame
+    ///
+    /// Variables in local, closure and catch scopes are set with `Debugger.setVariableValue`,
+    /// anything else such as a global or a property is assigned to by evaluating an assignment.
+    fn set_variable(
+        &mut self,
+        variable: &Variable,
+        value: &str,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        let (call_frame_id, scopes) = {
+            let analyser = self.analyser.lock().unwrap();
+            (analyser.get_call_frame_id(), analyser.get_settable_scopes())
+        };
+
+        let call_frame_id = match call_frame_id {
+            Some(call_frame_id) => call_frame_id,
+            None => {
+                log_msg(LogLevel::WARN, "Not paused, can't set variable");
+                return Box::new(future::lazy(move || {
+                    Ok(serde_json::json!({"status":"ERROR","error":"Not paused"}))
+                }));
+            }
+        };
+
+        let scopes = match is_identifier(&variable.name) {
+            true => scopes.unwrap_or_default(),
+            false => vec![],
+        };
+
+        let fs: Vec<_> = scopes
+            .iter()
+            .map(|(_, object_id)| {
+                let msg = OwnedMessage::Text(
+                    serde_json::json!({
+                        "method": "Runtime.getProperties",
+                        "params": {
+                            "objectId": object_id,
+                            "ownProperties": true,
+                        },
+                    })
+                    .to_string(),
+                );
+                self.ws_handler
+                    .lock()
+                    .unwrap()
+                    .send_and_receive_message(msg)
+            })
+            .collect();
+
+        let ws_handler = self.ws_handler.clone();
+        let variable = variable.name.clone();
+        let value = value.to_string();
+        let variable_references = self.variable_references.clone();
+
+        let f = future::join_all(fs).and_then(move |responses| {
+            // The innermost scope with the variable is the one it refers to
+            let scope_number = responses
+                .iter()
+                .zip(scopes.iter())
+                .find(
+                    |(response, _)| match response["result"]["result"].as_array() {
+                        Some(properties) => properties
+                            .iter()
+                            .any(|property| property["name"] == variable.as_str()),
+                        None => false,
+                    },
+                )
+                .map(|(_, (scope_number, _))| *scope_number);
+
+            let f = match scope_number {
+                Some(scope_number) => set_variable_value(
+                    ws_handler,
+                    call_frame_id,
+                    scope_number,
+                    variable.clone(),
+                    value,
+                ),
+                None => evaluate_on_call_frame(
+                    &ws_handler,
+                    &call_frame_id,
+                    &format!("{} = ({})", variable, value),
+                ),
+            };
+
+            f.map(move |result| match result {
+                Ok(remote_object) => {
+                    let mut resp =
+                        remote_object_json(remote_object, &mut variable_references.lock().unwrap());
+                    resp["status"] = serde_json::json!("OK");
+                    resp["variable"] = serde_json::json!(variable);
+                    resp
+                }
+                Err(error) => serde_json::json!({"status":"ERROR","error":error}),
+            })
+        });
+
+        Box::new(f)
+    }
+
+    fn print(
+        &mut self,
+        variable: &Variable,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        let call_frame_id = match self.analyser.lock().unwrap().get_call_frame_id() {
+            Some(call_frame_id) => call_frame_id,
+            None => {
+                log_msg(LogLevel::WARN, "Not paused, can't print variable");
+                return Box::new(future::lazy(move || {
+                    Ok(serde_json::json!({"status":"ERROR"}))
+                }));
+            }
+        };
+
+        let msg = OwnedMessage::Text(
+            serde_json::json!({
+                "method": "Debugger.evaluateOnCallFrame",
+                "params": {
+                    "callFrameId": call_frame_id,
+                    "expression": variable.name,
+                },
+            })
+            .to_string(),
+        );
+
+        let variable = variable.name.clone();
+        let variable_references = self.variable_references.clone();
+
+        let f = self
+            .ws_handler
+            .lock()
+            .unwrap()
+            .send_and_receive_message(msg)
+            .map(move |response| {
+                if response["error"].is_null() {
+                    let mut json = response;
                     let variable_type = json["result"]["result"]["type"].take();
-                    let value = json["result"]["result"]["value"].take();
+
+                    // Primitives have a value and objects a description along with an ID for
+                    // getting their properties
+                    let value = match json["result"]["result"].get("value") {
+                        Some(value) => value.clone(),
+                        None => json["result"]["result"]["description"].take(),
+                    };
+                    let variables_reference = match json["result"]["result"]["objectId"].as_str() {
+                        Some(object_id) => variable_references
+                            .lock()
+                            .unwrap()
+                            .add(object_id.to_string()),
+                        None => 0,
+                    };
+
                     serde_json::json!({
                         "status": "OK",
                         "type": variable_type,
                         "variable": variable,
                         "value": value,
+                        "variablesReference": variables_reference,
                     })
                 } else {
                     serde_json::json!({"status":"ERROR"})
@@ -301,3 +1441,193 @@ impl DebuggerV1 for ImplDebugger {
         Box::new(f)
     }
 }
+
+/// Evaluate an expression on a call frame, gives the remote object it evaluates to or the error
+/// when the expression throws, including syntax errors
+fn evaluate_on_call_frame(
+    ws_handler: &Arc<Mutex<WSHandler>>,
+    call_frame_id: &str,
+    expression: &str,
+) -> Box<dyn Future<Item = Result<serde_json::Value, String>, Error = io::Error> + Send> {
+    let msg = OwnedMessage::Text(
+        serde_json::json!({
+            "method": "Debugger.evaluateOnCallFrame",
+            "params": {
+                "callFrameId": call_frame_id,
+                "expression": expression,
+            },
+        })
+        .to_string(),
+    );
+
+    let f = ws_handler
+        .lock()
+        .unwrap()
+        .send_and_receive_message(msg)
+        .map(|mut response| {
+            let error = match response["error"]["message"].as_str() {
+                Some(message) => Some(message.to_string()),
+                None => {
+                    let exception_details = &response["result"]["exceptionDetails"];
+                    match exception_details["exception"]["description"].as_str() {
+                        Some(description) => Some(exception_message(description).to_string()),
+                        None => exception_details["text"].as_str().map(|s| s.to_string()),
+                    }
+                }
+            };
+
+            match error {
+                Some(error) => {
+                    log_msg(LogLevel::WARN, &error);
+                    Err(error)
+                }
+                None => Ok(response["result"]["result"].take()),
+            }
+        });
+
+    Box::new(f)
+}
+
+/// Set a variable in a scope of the call frame to the value of an expression, gives the
+/// variable's new value or the error setting it
+fn set_variable_value(
+    ws_handler: Arc<Mutex<WSHandler>>,
+    call_frame_id: String,
+    scope_number: u64,
+    variable: String,
+    value: String,
+) -> Box<dyn Future<Item = Result<serde_json::Value, String>, Error = io::Error> + Send> {
+    // The value's in brackets so an object literal isn't taken as a block
+    let value = format!("({})", value);
+
+    let f = evaluate_on_call_frame(&ws_handler, &call_frame_id, &value).and_then(move |result| {
+        let remote_object = match result {
+            Ok(remote_object) => remote_object,
+            Err(error) => {
+                return Box::new(future::ok(Err(error)))
+                    as Box<dyn Future<Item = _, Error = io::Error> + Send>
+            }
+        };
+
+        let msg = OwnedMessage::Text(
+            serde_json::json!({
+                "method": "Debugger.setVariableValue",
+                "params": {
+                    "scopeNumber": scope_number,
+                    "variableName": variable,
+                    "newValue": call_argument(&remote_object),
+                    "callFrameId": call_frame_id,
+                },
+            })
+            .to_string(),
+        );
+
+        let f = ws_handler.lock().unwrap().send_and_receive_message(msg);
+
+        let f = f.and_then(
+            move |response| match response["error"]["message"].as_str() {
+                Some(error) => {
+                    log_msg(LogLevel::WARN, error);
+                    Box::new(future::ok(Err(error.to_string())))
+                        as Box<dyn Future<Item = _, Error = io::Error> + Send>
+                }
+                None => evaluate_on_call_frame(&ws_handler, &call_frame_id, &variable),
+            },
+        );
+
+        Box::new(f)
+    });
+
+    Box::new(f)
+}
+
+/// The argument for passing a remote object back to Node, `undefined` is given as an empty
+/// argument
+fn call_argument(remote_object: &serde_json::Value) -> serde_json::Value {
+    match (
+        remote_object.get("unserializableValue"),
+        remote_object.get("objectId"),
+        remote_object.get("value"),
+    ) {
+        (Some(value), _, _) => serde_json::json!({ "unserializableValue": value }),
+        (None, Some(object_id), _) => serde_json::json!({ "objectId": object_id }),
+        (None, None, Some(value)) => serde_json::json!({ "value": value }),
+        (None, None, None) => serde_json::json!({}),
+    }
+}
+
+/// Whether the variable is a plain JavaScript identifier rather than a path to a property
+fn is_identifier(name: &str) -> bool {
+    match name.chars().next() {
+        Some(c) if c.is_alphabetic() || c == '_' || c == '$' => {}
+        _ => return false,
+    }
+    name.chars()
+        .all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// The type and value of a remote object, primitives have a value and objects a description
+/// along with an ID for getting their properties
+fn remote_object_json(
+    remote_object: serde_json::Value,
+    variable_references: &mut VariableReferences,
+) -> serde_json::Value {
+    let value = match remote_object.get("value") {
+        Some(value) => value.clone(),
+        None => remote_object["description"].clone(),
+    };
+    let variables_reference = match remote_object["objectId"].as_str() {
+        Some(object_id) => variable_references.add(object_id.to_string()),
+        None => 0,
+    };
+
+    serde_json::json!({
+        "type": remote_object["type"],
+        "value": value,
+        "variablesReference": variables_reference,
+    })
+}
+
+/// Describe a property from `Runtime.getProperties`, objects are described by Node and primitives
+/// are given as their JSON value
+fn variable_details(
+    property: &serde_json::Value,
+    variable_references: &mut VariableReferences,
+) -> VariableDetails {
+    let remote_object = &property["value"];
+    let type_ = remote_object["type"].as_str().unwrap_or("").to_string();
+
+    let value = match remote_object["description"].as_str() {
+        Some(description) => description.to_string(),
+        None => match remote_object.get("value") {
+            Some(value) => value.to_string(),
+            None => type_.clone(),
+        },
+    };
+
+    let variables_reference = match remote_object["objectId"].as_str() {
+        Some(object_id) => variable_references.add(object_id.to_string()),
+        None => 0,
+    };
+
+    VariableDetails::new(
+        property["name"].as_str().unwrap_or("").to_string(),
+        type_,
+        value,
+        variables_reference,
+    )
+}
+
+/// Build the `Debugger.setPauseOnExceptions` message for the exception breakpoint mode
+fn set_pause_on_exceptions_msg(mode: ExceptionBreakpointMode) -> serde_json::Value {
+    let state = match mode {
+        ExceptionBreakpointMode::None => "none",
+        ExceptionBreakpointMode::Uncaught => "uncaught",
+        ExceptionBreakpointMode::All => "all",
+    };
+
+    serde_json::json!({
+        "method": "Debugger.setPauseOnExceptions",
+        "params": {"state": state},
+    })
+}
@@ -2,9 +2,12 @@
 //!
 //! This module performs the basic setup and spawning of the Node process.
 
-use std::io::BufReader;
+use std::io::{BufReader, Read, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::Duration;
 
-use crate::util::{check_and_spawn_process, read_output, setup_stdin};
+use crate::util::{check_and_spawn_process, read_output, setup_stdin, signal_process};
 
 use regex::Regex;
 use tokio::prelude::*;
@@ -14,30 +17,32 @@ use tokio_process::{Child, ChildStderr, ChildStdout};
 /// Main handler for spawning the Node process
 #[derive(Debug)]
 pub struct Process {
-    debugger_cmd: Option<String>,
-    run_cmd: Option<Vec<String>>,
+    debugger_cmd: String,
+    run_cmd: Vec<String>,
     process: Option<Child>,
+    // The PID of the program when we've attached to it rather than running it
+    attached_pid: Option<u64>,
 }
 
 impl Process {
     /// Create a new Process
     pub fn new(debugger_cmd: String, run_cmd: Vec<String>) -> Self {
         Process {
-            debugger_cmd: Some(debugger_cmd),
-            run_cmd: Some(run_cmd),
+            debugger_cmd,
+            run_cmd,
             process: None,
+            attached_pid: None,
         }
     }
 
     /// Run Node program, including handling forwarding stdin onto the Node interpreter but
     /// not used to analyse the program as some of the other debuggers are.
     pub fn run(&mut self, tx: Sender<String>) {
+        self.attached_pid = None;
+
         let mut process = check_and_spawn_process(
-            vec![
-                self.debugger_cmd.take().unwrap(),
-                "--inspect-brk=0".to_string(),
-            ],
-            self.run_cmd.take().unwrap(),
+            vec![self.debugger_cmd.clone(), "--inspect-brk=0".to_string()],
+            self.run_cmd.clone(),
         );
 
         setup_stdin(
@@ -66,8 +71,58 @@ impl Process {
         self.process = Some(process);
     }
 
-    pub fn get_pid(&self) -> u64 {
-        self.process.as_ref().unwrap().id() as u64
+    /// Attach to a running Node program, returns the URL of the inspector's websocket if we
+    /// can find it
+    ///
+    /// Node opens the inspector on the default port when it receives SIGUSR1, we then ask the
+    /// inspector where its websocket is.
+    pub fn attach(&mut self, pid: u64) -> Option<String> {
+        if !signal_process(pid, "USR1") {
+            return None;
+        }
+
+        for _ in 0..10 {
+            match inspector_websocket_url("127.0.0.1:9229") {
+                Some(url) => {
+                    self.attached_pid = Some(pid);
+                    return Some(url);
+                }
+                None => thread::sleep(Duration::from_millis(500)),
+            }
+        }
+
+        None
+    }
+
+    /// Kill the Node process so that it can be run again, returns the PID killed if there was
+    /// one
+    pub fn kill(&mut self) -> Option<u64> {
+        match self.attached_pid.take() {
+            Some(pid) => {
+                signal_process(pid, "KILL");
+                return Some(pid);
+            }
+            None => {}
+        }
+
+        match self.process.take() {
+            Some(mut process) => {
+                let pid = process.id() as u64;
+                match process.kill() {
+                    Ok(_) => {}
+                    Err(e) => eprintln!("Error killing Node: {}", e),
+                };
+                Some(pid)
+            }
+            None => None,
+        }
+    }
+
+    pub fn get_pid(&self) -> Option<u64> {
+        match self.attached_pid {
+            Some(pid) => Some(pid),
+            None => self.process.as_ref().map(|process| process.id() as u64),
+        }
     }
 
     /// Perform setup of reading Node stdout and writing it back to PADRE stdout.
@@ -114,3 +169,47 @@ impl Process {
         );
     }
 }
+
+/// Ask the inspector listening at the address, e.g. `127.0.0.1:9229`, for the URL of its websocket
+pub fn inspector_websocket_url(addr: &str) -> Option<String> {
+    let mut stream = match TcpStream::connect(addr) {
+        Ok(stream) => stream,
+        Err(_) => return None,
+    };
+
+    stream
+        .set_read_timeout(Some(Duration::new(2, 0)))
+        .expect("Can't set timeout on inspector connection");
+
+    let request = format!("GET /json/list HTTP/1.0\r\nHost: {}\r\n\r\n", addr);
+    match stream.write_all(request.as_bytes()) {
+        Ok(_) => {}
+        Err(_) => return None,
+    };
+
+    // The inspector keeps the connection open so we read until we have the whole body
+    let mut response = vec![];
+    let mut buf = [0; 4096];
+    loop {
+        match stream.read(&mut buf) {
+            Ok(0) | Err(_) => return None,
+            Ok(n) => response.extend_from_slice(&buf[..n]),
+        };
+
+        let text = String::from_utf8_lossy(&response);
+        let body = match text.find("\r\n\r\n") {
+            Some(i) => &text[i + 4..],
+            None => continue,
+        };
+
+        match serde_json::from_str::<serde_json::Value>(body) {
+            Ok(json) => {
+                return match json[0]["webSocketDebuggerUrl"].as_str() {
+                    Some(url) => Some(url.to_string()),
+                    None => None,
+                };
+            }
+            Err(_) => {}
+        };
+    }
+}
@@ -86,6 +86,10 @@ impl WSHandler {
         tokio::spawn(fut);
     }
 
+    pub fn is_connected(&self) -> bool {
+        self.ws_tx.is_some()
+    }
+
     pub fn close(&self) {
         let tx = self.ws_tx.clone();
 
@@ -99,6 +103,14 @@ impl WSHandler {
         );
     }
 
+    /// Close the connection and forget it so that we can connect again
+    pub fn disconnect(&mut self) {
+        if self.is_connected() {
+            self.close();
+            self.ws_tx = None;
+        }
+    }
+
     pub fn send_and_receive_message(
         &mut self,
         msg: OwnedMessage,
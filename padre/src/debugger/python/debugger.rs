@@ -4,14 +4,21 @@
 //! communicating through the `Process`.
 
 use std::io;
+use std::path::Path;
 use std::process::exit;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use super::process::{Event, Listener, PDBStatus, Process};
 use crate::config::Config;
-use crate::debugger::{DebuggerV1, FileLocation, Variable};
-use crate::notifier::{log_msg, LogLevel};
+use crate::debugger::{
+    parse_log_message, AttachTarget, BreakpointCondition, BreakpointRegistry, BreakpointStatus,
+    DebuggerV1, ExceptionBreakpointMode, FileLocation, FrameSelection, FunctionName,
+    LogMessagePart, RegisterGroup, SourceMap, ThreadDetails, Variable, VariableDetails,
+    VariableReferences, WatchMode, WatchTarget,
+};
+use crate::notifier::{breakpoint_removed, log_msg, signal_exited, LogLevel};
+use crate::util::{find_process_by_name, interrupt_process};
 
 use bytes::Bytes;
 use tokio::prelude::*;
@@ -20,46 +27,154 @@ use tokio::sync::mpsc;
 #[derive(Debug)]
 pub struct ImplDebugger {
     process: Arc<Mutex<Process>>,
-    pending_breakpoints: Option<Vec<FileLocation>>,
+    breakpoints: Arc<Mutex<BreakpointRegistry>>,
+    variable_references: Arc<Mutex<VariableReferences>>,
 }
 
 impl ImplDebugger {
-    pub fn new(debugger_cmd: String, run_cmd: Vec<String>) -> ImplDebugger {
+    pub fn new(
+        debugger_cmd: String,
+        run_cmd: Vec<String>,
+        breakpoints: Arc<Mutex<BreakpointRegistry>>,
+    ) -> ImplDebugger {
         ImplDebugger {
-            process: Arc::new(Mutex::new(Process::new(debugger_cmd, run_cmd))),
-            pending_breakpoints: Some(vec![]),
+            process: Arc::new(Mutex::new(Process::new(
+                debugger_cmd,
+                run_cmd,
+                breakpoints.clone(),
+            ))),
+            breakpoints,
+            variable_references: Arc::new(Mutex::new(VariableReferences::new())),
         }
     }
 
-    fn check_process_running(
-        &self,
-    ) -> Option<Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>> {
-        match self.process.lock().unwrap().get_status() {
-            PDBStatus::None => {
-                let f = future::lazy(move || {
-                    let resp = serde_json::json!({"status":"ERROR"});
-                    Ok(resp)
-                });
-                return Some(Box::new(f));
-            }
-            _ => None,
-        }
+    /// Send the statement to print variables and wait for them, the variables that can be
+    /// expanded are given references to the expressions for them.
+    fn variables(
+        &mut self,
+        stmt: String,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = Result<Vec<VariableDetails>, String>, Error = io::Error> + Send>
+    {
+        let (tx, rx) = mpsc::channel(1);
+
+        self.process
+            .lock()
+            .unwrap()
+            .set_status(PDBStatus::Variables);
+
+        self.process
+            .lock()
+            .unwrap()
+            .add_listener(Listener::Variables, tx);
+
+        let variable_references = self.variable_references.clone();
+
+        let f = rx
+            .take(1)
+            .into_future()
+            .timeout(Duration::new(
+                config
+                    .lock()
+                    .unwrap()
+                    .get_config("PrintVariableTimeout")
+                    .unwrap() as u64,
+                0,
+            ))
+            .map(move |event| match event.0.unwrap() {
+                Event::Variables(variables) => Ok(variables
+                    .into_iter()
+                    .map(|(mut variable, expression)| {
+                        match expression {
+                            Some(e) => {
+                                variable.variables_reference =
+                                    variable_references.lock().unwrap().add(e)
+                            }
+                            None => {}
+                        }
+                        variable
+                    })
+                    .collect()),
+                Event::VariablesFailed(msg) => {
+                    log_msg(LogLevel::WARN, &msg);
+                    Err(msg)
+                }
+                _ => unreachable!(),
+            })
+            .map_err(|e| {
+                eprintln!("Reading stdin error {:?}", e);
+                io::Error::new(io::ErrorKind::Other, "Timed out printing variables")
+            });
+
+        self.process.lock().unwrap().write_stdin(Bytes::from(stmt));
+
+        Box::new(f)
     }
-}
 
-impl DebuggerV1 for ImplDebugger {
-    fn setup(&mut self) {}
+    /// Send the statement to print the threads and wait for them
+    fn get_threads(
+        &mut self,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = Result<Vec<ThreadDetails>, String>, Error = io::Error> + Send> {
+        let (tx, rx) = mpsc::channel(1);
 
-    fn teardown(&mut self) {
-        exit(0);
+        self.process.lock().unwrap().set_status(PDBStatus::Threads);
+
+        self.process
+            .lock()
+            .unwrap()
+            .add_listener(Listener::Threads, tx);
+
+        let f = rx
+            .take(1)
+            .into_future()
+            .timeout(Duration::new(
+                config
+                    .lock()
+                    .unwrap()
+                    .get_config("PrintVariableTimeout")
+                    .unwrap() as u64,
+                0,
+            ))
+            .map(move |event| match event.0.unwrap() {
+                Event::Threads(threads) => Ok(threads),
+                Event::ThreadsFailed(msg) => {
+                    log_msg(LogLevel::WARN, &msg);
+                    Err(msg)
+                }
+                _ => unreachable!(),
+            })
+            .map_err(|e| {
+                eprintln!("Reading stdin error {:?}", e);
+                io::Error::new(io::ErrorKind::Other, "Timed out getting threads")
+            });
+
+        self.process
+            .lock()
+            .unwrap()
+            .write_stdin(Bytes::from(threads_stmt()));
+
+        Box::new(f)
     }
 
-    fn run(
+    /// Kill pdb along with the program, pdb forgets the breakpoints so they're all marked as
+    /// pending ready for running again.
+    fn kill_process(&mut self) {
+        match self.process.lock().unwrap().kill() {
+            // Report it as killed by SIGKILL like LLDB does
+            Some(pid) => signal_exited(pid, 9),
+            None => {}
+        }
+
+        self.breakpoints.lock().unwrap().reset();
+    }
+
+    /// Wait for pdb to start, either from running the program or attaching to it, and then set
+    /// any pending breakpoints
+    fn wait_for_launch(
         &mut self,
         config: Arc<Mutex<Config>>,
     ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
-        log_msg(LogLevel::INFO, "Launching process");
-
         let (tx, rx) = mpsc::channel(1);
 
         self.process
@@ -67,7 +182,7 @@ impl DebuggerV1 for ImplDebugger {
             .unwrap()
             .add_listener(Listener::Launch, tx);
 
-        let pending_breakpoints = self.pending_breakpoints.take().unwrap();
+        let pending_breakpoints = self.breakpoints.lock().unwrap().pending();
 
         let process = self.process.clone();
         let process2 = self.process.clone();
@@ -79,7 +194,15 @@ impl DebuggerV1 for ImplDebugger {
                 match event.0.unwrap() {
                     Event::Launched => {
                         for bkpt in &pending_breakpoints {
-                            let stmt = format!("break {}:{}\n", bkpt.name, bkpt.line_num);
+                            let file_location = match bkpt.file_location() {
+                                Some(fl) => fl,
+                                None => continue,
+                            };
+                            let stmt = match (bkpt.function(), bkpt.log_message()) {
+                                (Some(function), _) => break_function_stmt(function),
+                                (None, Some(msg)) => logpoint_stmt(file_location, msg),
+                                (None, None) => break_stmt(file_location, bkpt.condition()),
+                            };
                             process
                                 .clone()
                                 .lock()
@@ -108,31 +231,47 @@ impl DebuggerV1 for ImplDebugger {
                 io::Error::new(io::ErrorKind::Other, "Timed out setting breakpoint")
             });
 
-        self.process.lock().unwrap().run();
-
         Box::new(f)
     }
 
-    fn breakpoint(
+    fn check_process_running(
+        &self,
+    ) -> Option<Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send>> {
+        match self.process.lock().unwrap().get_status() {
+            PDBStatus::None => {
+                let f = future::lazy(move || {
+                    let resp = serde_json::json!({"status":"ERROR"});
+                    Ok(resp)
+                });
+                return Some(Box::new(f));
+            }
+            _ => None,
+        }
+    }
+
+    /// There are no machine instructions or registers to debug
+    fn no_machine_code(
+        &self,
+        action: &str,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        log_msg(LogLevel::WARN, &format!("Can't {} in Python", action));
+
+        Box::new(future::lazy(move || {
+            let resp = serde_json::json!({"status":"ERROR"});
+            Ok(resp)
+        }))
+    }
+
+    /// Send the pdb statement(s) to set a breakpoint and wait to hear it's been set
+    fn set_breakpoint(
         &mut self,
         file_location: &FileLocation,
+        stmt: String,
         config: Arc<Mutex<Config>>,
     ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
-        log_msg(
-            LogLevel::INFO,
-            &format!(
-                "Setting breakpoint in file {} at line number {}",
-                file_location.name, file_location.line_num
-            ),
-        );
-
-        // If not started yet add as a pending breakpoint that will get set during run period.
+        // If not started yet leave as a pending breakpoint that will get set during run period.
         match self.process.lock().unwrap().get_status() {
             PDBStatus::None => {
-                match self.pending_breakpoints {
-                    Some(ref mut x) => x.push(file_location.clone()),
-                    None => {}
-                };
                 let f = future::lazy(move || {
                     let resp = serde_json::json!({"status":"PENDING"});
                     Ok(resp)
@@ -149,6 +288,9 @@ impl DebuggerV1 for ImplDebugger {
             .unwrap()
             .add_listener(Listener::Breakpoint, tx);
 
+        let breakpoints = self.breakpoints.clone();
+        let location = file_location.clone();
+
         let f = rx
             .take(1)
             .into_future()
@@ -161,50 +303,112 @@ impl DebuggerV1 for ImplDebugger {
                 0,
             ))
             .map(move |event| match event.0.unwrap() {
-                Event::BreakpointSet(_) => serde_json::json!({"status":"OK"}),
+                Event::BreakpointSet(_, _) => serde_json::json!({"status":"OK"}),
                 _ => unreachable!(),
             })
-            .map_err(|e| {
+            .map_err(move |e| {
                 eprintln!("Reading stdin error {:?}", e);
+                breakpoints
+                    .lock()
+                    .unwrap()
+                    .set_status(&location, BreakpointStatus::Failed);
                 io::Error::new(io::ErrorKind::Other, "Timed out setting breakpoint")
             });
 
-        let stmt = format!("break {}:{}\n", file_location.name, file_location.line_num);
-
         self.process.lock().unwrap().write_stdin(Bytes::from(stmt));
 
         Box::new(f)
     }
+}
 
-    fn step_in(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
-        match self.check_process_running() {
-            Some(f) => return f,
-            None => {}
+impl DebuggerV1 for ImplDebugger {
+    fn setup(&mut self) {}
+
+    fn teardown(&mut self) {
+        exit(0);
+    }
+
+    fn set_source_map(&mut self, _source_map: &SourceMap) {}
+
+    fn run(
+        &mut self,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        log_msg(LogLevel::INFO, "Launching process");
+
+        let f = self.wait_for_launch(config);
+
+        self.process.lock().unwrap().run();
+
+        f
+    }
+
+    /// Attach to a program running the PADRE attach helper, it stops the program when we connect
+    fn attach(
+        &mut self,
+        target: &AttachTarget,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        let pid = match target {
+            AttachTarget::Pid(pid) => Some(*pid),
+            AttachTarget::Name(name) => find_process_by_name(name),
         };
 
-        self.process
-            .lock()
-            .unwrap()
-            .write_stdin(Bytes::from("step\n"));
+        let pid = match pid {
+            Some(pid) => pid,
+            None => {
+                log_msg(LogLevel::WARN, "Can't find process to attach to");
+                let f = future::lazy(move || {
+                    let resp = serde_json::json!({"status":"ERROR"});
+                    Ok(resp)
+                });
+                return Box::new(f);
+            }
+        };
 
-        let f = future::lazy(move || {
-            let resp = serde_json::json!({"status":"OK"});
-            Ok(resp)
-        });
+        let socket_path = format!("/tmp/padre-python-{}.sock", pid);
 
-        Box::new(f)
+        if !Path::new(&socket_path).exists() {
+            log_msg(
+                LogLevel::WARN,
+                &format!("Process {} isn't running the PADRE attach helper", pid),
+            );
+            let f = future::lazy(move || {
+                let resp = serde_json::json!({"status":"ERROR"});
+                Ok(resp)
+            });
+            return Box::new(f);
+        }
+
+        log_msg(LogLevel::INFO, &format!("Attaching to process {}", pid));
+
+        let f = self.wait_for_launch(config);
+
+        self.process.lock().unwrap().attach(pid, &socket_path);
+
+        f
     }
 
-    fn step_over(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+    /// Kill pdb and the program and run it again, pdb forgets the breakpoints so they're all set
+    /// again as if they were pending.
+    fn restart(
+        &mut self,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        log_msg(LogLevel::INFO, "Restarting process");
+
+        self.kill_process();
+
+        self.run(config)
+    }
+
+    fn kill(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
         match self.check_process_running() {
             Some(f) => return f,
             None => {}
         };
 
-        self.process
-            .lock()
-            .unwrap()
-            .write_stdin(Bytes::from("next\n"));
+        self.kill_process();
 
         let f = future::lazy(move || {
             let resp = serde_json::json!({"status":"OK"});
@@ -214,46 +418,92 @@ impl DebuggerV1 for ImplDebugger {
         Box::new(f)
     }
 
-    fn continue_(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
-        match self.check_process_running() {
-            Some(f) => return f,
-            None => {}
-        };
-
-        self.process
-            .lock()
-            .unwrap()
-            .write_stdin(Bytes::from("continue\n"));
+    /// pdb runs the program itself so there's nothing to detach from
+    fn detach(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        log_msg(LogLevel::WARN, "pdb can't detach from the program");
 
         let f = future::lazy(move || {
-            let resp = serde_json::json!({"status":"OK"});
+            let resp = serde_json::json!({"status":"ERROR"});
             Ok(resp)
         });
 
         Box::new(f)
     }
 
-    fn print(
+    fn breakpoint(
         &mut self,
-        variable: &Variable,
+        file_location: &FileLocation,
+        condition: &BreakpointCondition,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        log_msg(
+            LogLevel::INFO,
+            &format!(
+                "Setting breakpoint in file {} at line number {}",
+                file_location.name, file_location.line_num
+            ),
+        );
+
+        let stmt = break_stmt(file_location, condition);
+
+        self.set_breakpoint(file_location, stmt, config)
+    }
+
+    fn logpoint(
+        &mut self,
+        file_location: &FileLocation,
+        log_message: &str,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        log_msg(
+            LogLevel::INFO,
+            &format!(
+                "Setting logpoint in file {} at line number {}",
+                file_location.name, file_location.line_num
+            ),
+        );
+
+        let stmt = logpoint_stmt(file_location, log_message);
+
+        self.set_breakpoint(file_location, stmt, config)
+    }
+
+    fn breakpoint_function(
+        &mut self,
+        function: &FunctionName,
         config: Arc<Mutex<Config>>,
     ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        log_msg(
+            LogLevel::INFO,
+            &format!("Setting breakpoint on function {}", function.name),
+        );
+
+        // pdb looks the function up in the current frame so we need to be running
         match self.check_process_running() {
             Some(f) => return f,
             None => {}
-        };
+        }
+
+        if function.is_regex {
+            log_msg(
+                LogLevel::WARN,
+                "Can't set breakpoints on functions by regex in Python",
+            );
+            return Box::new(future::lazy(move || {
+                let resp = serde_json::json!({"status":"ERROR"});
+                Ok(resp)
+            }));
+        }
 
         let (tx, rx) = mpsc::channel(1);
 
         self.process
             .lock()
             .unwrap()
-            .set_status(PDBStatus::Printing(variable.clone()));
+            .add_listener(Listener::Breakpoint, tx);
 
-        self.process
-            .lock()
-            .unwrap()
-            .add_listener(Listener::PrintVariable, tx);
+        let breakpoints = self.breakpoints.clone();
+        let function2 = function.clone();
 
         let f = rx
             .take(1)
@@ -262,27 +512,891 @@ impl DebuggerV1 for ImplDebugger {
                 config
                     .lock()
                     .unwrap()
-                    .get_config("PrintVariableTimeout")
+                    .get_config("BreakpointTimeout")
                     .unwrap() as u64,
                 0,
             ))
             .map(move |event| match event.0.unwrap() {
-                Event::PrintVariable(variable, value) => serde_json::json!({
-                    "status": "OK",
-                    "variable": variable.name,
-                    "value": value,
-                }),
+                Event::BreakpointSet(id, file_location) => {
+                    let id = breakpoints.lock().unwrap().add_function(
+                        function2,
+                        Some(file_location),
+                        id,
+                        BreakpointStatus::Resolved,
+                    );
+                    serde_json::json!({"status":"OK","id":id})
+                }
                 _ => unreachable!(),
             })
-            .map_err(|e| {
+            .map_err(move |e| {
                 eprintln!("Reading stdin error {:?}", e);
-                io::Error::new(io::ErrorKind::Other, "Timed out printing variable")
+                io::Error::new(io::ErrorKind::Other, "Timed out setting breakpoint")
             });
 
-        let stmt = format!("print({})\n", variable.name);
+        let stmt = break_function_stmt(function);
 
         self.process.lock().unwrap().write_stdin(Bytes::from(stmt));
 
         Box::new(f)
     }
+
+    fn remove_breakpoint(
+        &mut self,
+        file_location: &FileLocation,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        log_msg(
+            LogLevel::INFO,
+            &format!(
+                "Removing breakpoint in file {} at line number {}",
+                file_location.name, file_location.line_num
+            ),
+        );
+
+        // If not started yet just drop it from the pending breakpoints.
+        match self.process.lock().unwrap().get_status() {
+            PDBStatus::None => {
+                let found = self
+                    .breakpoints
+                    .lock()
+                    .unwrap()
+                    .remove(file_location)
+                    .is_some();
+                if found {
+                    breakpoint_removed(&file_location.name, file_location.line_num);
+                }
+                let f = future::lazy(move || {
+                    let resp = match found {
+                        true => serde_json::json!({"status":"OK"}),
+                        false => serde_json::json!({"status":"ERROR"}),
+                    };
+                    Ok(resp)
+                });
+                return Box::new(f);
+            }
+            _ => {}
+        }
+
+        let (tx, rx) = mpsc::channel(1);
+
+        self.process
+            .lock()
+            .unwrap()
+            .add_listener(Listener::BreakpointRemoved, tx);
+
+        let location = file_location.clone();
+
+        let f = rx
+            .take(1)
+            .into_future()
+            .timeout(Duration::new(
+                config
+                    .lock()
+                    .unwrap()
+                    .get_config("BreakpointTimeout")
+                    .unwrap() as u64,
+                0,
+            ))
+            .map(move |event| match event.0.unwrap() {
+                Event::BreakpointRemoved(_) => serde_json::json!({"status":"OK"}),
+                Event::BreakpointNotFound => {
+                    log_msg(
+                        LogLevel::WARN,
+                        &format!(
+                            "No breakpoint found in file {} at line number {}",
+                            location.name, location.line_num
+                        ),
+                    );
+                    serde_json::json!({"status":"ERROR"})
+                }
+                _ => unreachable!(),
+            })
+            .map_err(|e| {
+                eprintln!("Reading stdin error {:?}", e);
+                io::Error::new(io::ErrorKind::Other, "Timed out removing breakpoint")
+            });
+
+        let stmt = format!("clear {}:{}\n", file_location.name, file_location.line_num);
+
+        self.process.lock().unwrap().write_stdin(Bytes::from(stmt));
+
+        Box::new(f)
+    }
+
+    /// Clear the breakpoint by its number, pdb reports where it was as for other breakpoints
+    fn remove_breakpoint_function(
+        &mut self,
+        function: &FunctionName,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        log_msg(
+            LogLevel::INFO,
+            &format!("Removing breakpoint on function {}", function.name),
+        );
+
+        let id = match self
+            .breakpoints
+            .lock()
+            .unwrap()
+            .get_function(function)
+            .and_then(|bkpt| bkpt.native_id().map(|x| x.to_string()))
+        {
+            Some(id) => id,
+            None => {
+                log_msg(
+                    LogLevel::WARN,
+                    &format!("No breakpoint found on function {}", function.name),
+                );
+                return Box::new(future::lazy(move || {
+                    let resp = serde_json::json!({"status":"ERROR"});
+                    Ok(resp)
+                }));
+            }
+        };
+
+        let (tx, rx) = mpsc::channel(1);
+
+        self.process
+            .lock()
+            .unwrap()
+            .add_listener(Listener::BreakpointRemoved, tx);
+
+        let breakpoints = self.breakpoints.clone();
+        let function = function.clone();
+
+        let f = rx
+            .take(1)
+            .into_future()
+            .timeout(Duration::new(
+                config
+                    .lock()
+                    .unwrap()
+                    .get_config("BreakpointTimeout")
+                    .unwrap() as u64,
+                0,
+            ))
+            .map(move |event| match event.0.unwrap() {
+                Event::BreakpointRemoved(_) => {
+                    breakpoints.lock().unwrap().remove_function(&function);
+                    serde_json::json!({"status":"OK"})
+                }
+                Event::BreakpointNotFound => {
+                    log_msg(
+                        LogLevel::WARN,
+                        &format!("No breakpoint found on function {}", function.name),
+                    );
+                    serde_json::json!({"status":"ERROR"})
+                }
+                _ => unreachable!(),
+            })
+            .map_err(|e| {
+                eprintln!("Reading stdin error {:?}", e);
+                io::Error::new(io::ErrorKind::Other, "Timed out removing breakpoint")
+            });
+
+        let stmt = format!("clear {}\n", id);
+
+        self.process.lock().unwrap().write_stdin(Bytes::from(stmt));
+
+        Box::new(f)
+    }
+
+    /// pdb always stops post-mortem on uncaught exceptions, we continue straight away if we
+    /// don't want to stop on them.
+    fn set_exception_breakpoints(
+        &mut self,
+        mode: ExceptionBreakpointMode,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match mode {
+            ExceptionBreakpointMode::All => {
+                log_msg(
+                    LogLevel::WARN,
+                    "Can't break on exceptions that are caught in Python",
+                );
+                return Box::new(future::lazy(move || {
+                    let resp = serde_json::json!({"status":"ERROR"});
+                    Ok(resp)
+                }));
+            }
+            _ => {}
+        }
+
+        self.process.lock().unwrap().set_exception_mode(mode);
+
+        let f = future::lazy(move || {
+            let resp = serde_json::json!({"status":"OK"});
+            Ok(resp)
+        });
+
+        Box::new(f)
+    }
+
+    fn watch(
+        &mut self,
+        _: &WatchTarget,
+        _: WatchMode,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        log_msg(LogLevel::WARN, "Can't set watchpoints in Python");
+
+        let f = future::lazy(move || {
+            let resp = serde_json::json!({"status":"ERROR"});
+            Ok(resp)
+        });
+
+        Box::new(f)
+    }
+
+    fn step_in(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_process_running() {
+            Some(f) => return f,
+            None => {}
+        };
+
+        self.process
+            .lock()
+            .unwrap()
+            .write_stdin(Bytes::from("step\n"));
+
+        let f = future::lazy(move || {
+            let resp = serde_json::json!({"status":"OK"});
+            Ok(resp)
+        });
+
+        Box::new(f)
+    }
+
+    fn step_over(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_process_running() {
+            Some(f) => return f,
+            None => {}
+        };
+
+        self.process
+            .lock()
+            .unwrap()
+            .write_stdin(Bytes::from("next\n"));
+
+        let f = future::lazy(move || {
+            let resp = serde_json::json!({"status":"OK"});
+            Ok(resp)
+        });
+
+        Box::new(f)
+    }
+
+    fn step_out(
+        &mut self,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_process_running() {
+            Some(f) => return f,
+            None => {}
+        };
+
+        self.process
+            .lock()
+            .unwrap()
+            .write_stdin(Bytes::from("return\n"));
+
+        let f = future::lazy(move || {
+            let resp = serde_json::json!({"status":"OK"});
+            Ok(resp)
+        });
+
+        Box::new(f)
+    }
+
+    fn step_instruction(
+        &mut self,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        self.no_machine_code("step by instruction")
+    }
+
+    fn next_instruction(
+        &mut self,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        self.no_machine_code("step by instruction")
+    }
+
+    /// Continue to a temporary breakpoint, if we're ignoring breakpoints they're disabled until
+    /// we next stop
+    fn run_to_line(
+        &mut self,
+        file_location: &FileLocation,
+        ignore_breakpoints: bool,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_process_running() {
+            Some(f) => return f,
+            None => {}
+        };
+
+        let breakpoints = self.breakpoints.lock().unwrap();
+
+        // Don't set a temporary breakpoint on top of an existing one as it gets removed when the
+        // temporary one is deleted
+        let existing_id = breakpoints
+            .find_location(&file_location.name, file_location.line_num)
+            .and_then(|fl| breakpoints.get(&fl))
+            .and_then(|bkpt| bkpt.native_id().map(|id| id.to_string()));
+
+        let mut stmt = "".to_string();
+
+        if ignore_breakpoints {
+            let ids: Vec<String> = breakpoints
+                .native_ids()
+                .into_iter()
+                .filter(|id| Some(id) != existing_id.as_ref())
+                .collect();
+            if !ids.is_empty() {
+                stmt += &format!("disable {}\n", ids.join(" "));
+                self.process
+                    .lock()
+                    .unwrap()
+                    .write_stdin_on_stop(Bytes::from(format!("enable {}\n", ids.join(" "))));
+            }
+        }
+
+        match existing_id {
+            Some(_) => {}
+            None => stmt += &format!("tbreak {}:{}\n", file_location.name, file_location.line_num),
+        }
+
+        stmt += "continue\n";
+
+        self.process.lock().unwrap().write_stdin(Bytes::from(stmt));
+
+        let f = future::lazy(move || {
+            let resp = serde_json::json!({"status":"OK"});
+            Ok(resp)
+        });
+
+        Box::new(f)
+    }
+
+    fn continue_(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_process_running() {
+            Some(f) => return f,
+            None => {}
+        };
+
+        self.process
+            .lock()
+            .unwrap()
+            .write_stdin(Bytes::from("continue\n"));
+
+        let f = future::lazy(move || {
+            let resp = serde_json::json!({"status":"OK"});
+            Ok(resp)
+        });
+
+        Box::new(f)
+    }
+
+    /// Interrupt Python with SIGINT, pdb then stops where it was
+    fn pause(&mut self) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_process_running() {
+            Some(f) => return f,
+            None => {}
+        };
+
+        let interrupted = interrupt_process(self.process.lock().unwrap().get_pid());
+
+        let f = future::lazy(move || {
+            let resp = match interrupted {
+                true => serde_json::json!({"status":"OK"}),
+                false => serde_json::json!({"status":"ERROR"}),
+            };
+            Ok(resp)
+        });
+
+        Box::new(f)
+    }
+
+    fn backtrace(
+        &mut self,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_process_running() {
+            Some(f) => return f,
+            None => {}
+        };
+
+        let (tx, rx) = mpsc::channel(1);
+
+        self.process
+            .lock()
+            .unwrap()
+            .set_status(PDBStatus::Backtrace);
+
+        self.process
+            .lock()
+            .unwrap()
+            .add_listener(Listener::Backtrace, tx);
+
+        let f = rx
+            .take(1)
+            .into_future()
+            .timeout(Duration::new(
+                config
+                    .lock()
+                    .unwrap()
+                    .get_config("PrintVariableTimeout")
+                    .unwrap() as u64,
+                0,
+            ))
+            .map(move |event| match event.0.unwrap() {
+                Event::Backtrace(frames) => {
+                    let frames: Vec<serde_json::Value> =
+                        frames.iter().map(|frame| frame.to_json()).collect();
+                    serde_json::json!({"status":"OK","frames":frames})
+                }
+                _ => unreachable!(),
+            })
+            .map_err(|e| {
+                eprintln!("Reading stdin error {:?}", e);
+                io::Error::new(io::ErrorKind::Other, "Timed out getting backtrace")
+            });
+
+        self.process
+            .lock()
+            .unwrap()
+            .write_stdin(Bytes::from("where\n"));
+
+        Box::new(f)
+    }
+
+    fn disassemble(
+        &mut self,
+        _: Option<&str>,
+        _: Option<u64>,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        self.no_machine_code("disassemble")
+    }
+
+    fn registers(
+        &mut self,
+        _: Option<RegisterGroup>,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        self.no_machine_code("read registers")
+    }
+
+    fn set_register(
+        &mut self,
+        _: &str,
+        _: &str,
+        _: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        self.no_machine_code("set registers")
+    }
+
+    /// Select a frame, we find out which frame is current with `where` first to know how far to
+    /// move `up` or `down`
+    fn select_frame(
+        &mut self,
+        frame: FrameSelection,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_process_running() {
+            Some(f) => return f,
+            None => {}
+        };
+
+        let (tx, rx) = mpsc::channel(1);
+
+        self.process
+            .lock()
+            .unwrap()
+            .set_status(PDBStatus::SelectingFrame(frame));
+
+        self.process
+            .lock()
+            .unwrap()
+            .add_listener(Listener::SelectFrame, tx);
+
+        let f = rx
+            .take(1)
+            .into_future()
+            .timeout(Duration::new(
+                config
+                    .lock()
+                    .unwrap()
+                    .get_config("PrintVariableTimeout")
+                    .unwrap() as u64,
+                0,
+            ))
+            .map(move |event| match event.0.unwrap() {
+                Event::FrameSelected(index) => serde_json::json!({"status":"OK","frame":index}),
+                Event::FrameNotFound => {
+                    log_msg(LogLevel::WARN, "No such frame");
+                    serde_json::json!({"status":"ERROR"})
+                }
+                _ => unreachable!(),
+            })
+            .map_err(|e| {
+                eprintln!("Reading stdin error {:?}", e);
+                io::Error::new(io::ErrorKind::Other, "Timed out selecting frame")
+            });
+
+        self.process
+            .lock()
+            .unwrap()
+            .write_stdin(Bytes::from("where\n"));
+
+        Box::new(f)
+    }
+
+    fn threads(
+        &mut self,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_process_running() {
+            Some(f) => return f,
+            None => {}
+        };
+
+        let f = self.get_threads(config).map(|threads| match threads {
+            Ok(threads) => {
+                let threads: Vec<serde_json::Value> =
+                    threads.iter().map(|thread| thread.to_json()).collect();
+                serde_json::json!({"status":"OK","threads":threads})
+            }
+            Err(_) => serde_json::json!({"status":"ERROR"}),
+        });
+
+        Box::new(f)
+    }
+
+    /// pdb only debugs the thread it's stopped in so that's the only thread that can be selected
+    fn select_thread(
+        &mut self,
+        id: u64,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_process_running() {
+            Some(f) => return f,
+            None => {}
+        };
+
+        let f = self.get_threads(config).map(move |threads| {
+            let threads = match threads {
+                Ok(threads) => threads,
+                Err(_) => return serde_json::json!({"status":"ERROR"}),
+            };
+
+            match threads.iter().find(|thread| thread.id == id) {
+                Some(thread) => match thread.current {
+                    true => serde_json::json!({"status":"OK","thread":id}),
+                    false => {
+                        log_msg(
+                            LogLevel::WARN,
+                            "pdb can only debug the thread it's stopped in",
+                        );
+                        serde_json::json!({"status":"ERROR"})
+                    }
+                },
+                None => {
+                    log_msg(LogLevel::WARN, &format!("No thread {}", id));
+                    serde_json::json!({"status":"ERROR"})
+                }
+            }
+        });
+
+        Box::new(f)
+    }
+
+    fn locals(
+        &mut self,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_process_running() {
+            Some(f) => return f,
+            None => {}
+        };
+
+        // Names starting with `__` are Python's and pdb's own
+        let stmt =
+            variables_stmt("[(k, k, v) for k, v in locals().items() if not k.startswith(\"__\")]");
+
+        let f = self
+            .variables(stmt, config)
+            .map(|variables| match variables {
+                Ok(variables) => {
+                    let locals: Vec<serde_json::Value> =
+                        variables.iter().map(|local| local.to_json()).collect();
+                    serde_json::json!({"status":"OK","locals":locals})
+                }
+                Err(_) => serde_json::json!({"status":"ERROR"}),
+            });
+
+        Box::new(f)
+    }
+
+    /// Get the items of a dictionary, list or tuple or the attributes of any other object
+    fn expand_variable(
+        &mut self,
+        variables_reference: u64,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_process_running() {
+            Some(f) => return f,
+            None => {}
+        };
+
+        let expression = match self
+            .variable_references
+            .lock()
+            .unwrap()
+            .get(variables_reference)
+        {
+            Some(expression) => expression,
+            None => {
+                log_msg(LogLevel::WARN, "No such variables reference");
+                return Box::new(future::lazy(move || {
+                    Ok(serde_json::json!({"status":"ERROR"}))
+                }));
+            }
+        };
+
+        let stmt = variables_stmt(&format!(
+            "(lambda v, e: \
+             [(repr(k), \"%s[%r]\" % (e, k), c) for k, c in v.items()] \
+             if isinstance(v, dict) else \
+             [(\"[%d]\" % i, \"%s[%d]\" % (e, i), c) for i, c in enumerate(v)] \
+             if isinstance(v, (list, tuple)) else \
+             [(k, \"%s.%s\" % (e, k), c) for k, c in vars(v).items()])(({}), {})",
+            expression,
+            serde_json::to_string(&expression).unwrap()
+        ));
+
+        let f = self
+            .variables(stmt, config)
+            .map(|variables| match variables {
+                Ok(variables) => {
+                    let variables: Vec<serde_json::Value> = variables
+                        .iter()
+                        .map(|variable| variable.to_json())
+                        .collect();
+                    serde_json::json!({"status":"OK","variables":variables})
+                }
+                Err(_) => serde_json::json!({"status":"ERROR"}),
+            });
+
+        Box::new(f)
+    }
+
+    /// Evaluate the expression in the current frame or, when a frame is given, in the globals and
+    /// locals of that frame so the frame pdb has selected doesn't change
+    fn evaluate(
+        &mut self,
+        expression: &str,
+        frame: Option<u64>,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_process_running() {
+            Some(f) => return f,
+            None => {}
+        };
+
+        let name = serde_json::to_string(expression).unwrap();
+        let code = match frame {
+            Some(index) => format!(
+                "(lambda f: eval({}, f.f_globals, f.f_locals))({})",
+                name,
+                frame_expr(index)
+            ),
+            None => expression.to_string(),
+        };
+        let stmt = variables_stmt(&format!(
+            "[({}, {}, ({}))]",
+            name,
+            serde_json::to_string(&code).unwrap(),
+            code
+        ));
+
+        let expression = expression.to_string();
+
+        let f = self
+            .variables(stmt, config)
+            .map(move |variables| match variables {
+                Ok(variables) => match variables.first() {
+                    Some(variable) => serde_json::json!({
+                        "status": "OK",
+                        "expression": expression,
+                        "type": variable.type_,
+                        "value": variable.value,
+                        "variablesReference": variable.variables_reference,
+                    }),
+                    None => serde_json::json!({"status":"ERROR"}),
+                },
+                Err(error) => serde_json::json!({"status":"ERROR","error":error}),
+            });
+
+        Box::new(f)
+    }
+
+    /// Assign the value in the current frame and print the variable back, if the assignment
+    /// fails pdb reports the error before printing the variable.
+    fn set_variable(
+        &mut self,
+        variable: &Variable,
+        value: &str,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_process_running() {
+            Some(f) => return f,
+            None => {}
+        };
+
+        let name = serde_json::to_string(&variable.name).unwrap();
+        let stmt = format!(
+            "!{} = ({})\n{}",
+            variable.name,
+            value,
+            variables_stmt(&format!("[({}, {}, ({}))]", name, name, variable.name))
+        );
+
+        let f = self
+            .variables(stmt, config)
+            .map(|variables| match variables {
+                Ok(variables) => match variables.first() {
+                    Some(variable) => serde_json::json!({
+                        "status": "OK",
+                        "variable": variable.name,
+                        "type": variable.type_,
+                        "value": variable.value,
+                        "variablesReference": variable.variables_reference,
+                    }),
+                    None => serde_json::json!({"status":"ERROR"}),
+                },
+                Err(error) => serde_json::json!({"status":"ERROR","error":error}),
+            });
+
+        Box::new(f)
+    }
+
+    fn print(
+        &mut self,
+        variable: &Variable,
+        config: Arc<Mutex<Config>>,
+    ) -> Box<dyn Future<Item = serde_json::Value, Error = io::Error> + Send> {
+        match self.check_process_running() {
+            Some(f) => return f,
+            None => {}
+        };
+
+        let name = serde_json::to_string(&variable.name).unwrap();
+        let stmt = variables_stmt(&format!("[({}, {}, ({}))]", name, name, variable.name));
+
+        let f = self.variables(stmt, config).map(|variables| {
+            match variables
+                .as_ref()
+                .ok()
+                .and_then(|variables| variables.first())
+            {
+                Some(variable) => serde_json::json!({
+                    "status": "OK",
+                    "variable": variable.name,
+                    "type": variable.type_,
+                    "value": variable.value,
+                    "variablesReference": variable.variables_reference,
+                }),
+                None => serde_json::json!({"status":"ERROR"}),
+            }
+        });
+
+        Box::new(f)
+    }
+}
+
+/// The pdb statement to print the variables as JSON on one line so we can find them in the output
+///
+/// The variables are given by a Python expression for a list of (name, expression, value) tuples,
+/// a variable can be expanded when it's a container or an object with attributes.
+fn variables_stmt(variables: &str) -> String {
+    format!(
+        "!print(\"PADRE variables \" + __import__(\"json\").dumps([\
+         dict(name=n, expr=x, type=type(v).__name__, value=repr(v), \
+         expandable=isinstance(v, (dict, list, tuple)) \
+         or (hasattr(v, \"__dict__\") and not callable(v))) \
+         for n, x, v in {}]))\n",
+        variables
+    )
+}
+
+/// The pdb statement to print the threads as JSON on one line
+///
+/// pdb runs the statement in the thread it's stopped in so we find where that thread is from the
+/// program's frames rather than pdb's.
+fn threads_stmt() -> String {
+    format!(
+        "!print(\"PADRE threads \" + __import__(\"json\").dumps([\
+         dict(id=t.ident, name=t.name, current=c, \
+         file=f.f_code.co_filename if f else None, line=f.f_lineno if f else None) \
+         for t, c in [(t, t.ident == __import__(\"threading\").get_ident()) \
+         for t in __import__(\"threading\").enumerate()] \
+         for f in [{} if c else __import__(\"sys\")._current_frames().get(t.ident)]]))\n",
+        frame_expr(0)
+    )
+}
+
+/// A Python expression for the frame of the program at the index given, 0 being the innermost
+///
+/// pdb runs statements in frames of its own on top of the program's so we walk out past those.
+fn frame_expr(index: u64) -> String {
+    format!(
+        "[f for f, _ in __import__(\"traceback\").walk_stack(None) \
+         if f.f_code.co_filename not in (\"<stdin>\", __import__(\"pdb\").__file__, \
+         __import__(\"bdb\").__file__, __import__(\"cmd\").__file__)][{}]",
+        index
+    )
+}
+
+/// The pdb statement to set a breakpoint, the hit count is set separately with `ignore` once
+/// pdb has told us the breakpoint number.
+fn break_stmt(file_location: &FileLocation, condition: &BreakpointCondition) -> String {
+    match &condition.condition {
+        Some(c) => format!(
+            "break {}:{}, {}\n",
+            file_location.name, file_location.line_num, c
+        ),
+        None => format!("break {}:{}\n", file_location.name, file_location.line_num),
+    }
+}
+
+/// The pdb statement to set a breakpoint on a function, pdb finds the function in the source
+/// when it's yet to be defined so this works as soon as the program starts too.
+fn break_function_stmt(function: &FunctionName) -> String {
+    match &function.module {
+        Some(m) => format!("break {}.{}\n", m, function.name),
+        None => format!("break {}\n", function.name),
+    }
+}
+
+/// The pdb statements to set a logpoint, the print is added as a command to the breakpoint just
+/// set so that it silently prints and continues. The `continue` also ends the command list.
+fn logpoint_stmt(file_location: &FileLocation, log_message: &str) -> String {
+    let mut message = vec![serde_json::json!(format!(
+        "PADRE logpoint {}:{}: ",
+        file_location.name, file_location.line_num
+    ))
+    .to_string()];
+
+    for part in parse_log_message(log_message) {
+        match part {
+            LogMessagePart::Text(t) => message.push(serde_json::json!(t).to_string()),
+            LogMessagePart::Expression(e) => message.push(format!("str(({}))", e)),
+        }
+    }
+
+    format!(
+        "break {}:{}\ncommands\nsilent\nprint({})\ncontinue\n",
+        file_location.name,
+        file_location.line_num,
+        message.join(" + ")
+    )
 }
@@ -0,0 +1,145 @@
+"""PADRE attach helper for Python
+
+Allows PADRE to attach to a running Python program with `--attach <pid>`. Either run
+the program through this helper:
+
+    python3 padre_attach.py my_prog.py arg1 arg2
+
+or import it from the program with `import padre_attach`.
+
+The helper listens on the Unix socket `/tmp/padre-python-<pid>.sock`, when PADRE
+connects the main thread is interrupted with SIGUSR2 and stops in pdb with its
+input and output going over the socket. When PADRE disconnects all breakpoints
+are cleared and the program continues.
+"""
+
+import atexit
+import os
+import pdb
+import runpy
+import signal
+import socket
+import sys
+import threading
+
+SOCKET_PATH = "/tmp/padre-python-{}.sock"
+
+_connection = None
+_lock = threading.Lock()
+
+
+class _SocketIO:
+    """File-like wrapper so pdb and the program's stdout talk over the socket"""
+
+    def __init__(self, conn):
+        self._file = conn.makefile("rw", encoding="utf-8", newline="\n")
+        self._stdout = sys.stdout
+        self._debugger = None
+
+    def write(self, s):
+        try:
+            self._file.write(s)
+            self._file.flush()
+        except OSError:
+            pass
+        return len(s)
+
+    def flush(self):
+        pass
+
+    def readline(self):
+        try:
+            line = self._file.readline()
+        except OSError:
+            line = ""
+
+        if line == "":
+            # PADRE has gone away, let the program carry on without us
+            self._disconnect()
+            return "continue\n"
+
+        return line
+
+    def _disconnect(self):
+        global _connection
+
+        if self._debugger is not None:
+            self._debugger.clear_all_breaks()
+        sys.stdout = self._stdout
+
+        with _lock:
+            _connection = None
+
+
+def _debug(signum, frame):
+    with _lock:
+        conn = _connection
+    if conn is None:
+        return
+
+    io = _SocketIO(conn)
+    debugger = pdb.Pdb(stdin=io, stdout=io)
+    io._debugger = debugger
+    sys.stdout = io
+    debugger.set_trace(frame)
+
+
+def _listen(path, main_ident):
+    global _connection
+
+    server = socket.socket(socket.AF_UNIX, socket.SOCK_STREAM)
+    server.bind(path)
+    server.listen(1)
+
+    while True:
+        conn, _ = server.accept()
+        with _lock:
+            if _connection is not None:
+                conn.close()
+                continue
+            _connection = conn
+        signal.pthread_kill(main_ident, signal.SIGUSR2)
+
+
+def _exited(path):
+    with _lock:
+        conn = _connection
+    if conn is not None:
+        try:
+            conn.sendall(b"The program finished and will be restarted\n")
+        except OSError:
+            pass
+
+    try:
+        os.unlink(path)
+    except OSError:
+        pass
+
+
+def install():
+    path = SOCKET_PATH.format(os.getpid())
+    try:
+        os.unlink(path)
+    except OSError:
+        pass
+
+    signal.signal(signal.SIGUSR2, _debug)
+    atexit.register(_exited, path)
+
+    thread = threading.Thread(
+        target=_listen, args=(path, threading.main_thread().ident), daemon=True
+    )
+    thread.start()
+
+
+if __name__ == "__main__":
+    if len(sys.argv) < 2:
+        print("Usage: padre_attach.py program.py [args...]", file=sys.stderr)
+        sys.exit(2)
+
+    sys.argv = sys.argv[1:]
+    sys.path[0] = os.path.dirname(os.path.abspath(sys.argv[0]))
+    install()
+    runpy.run_path(sys.argv[0], run_name="__main__")
+else:
+    install()
@@ -6,11 +6,18 @@
 
 use std::collections::HashMap;
 use std::io::BufReader;
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 
-use crate::debugger::{FileLocation, Variable};
-use crate::notifier::{breakpoint_set, jump_to_position, signal_exited};
-use crate::util::{check_and_spawn_process, read_output, setup_stdin};
+use crate::debugger::{
+    BreakpointRegistry, BreakpointStatus, ExceptionBreakpointMode, FileLocation, FrameSelection,
+    StackFrame, ThreadDetails, VariableDetails,
+};
+use crate::notifier::{
+    breakpoint_removed, breakpoint_set, exception_thrown, jump_to_position, logpoint_hit,
+    signal_exited,
+};
+use crate::util::{check_and_spawn_process, read_output, setup_stdin, signal_process};
 
 use bytes::Bytes;
 use regex::Regex;
@@ -22,17 +29,29 @@ use tokio_process::{Child, ChildStderr, ChildStdout};
 pub enum PDBStatus {
     None,
     Running,
-    Printing(Variable),
+    Variables,
+    Threads,
+    Backtrace,
+    SelectingFrame(FrameSelection),
 }
 
 /// You can register to listen for one of the following events:
 /// - Breakpoint: A breakpoint event has happened
-/// - PrintVariable: A variable printing event
+/// - BreakpointRemoved: A breakpoint has been cleared
+/// - Variables: Variables have been printed, either for printing a variable, the locals or the
+///   children of a variable
+/// - Backtrace: A backtrace has been printed
+/// - SelectFrame: A frame has been selected
+/// - Threads: The threads have been printed
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
 pub enum Listener {
     Launch,
     Breakpoint,
-    PrintVariable,
+    BreakpointRemoved,
+    Variables,
+    Backtrace,
+    SelectFrame,
+    Threads,
 }
 
 /// A Python event is something that can be registered for being listened to and can be triggered
@@ -40,29 +59,49 @@ pub enum Listener {
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
 pub enum Event {
     Launched,
-    BreakpointSet(FileLocation),
-    PrintVariable(Variable, String),
+    // (Breakpoint number, Location)
+    BreakpointSet(String, FileLocation),
+    BreakpointRemoved(FileLocation),
+    BreakpointNotFound,
+    // (Variable, expression to get its children with if it has any)
+    Variables(Vec<(VariableDetails, Option<String>)>),
+    // (Error message)
+    VariablesFailed(String),
+    Backtrace(Vec<StackFrame>),
+    // (Frame index)
+    FrameSelected(u64),
+    FrameNotFound,
+    Threads(Vec<ThreadDetails>),
+    // (Error message)
+    ThreadsFailed(String),
 }
 
 /// Main handler for spawning the Python process
 #[derive(Debug)]
 pub struct Process {
-    debugger_cmd: Option<String>,
-    run_cmd: Option<Vec<String>>,
+    debugger_cmd: String,
+    run_cmd: Vec<String>,
     process: Option<Child>,
+    // The PID of the program when we've attached to it rather than running it
+    attached_pid: Option<u64>,
     stdin_tx: Option<Sender<Bytes>>,
     analyser: Arc<Mutex<Analyser>>,
 }
 
 impl Process {
     /// Create a new Process
-    pub fn new(debugger_cmd: String, run_cmd: Vec<String>) -> Self {
+    pub fn new(
+        debugger_cmd: String,
+        run_cmd: Vec<String>,
+        breakpoints: Arc<Mutex<BreakpointRegistry>>,
+    ) -> Self {
         Process {
-            debugger_cmd: Some(debugger_cmd),
-            run_cmd: Some(run_cmd),
+            debugger_cmd,
+            run_cmd,
             process: None,
+            attached_pid: None,
             stdin_tx: None,
-            analyser: Arc::new(Mutex::new(Analyser::new())),
+            analyser: Arc::new(Mutex::new(Analyser::new(breakpoints))),
         }
     }
 
@@ -74,15 +113,40 @@ impl Process {
     /// - Sets up a thread to read stdin and forward it onto Python interpreter;
     /// - Checks that Python and the program to be ran both exist, otherwise panics.
     pub fn run(&mut self) {
-        let mut process = check_and_spawn_process(
+        self.attached_pid = None;
+
+        let process = check_and_spawn_process(
             vec![
-                self.debugger_cmd.take().unwrap(),
+                self.debugger_cmd.clone(),
                 "-m".to_string(),
                 "pdb".to_string(),
             ],
-            self.run_cmd.take().unwrap(),
+            self.run_cmd.clone(),
+        );
+
+        self.setup(process);
+    }
+
+    /// Attach to a Python program running the PADRE attach helper
+    ///
+    /// We run a small Python bridge that relays stdin and stdout to the helper's socket so that
+    /// pdb in the program can be treated the same as when we run the program ourselves.
+    pub fn attach(&mut self, pid: u64, socket_path: &str) {
+        self.attached_pid = Some(pid);
+
+        let process = check_and_spawn_process(
+            vec![
+                self.debugger_cmd.clone(),
+                "-c".to_string(),
+                attach_bridge(socket_path),
+            ],
+            vec![],
         );
 
+        self.setup(process);
+    }
+
+    fn setup(&mut self, mut process: Child) {
         self.setup_stdout(
             process
                 .stdout()
@@ -103,18 +167,54 @@ impl Process {
             true,
         );
 
-        self.analyser.lock().unwrap().set_pid(process.id() as u64);
+        let pid = match self.attached_pid {
+            Some(pid) => pid,
+            None => process.id() as u64,
+        };
+        self.analyser.lock().unwrap().set_pid(pid);
+        self.analyser.lock().unwrap().set_stdin_tx(stdin_tx.clone());
 
         self.stdin_tx = Some(stdin_tx);
         self.process = Some(process);
     }
 
+    /// Kill pdb along with the program so that it can be run again, returns the PID killed if
+    /// there was one
+    pub fn kill(&mut self) -> Option<u64> {
+        let pid = match self.process.take() {
+            Some(mut process) => {
+                let pid = process.id() as u64;
+                match process.kill() {
+                    Ok(_) => {}
+                    Err(e) => eprintln!("Error killing Python: {}", e),
+                };
+                Some(pid)
+            }
+            None => None,
+        };
+
+        let pid = match self.attached_pid.take() {
+            Some(attached_pid) => {
+                signal_process(attached_pid, "KILL");
+                Some(attached_pid)
+            }
+            None => pid,
+        };
+
+        self.analyser.lock().unwrap().reset();
+
+        pid
+    }
+
     pub fn add_listener(&self, kind: Listener, sender: Sender<Event>) {
         self.analyser.lock().unwrap().add_listener(kind, sender);
     }
 
     pub fn get_pid(&self) -> u64 {
-        self.process.as_ref().unwrap().id() as u64
+        match self.attached_pid {
+            Some(pid) => pid,
+            None => self.process.as_ref().unwrap().id() as u64,
+        }
     }
 
     pub fn get_status(&self) -> PDBStatus {
@@ -125,6 +225,15 @@ impl Process {
         self.analyser.lock().unwrap().status = status;
     }
 
+    /// Send a message to write to stdin the next time we stop
+    pub fn write_stdin_on_stop(&self, bytes: Bytes) {
+        self.analyser.lock().unwrap().stdin_on_stop = Some(bytes);
+    }
+
+    pub fn set_exception_mode(&self, mode: ExceptionBreakpointMode) {
+        self.analyser.lock().unwrap().exception_mode = mode;
+    }
+
     /// Send a message to write to stdin
     pub fn write_stdin(&mut self, bytes: Bytes) {
         let tx = self.stdin_tx.clone();
@@ -153,10 +262,12 @@ impl Process {
 
     /// Perform setup of reading Python stderr, analysing it and writing it back to stdout.
     fn setup_stderr(&mut self, stderr: ChildStderr) {
+        let analyser = self.analyser.clone();
         tokio::spawn(
             read_output(BufReader::new(stderr))
                 .for_each(move |text| {
                     eprint!("{}", text);
+                    analyser.lock().unwrap().analyse_stderr(&text);
                     Ok(())
                 })
                 .map_err(|e| eprintln!("Err reading Python stderr: {}", e)),
@@ -164,22 +275,72 @@ impl Process {
     }
 }
 
+/// Python code relaying stdin and stdout to the socket of the PADRE attach helper
+fn attach_bridge(socket_path: &str) -> String {
+    format!(
+        r#"import os, socket, sys, threading
+s = socket.socket(socket.AF_UNIX, socket.SOCK_STREAM)
+s.connect("{}")
+def relay_stdin():
+    for line in sys.stdin.buffer:
+        s.sendall(line)
+    s.shutdown(socket.SHUT_WR)
+threading.Thread(target=relay_stdin, daemon=True).start()
+while True:
+    data = s.recv(4096)
+    if not data:
+        break
+    os.write(1, data)
+"#,
+        socket_path
+    )
+}
+
 #[derive(Debug)]
 pub struct Analyser {
     status: PDBStatus,
     pid: Option<u64>,
     listeners: HashMap<Listener, Sender<Event>>,
+    breakpoints: Arc<Mutex<BreakpointRegistry>>,
+    stdin_tx: Option<Sender<Bytes>>,
+    stdin_on_stop: Option<Bytes>,
+    // (File, line number, function, is current frame) of each frame printed by `where`,
+    // outermost first
+    backtrace: Vec<(String, u64, String, bool)>,
+    exception_mode: ExceptionBreakpointMode,
+    in_traceback: bool,
+    // The exception message and where we stopped post-mortem, we report the exception once we
+    // have both as they come from stderr and stdout respectively.
+    exception_message: Option<String>,
+    post_mortem: bool,
+    post_mortem_location: Option<FileLocation>,
+    // Output while printing variables or threads that isn't a full line yet
+    json_output: String,
 }
 
 impl Analyser {
-    pub fn new() -> Self {
+    pub fn new(breakpoints: Arc<Mutex<BreakpointRegistry>>) -> Self {
         Analyser {
             status: PDBStatus::None,
             pid: None,
             listeners: HashMap::new(),
+            breakpoints,
+            stdin_tx: None,
+            stdin_on_stop: None,
+            backtrace: vec![],
+            exception_mode: ExceptionBreakpointMode::Uncaught,
+            in_traceback: false,
+            exception_message: None,
+            post_mortem: false,
+            post_mortem_location: None,
+            json_output: "".to_string(),
         }
     }
 
+    pub fn set_stdin_tx(&mut self, stdin_tx: Sender<Bytes>) {
+        self.stdin_tx = Some(stdin_tx);
+    }
+
     pub fn get_status(&mut self) -> PDBStatus {
         self.status.clone()
     }
@@ -188,13 +349,24 @@ impl Analyser {
         lazy_static! {
             static ref RE_BREAKPOINT: Regex =
                 Regex::new("^Breakpoint (\\d*) at (.*):(\\d*)$").unwrap();
+            static ref RE_BREAKPOINT_DELETED: Regex =
+                Regex::new("^Deleted breakpoint (\\d*) at (.*):(\\d*)$").unwrap();
+            static ref RE_BREAKPOINT_NOT_FOUND: Regex =
+                Regex::new("^\\*\\*\\* (There is no breakpoint at|There are no breakpoints in) ")
+                    .unwrap();
+            static ref RE_LOGPOINT: Regex =
+                Regex::new("PADRE logpoint (.*):(\\d+): (.*)$").unwrap();
             static ref RE_JUMP_TO_POSITION: Regex =
-                Regex::new("^> (.*)\\((\\d*)\\)[<>\\w]*\\(\\)$").unwrap();
+                Regex::new("^> (.*)\\((\\d*)\\)[<>\\w]*\\(\\)(->.*)?$").unwrap();
             static ref RE_PROCESS_EXITED: Regex =
                 Regex::new("^The program finished and will be restarted$").unwrap();
             static ref RE_PROCESS_EXITED_WITH_CODE: Regex =
                 Regex::new("^The program exited via sys.exit\\(\\)\\. Exit status: (-?\\d*)$")
                     .unwrap();
+            static ref RE_POST_MORTEM: Regex =
+                Regex::new("^Uncaught exception\\. Entering post mortem debugging$").unwrap();
+            static ref RE_POST_MORTEM_FINISHED: Regex =
+                Regex::new("^Post mortem debugger finished\\. ").unwrap();
         }
 
         for line in s.split("\n") {
@@ -208,15 +380,55 @@ impl Analyser {
             }
 
             for cap in RE_BREAKPOINT.captures_iter(line) {
+                let id = cap[1].to_string();
+                let file = cap[2].to_string();
+                let line = cap[3].parse::<u64>().unwrap();
+                self.found_breakpoint(id, file, line);
+            }
+
+            for cap in RE_BREAKPOINT_DELETED.captures_iter(line) {
                 let file = cap[2].to_string();
                 let line = cap[3].parse::<u64>().unwrap();
-                self.found_breakpoint(file, line);
+                self.removed_breakpoint(file, line);
+            }
+
+            for _ in RE_BREAKPOINT_NOT_FOUND.captures_iter(line) {
+                self.breakpoint_not_found();
+            }
+
+            for cap in RE_LOGPOINT.captures_iter(line) {
+                let file = cap[1].to_string();
+                let line = cap[2].parse::<u64>().unwrap();
+                logpoint_hit(&file, line, &cap[3]);
+            }
+
+            for _ in RE_POST_MORTEM.captures_iter(line) {
+                self.entered_post_mortem();
+            }
+
+            // `where` marks the current frame in the same way as when we stop
+            match self.status {
+                PDBStatus::Backtrace | PDBStatus::SelectingFrame(_) => continue,
+                _ => {}
             }
 
             for cap in RE_JUMP_TO_POSITION.captures_iter(line) {
                 let file = cap[1].to_string();
                 let line = cap[2].parse::<u64>().unwrap();
+                if self.post_mortem {
+                    self.post_mortem = false;
+                    self.post_mortem_location = Some(FileLocation::new(file.clone(), line));
+                    self.report_exception();
+                }
                 jump_to_position(&file, line);
+                match self.stdin_on_stop.take() {
+                    Some(bytes) => self.write_stdin(bytes),
+                    None => {}
+                }
+            }
+
+            for _ in RE_POST_MORTEM_FINISHED.captures_iter(line) {
+                signal_exited(self.pid.unwrap(), 1);
             }
 
             for _ in RE_PROCESS_EXITED.captures_iter(line) {
@@ -230,13 +442,228 @@ impl Analyser {
         }
 
         match self.status.clone() {
-            PDBStatus::Printing(var) => {
-                self.print_variable(var, s);
-            }
+            PDBStatus::Variables => match self.analyse_json(s) {
+                Some(result) => self.printed_variables(result),
+                None => {}
+            },
+            PDBStatus::Threads => match self.analyse_json(s) {
+                Some(result) => self.printed_threads(result),
+                None => {}
+            },
+            PDBStatus::Backtrace => match self.analyse_backtrace(s) {
+                Some((frames, _)) => self.printed_backtrace(frames),
+                None => {}
+            },
+            PDBStatus::SelectingFrame(frame) => match self.analyse_backtrace(s) {
+                Some((frames, current)) => self.select_frame(frame, frames, current),
+                None => {}
+            },
             _ => {}
         }
     }
 
+    /// Analyse the output of `where`, we have the full backtrace when we get the prompt back
+    ///
+    /// Returns the frames, innermost first, along with the index of the current frame.
+    fn analyse_backtrace(&mut self, s: &str) -> Option<(Vec<StackFrame>, u64)> {
+        lazy_static! {
+            static ref RE_FRAME: Regex =
+                Regex::new("^([> ]) (.*)\\((\\d+)\\)(.*)\\(\\)(->.*)?$").unwrap();
+        }
+
+        for line in s.split("\n") {
+            let line = line.trim_start_matches("(Pdb) ");
+            for cap in RE_FRAME.captures_iter(line) {
+                let file = cap[2].to_string();
+                let line = cap[3].parse::<u64>().unwrap();
+                let function = cap[4].to_string();
+                self.backtrace.push((file, line, function, &cap[1] == ">"));
+            }
+        }
+
+        if !s.ends_with("(Pdb) ") || self.backtrace.is_empty() {
+            return None;
+        }
+
+        self.status = PDBStatus::Running;
+
+        let mut frames: Vec<(String, u64, String, bool)> = self.backtrace.drain(..).collect();
+
+        // Remove the frames of pdb itself running the program
+        match frames.iter().position(|frame| frame.0 == "<string>") {
+            Some(i) => {
+                frames.drain(..=i);
+            }
+            None => {}
+        }
+
+        let current = frames.iter().rev().position(|frame| frame.3).unwrap_or(0) as u64;
+
+        let frames = frames
+            .into_iter()
+            .rev()
+            .enumerate()
+            .map(|(index, (file, line, function, _))| {
+                let module = Path::new(&file)
+                    .file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string());
+                StackFrame::new(index as u64, function, Some(file), Some(line), module)
+            })
+            .collect();
+
+        Some((frames, current))
+    }
+
+    /// Analyse the variables or threads printed as JSON by the statement we send, an error
+    /// running the statement is printed by pdb instead
+    fn analyse_json(&mut self, s: &str) -> Option<Result<Vec<serde_json::Value>, String>> {
+        lazy_static! {
+            static ref RE_JSON: Regex =
+                Regex::new("^(\\(Pdb\\) )?PADRE (variables|threads) (\\[.*\\])$").unwrap();
+            static ref RE_JSON_ERROR: Regex = Regex::new("^(\\(Pdb\\) )?\\*\\*\\* (.*)$").unwrap();
+        }
+
+        // The output can come in pieces, only look at full lines
+        self.json_output.push_str(s);
+        let output: String = match self.json_output.rfind('\n') {
+            Some(i) => self.json_output.drain(..=i).collect(),
+            None => return None,
+        };
+
+        for line in output.split("\n") {
+            let result = match (RE_JSON.captures(line), RE_JSON_ERROR.captures(line)) {
+                (Some(cap), _) => match serde_json::from_str(&cap[3]) {
+                    Ok(values) => Ok(values),
+                    Err(e) => {
+                        eprintln!("Can't read {} {}: {}", &cap[2], &cap[3], e);
+                        Ok(vec![])
+                    }
+                },
+                (None, Some(cap)) => Err(cap[2].to_string()),
+                (None, None) => continue,
+            };
+
+            self.status = PDBStatus::Running;
+            self.json_output.clear();
+
+            return Some(result);
+        }
+
+        None
+    }
+
+    fn printed_variables(&mut self, result: Result<Vec<serde_json::Value>, String>) {
+        let event = match result {
+            Ok(variables) => Event::Variables(
+                variables
+                    .iter()
+                    .map(|variable| {
+                        let details = VariableDetails::new(
+                            variable["name"].as_str().unwrap_or("").to_string(),
+                            variable["type"].as_str().unwrap_or("").to_string(),
+                            variable["value"].as_str().unwrap_or("").to_string(),
+                            0,
+                        );
+                        let expression = match variable["expandable"].as_bool() {
+                            Some(true) => variable["expr"].as_str().map(|e| e.to_string()),
+                            _ => None,
+                        };
+                        (details, expression)
+                    })
+                    .collect(),
+            ),
+            Err(msg) => Event::VariablesFailed(msg),
+        };
+
+        match self.listeners.remove(&Listener::Variables) {
+            Some(listener) => {
+                listener.send(event).wait().unwrap();
+            }
+            None => {}
+        }
+    }
+
+    fn printed_threads(&mut self, result: Result<Vec<serde_json::Value>, String>) {
+        let event = match result {
+            Ok(threads) => Event::Threads(
+                threads
+                    .iter()
+                    .map(|thread| {
+                        ThreadDetails::new(
+                            thread["id"].as_u64().unwrap_or(0),
+                            thread["name"].as_str().unwrap_or("").to_string(),
+                            thread["current"].as_bool().unwrap_or(false),
+                            thread["file"].as_str().map(|file| file.to_string()),
+                            thread["line"].as_u64(),
+                        )
+                    })
+                    .collect(),
+            ),
+            Err(msg) => Event::ThreadsFailed(msg),
+        };
+
+        match self.listeners.remove(&Listener::Threads) {
+            Some(listener) => {
+                listener.send(event).wait().unwrap();
+            }
+            None => {}
+        }
+    }
+
+    fn printed_backtrace(&mut self, frames: Vec<StackFrame>) {
+        match self.listeners.remove(&Listener::Backtrace) {
+            Some(listener) => {
+                listener.send(Event::Backtrace(frames)).wait().unwrap();
+            }
+            None => {}
+        }
+    }
+
+    /// Move up or down the stack to the frame now we know which frame is current, pdb reports
+    /// where we've moved to as it would a stop.
+    fn select_frame(&mut self, frame: FrameSelection, frames: Vec<StackFrame>, current: u64) {
+        let event = match frame.target(current) {
+            Some(index) if (index as usize) < frames.len() => {
+                if index > current {
+                    self.write_stdin(Bytes::from(format!("up {}\n", index - current)));
+                } else if index < current {
+                    self.write_stdin(Bytes::from(format!("down {}\n", current - index)));
+                } else {
+                    let frame = &frames[index as usize];
+                    jump_to_position(frame.file.as_ref().unwrap(), frame.line.unwrap());
+                }
+                Event::FrameSelected(index)
+            }
+            _ => Event::FrameNotFound,
+        };
+
+        match self.listeners.remove(&Listener::SelectFrame) {
+            Some(listener) => {
+                listener.send(event).wait().unwrap();
+            }
+            None => {}
+        }
+    }
+
+    /// Analyse stderr for the traceback of an uncaught exception
+    pub fn analyse_stderr(&mut self, s: &str) {
+        lazy_static! {
+            static ref RE_TRACEBACK: Regex =
+                Regex::new("^Traceback \\(most recent call last\\):$").unwrap();
+            static ref RE_EXCEPTION: Regex = Regex::new("^[\\w.]+(: .*)?$").unwrap();
+        }
+
+        for line in s.split("\n") {
+            if RE_TRACEBACK.is_match(line) {
+                self.in_traceback = true;
+            } else if self.in_traceback && RE_EXCEPTION.is_match(line) {
+                self.in_traceback = false;
+                self.exception_message = Some(line.to_string());
+                self.report_exception();
+            }
+        }
+    }
+
     pub fn add_listener(&mut self, kind: Listener, sender: Sender<Event>) {
         self.listeners.insert(kind, sender);
     }
@@ -245,6 +672,21 @@ impl Analyser {
         self.pid = Some(pid);
     }
 
+    /// Forget everything about the last program run, the exception mode is kept
+    pub fn reset(&mut self) {
+        self.status = PDBStatus::None;
+        self.pid = None;
+        self.listeners.clear();
+        self.stdin_tx = None;
+        self.stdin_on_stop = None;
+        self.backtrace.clear();
+        self.in_traceback = false;
+        self.exception_message = None;
+        self.post_mortem = false;
+        self.post_mortem_location = None;
+        self.json_output.clear();
+    }
+
     fn python_launched(&mut self) {
         self.status = PDBStatus::Running;
         match self.listeners.remove(&Listener::Launch) {
@@ -255,13 +697,76 @@ impl Analyser {
         }
     }
 
-    fn found_breakpoint(&mut self, file: String, line: u64) {
+    fn entered_post_mortem(&mut self) {
+        match self.exception_mode {
+            ExceptionBreakpointMode::None => {
+                self.exception_message = None;
+                self.write_stdin(Bytes::from("continue\n"));
+            }
+            _ => self.post_mortem = true,
+        }
+    }
+
+    fn report_exception(&mut self) {
+        match (&self.post_mortem_location, &self.exception_message) {
+            (Some(fl), Some(msg)) => {
+                exception_thrown(&fl.name, fl.line_num, msg);
+                self.post_mortem_location = None;
+                self.exception_message = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn found_breakpoint(&mut self, id: String, file: String, line: u64) {
         breakpoint_set(&file, line);
+
+        // Breakpoints on functions are set again at the location they were found at when the
+        // program restarts, which may also have a breakpoint of its own
+        let (location, function) = {
+            let breakpoints = self.breakpoints.lock().unwrap();
+            let location = breakpoints.find_location(&file, line);
+            let function = match &location {
+                Some(location)
+                    if breakpoints.get(location).unwrap().status()
+                        == &BreakpointStatus::Pending =>
+                {
+                    None
+                }
+                _ => breakpoints.find_pending_function(&file, line),
+            };
+            (location, function)
+        };
+
+        if let Some(function) = function {
+            self.breakpoints.lock().unwrap().add_function(
+                function,
+                Some(FileLocation::new(file.clone(), line)),
+                id.clone(),
+                BreakpointStatus::Resolved,
+            );
+        } else if let Some(location) = location {
+            let mut breakpoints = self.breakpoints.lock().unwrap();
+
+            // pdb can only ignore a number of crossings once it has given us the breakpoint
+            // number, the condition was given when the breakpoint was set.
+            let hit_count = breakpoints.get(&location).unwrap().condition().hit_count;
+            match hit_count {
+                Some(n) if n > 1 => {
+                    self.write_stdin(Bytes::from(format!("ignore {} {}\n", id, n - 1)))
+                }
+                _ => {}
+            }
+
+            breakpoints.set_native_id(&location, id.clone());
+            breakpoints.set_status(&location, BreakpointStatus::Resolved);
+        }
+
         let file_location = FileLocation::new(file, line);
         match self.listeners.remove(&Listener::Breakpoint) {
             Some(listener) => {
                 listener
-                    .send(Event::BreakpointSet(file_location))
+                    .send(Event::BreakpointSet(id, file_location))
                     .wait()
                     .unwrap();
             }
@@ -269,16 +774,45 @@ impl Analyser {
         }
     }
 
-    fn print_variable(&mut self, variable: Variable, data: &str) {
-        let to = data.len() - 2;
-        match self.listeners.remove(&Listener::PrintVariable) {
+    fn write_stdin(&self, bytes: Bytes) {
+        match &self.stdin_tx {
+            Some(tx) => {
+                tokio::spawn(
+                    tx.clone()
+                        .send(bytes)
+                        .map(move |_| {})
+                        .map_err(|e| eprintln!("Error sending to Python: {}", e)),
+                );
+            }
+            None => {}
+        }
+    }
+
+    fn removed_breakpoint(&mut self, file: String, line: u64) {
+        breakpoint_removed(&file, line);
+
+        let location = self.breakpoints.lock().unwrap().find_location(&file, line);
+        if let Some(location) = location {
+            self.breakpoints.lock().unwrap().remove(&location);
+        }
+        let file_location = FileLocation::new(file, line);
+        match self.listeners.remove(&Listener::BreakpointRemoved) {
             Some(listener) => {
                 listener
-                    .send(Event::PrintVariable(variable, data[0..to].to_string()))
+                    .send(Event::BreakpointRemoved(file_location))
                     .wait()
                     .unwrap();
             }
             None => {}
         }
     }
+
+    fn breakpoint_not_found(&mut self) {
+        match self.listeners.remove(&Listener::BreakpointRemoved) {
+            Some(listener) => {
+                listener.send(Event::BreakpointNotFound).wait().unwrap();
+            }
+            None => {}
+        }
+    }
 }
@@ -11,12 +11,34 @@
 //!            - node
 //!            - python
 //!   -d/--debugger
+//!   --attach       PID of a running process to attach to rather than running a program
+//!   --attach-name  Name of a running process to attach to rather than running a program
+//!   --core         Core file to debug with the program rather than running it, LLDB only
+//!   --remote       Debug a program running elsewhere, the `host:port` of an `lldb-server` or
+//!                  `gdbserver` with LLDB or the inspector's address or websocket URL with Node
+//!   --path-map     Map the remote path of source files to the local path, `REMOTE=LOCAL`, can
+//!                  be given more than once
 //!
 //! The debug command should be specified as an addendum when running the command, e.g.
 //! ```
 //! padre -t=lldb -d=lldb -- my_program arg1 arg2 3 4
 //! ```
 //! will run the program `my_program arg1 arg2 3 4` in an `lldb` session.
+//!
+//! When attaching the debug command isn't needed but the debugger type or debugger is, e.g.
+//! ```
+//! padre -t=lldb --attach=1234
+//! ```
+//!
+//! When debugging a core file the program it came from is the debug command, e.g.
+//! ```
+//! padre -t=lldb --core=core.1234 -- my_program
+//! ```
+//!
+//! When debugging remotely with Node the debug command isn't needed, e.g.
+//! ```
+//! padre -t=node --remote=192.168.0.2:9229 --path-map=/app=/home/me/app
+//! ```
 
 #[macro_use]
 extern crate lazy_static;
@@ -69,6 +91,31 @@ fn get_app_args<'a>() -> ArgMatches<'a> {
                  .long("type")
                  .takes_value(true)
                  .help("specify debugger type from [lldb, node, java, python]"))
+        .arg(Arg::with_name("attach")
+                 .long("attach")
+                 .takes_value(true)
+                 .conflicts_with("attach-name")
+                 .help("specify the PID of a running process to attach to"))
+        .arg(Arg::with_name("attach-name")
+                 .long("attach-name")
+                 .takes_value(true)
+                 .help("specify the name of a running process to attach to"))
+        .arg(Arg::with_name("core")
+                 .long("core")
+                 .takes_value(true)
+                 .conflicts_with_all(&["attach", "attach-name"])
+                 .help("specify a core file to debug with the program"))
+        .arg(Arg::with_name("remote")
+                 .long("remote")
+                 .takes_value(true)
+                 .conflicts_with_all(&["attach", "attach-name", "core"])
+                 .help("specify the host:port of a debug server or the inspector to connect to"))
+        .arg(Arg::with_name("path-map")
+                 .long("path-map")
+                 .takes_value(true)
+                 .multiple(true)
+                 .number_of_values(1)
+                 .help("specify a REMOTE=LOCAL mapping of the paths of source files"))
         .arg(Arg::with_name("debug_cmd")
                  .multiple(true)
                  .takes_value(true))
@@ -94,6 +141,40 @@ fn get_connection(args: &ArgMatches) -> SocketAddr {
     return format!("{}:{}", host, port).parse::<SocketAddr>().unwrap();
 }
 
+fn get_attach_target(args: &ArgMatches) -> Option<debugger::AttachTarget> {
+    match args.value_of("attach") {
+        Some(s) => match s.parse::<u64>() {
+            Ok(pid) => return Some(debugger::AttachTarget::Pid(pid)),
+            Err(_) => {
+                panic!("Can't understand PID to attach to");
+            }
+        },
+        None => {}
+    };
+
+    match args.value_of("attach-name") {
+        Some(s) => Some(debugger::AttachTarget::Name(s.to_string())),
+        None => None,
+    }
+}
+
+fn get_source_map(args: &ArgMatches) -> debugger::SourceMap {
+    let mappings = match args.values_of("path-map") {
+        Some(values) => values
+            .map(|value| match value.find('=') {
+                Some(i) => (value[..i].to_string(), value[i + 1..].to_string()),
+                None => panic!(
+                    "Can't understand path mapping {}, expected REMOTE=LOCAL",
+                    value
+                ),
+            })
+            .collect(),
+        None => vec![],
+    };
+
+    debugger::SourceMap::new(mappings)
+}
+
 fn exit_padre(debugger: Arc<Mutex<debugger::Debugger>>) {
     let when = Instant::now() + Duration::new(5, 0);
 
@@ -120,16 +201,26 @@ impl Future for Runner {
     fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
         let args = get_app_args();
 
-        let debug_cmd: Vec<String> = args
-            .values_of("debug_cmd")
-            .expect("Can't find program to debug, please rerun with correct parameters")
-            .map(|x| x.to_string())
-            .collect::<Vec<String>>();
+        let attach = get_attach_target(&args);
+
+        let debug_cmd: Vec<String> = match args.values_of("debug_cmd") {
+            Some(values) => values.map(|x| x.to_string()).collect::<Vec<String>>(),
+            None => match (&attach, args.value_of("remote")) {
+                (Some(_), _) | (_, Some(_)) => vec![],
+                (None, None) => {
+                    panic!("Can't find program to debug, please rerun with correct parameters")
+                }
+            },
+        };
 
         let debugger = Arc::new(Mutex::new(debugger::get_debugger(
             args.value_of("debugger"),
             args.value_of("type"),
             debug_cmd,
+            attach,
+            args.value_of("core").map(|core| core.to_string()),
+            args.value_of("remote").map(|remote| remote.to_string()),
+            get_source_map(&args),
         )));
 
         let connection_addr = get_connection(&args);
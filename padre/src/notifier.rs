@@ -6,6 +6,7 @@
 use std::net::SocketAddr;
 use std::sync::Mutex;
 
+use crate::debugger::SourceMap;
 use crate::server::{Notification, PadreSend};
 
 use tokio::prelude::*;
@@ -40,9 +41,12 @@ struct Listener {
 ///
 /// We store a vector of `Listener`s and when one is finished with we drop it
 /// from the list.
+///
+/// Files in notifications are given with their local paths according to the source map.
 #[derive(Debug)]
 struct Notifier {
     listeners: Vec<Listener>,
+    source_map: SourceMap,
 }
 
 impl Notifier {
@@ -50,6 +54,7 @@ impl Notifier {
     fn new() -> Notifier {
         Notifier {
             listeners: Vec::new(),
+            source_map: SourceMap::default(),
         }
     }
 
@@ -95,6 +100,16 @@ pub fn remove_listener(addr: &SocketAddr) {
     NOTIFIER.lock().unwrap().remove_listener(addr);
 }
 
+/// Set the source map for giving the local paths of files in notifications
+pub fn set_source_map(source_map: SourceMap) {
+    NOTIFIER.lock().unwrap().source_map = source_map;
+}
+
+/// Get the local path of a file the debugger reported
+fn local_path(file: &str) -> String {
+    NOTIFIER.lock().unwrap().source_map.to_local(file)
+}
+
 /// Notify that a process has exited
 pub fn signal_exited(pid: u64, exit_code: i64) {
     let msg = Notification::new(
@@ -115,6 +130,7 @@ pub fn log_msg(level: LogLevel, msg: &str) {
 
 /// Notify about a code position change
 pub fn jump_to_position(file: &str, line: u64) {
+    let file = local_path(file);
     let msg = Notification::new(
         "padre#debugger#JumpToPosition".to_string(),
         vec![serde_json::json!(file), serde_json::json!(line)],
@@ -122,8 +138,36 @@ pub fn jump_to_position(file: &str, line: u64) {
     NOTIFIER.lock().unwrap().send_msg(msg);
 }
 
+/// Notify about the code position a thread has stopped at, for debuggers that can stop in more
+/// than one thread
+pub fn jump_to_thread_position(file: &str, line: u64, thread: u64) {
+    let file = local_path(file);
+    let msg = Notification::new(
+        "padre#debugger#JumpToPosition".to_string(),
+        vec![
+            serde_json::json!(file),
+            serde_json::json!(line),
+            serde_json::json!(thread),
+        ],
+    );
+    NOTIFIER.lock().unwrap().send_msg(msg);
+}
+
+/// Notify that we've stopped at an address with no source, the thread is given when we know
+/// which thread stopped
+pub fn jump_to_address(address: &str, thread: Option<u64>) {
+    let mut args = vec![serde_json::json!(address)];
+    match thread {
+        Some(thread) => args.push(serde_json::json!(thread)),
+        None => {}
+    }
+    let msg = Notification::new("padre#debugger#JumpToAddress".to_string(), args);
+    NOTIFIER.lock().unwrap().send_msg(msg);
+}
+
 /// Notify that a breakpoint has been set
 pub fn breakpoint_set(file: &str, line: u64) {
+    let file = local_path(file);
     let msg = Notification::new(
         "padre#debugger#BreakpointSet".to_string(),
         vec![serde_json::json!(file), serde_json::json!(line)],
@@ -131,6 +175,59 @@ pub fn breakpoint_set(file: &str, line: u64) {
     NOTIFIER.lock().unwrap().send_msg(msg);
 }
 
+/// Notify that a breakpoint has been removed
+pub fn breakpoint_removed(file: &str, line: u64) {
+    let file = local_path(file);
+    let msg = Notification::new(
+        "padre#debugger#BreakpointRemoved".to_string(),
+        vec![serde_json::json!(file), serde_json::json!(line)],
+    );
+    NOTIFIER.lock().unwrap().send_msg(msg);
+}
+
+/// Send the message from a logpoint being hit
+pub fn logpoint_hit(file: &str, line: u64, msg: &str) {
+    let file = local_path(file);
+    let msg = Notification::new(
+        "padre#debugger#Logpoint".to_string(),
+        vec![
+            serde_json::json!(file),
+            serde_json::json!(line),
+            serde_json::json!(msg),
+        ],
+    );
+    NOTIFIER.lock().unwrap().send_msg(msg);
+}
+
+/// Notify that an exception has been thrown, or a panic has occurred
+pub fn exception_thrown(file: &str, line: u64, msg: &str) {
+    let file = local_path(file);
+    let msg = Notification::new(
+        "padre#debugger#ExceptionThrown".to_string(),
+        vec![
+            serde_json::json!(file),
+            serde_json::json!(line),
+            serde_json::json!(msg),
+        ],
+    );
+    NOTIFIER.lock().unwrap().send_msg(msg);
+}
+
+/// Notify that a watchpoint has been hit
+///
+/// The old value is only known when the watched memory was written to.
+pub fn watchpoint_hit(id: u64, old_value: Option<&str>, new_value: &str) {
+    let msg = Notification::new(
+        "padre#debugger#WatchpointHit".to_string(),
+        vec![
+            serde_json::json!(id),
+            serde_json::json!(old_value),
+            serde_json::json!(new_value),
+        ],
+    );
+    NOTIFIER.lock().unwrap().send_msg(msg);
+}
+
 #[cfg(test)]
 mod tests {
     use std::net::{IpAddr, Ipv4Addr, SocketAddr};
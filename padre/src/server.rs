@@ -8,7 +8,7 @@ use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use crate::config::Config;
-use crate::debugger::{Debugger, DebuggerCmd};
+use crate::debugger::{Debugger, DebuggerCmd, SourceMap};
 use crate::notifier::{add_listener, log_msg, remove_listener, LogLevel};
 use crate::vimcodec::VimCodec;
 
@@ -26,6 +26,8 @@ pub enum PadreCmd {
     Pings,
     GetConfig(String),
     SetConfig(String, i64),
+    GetSourceMap,
+    SetSourceMap(SourceMap),
 }
 
 /// Contains command details of a request, either a `PadreCmd` or a `DebuggerCmd`
@@ -225,6 +227,8 @@ fn respond(
                 PadreCmd::Pings => pings(),
                 PadreCmd::GetConfig(key) => get_config(config, key),
                 PadreCmd::SetConfig(key, value) => set_config(config, key, *value),
+                PadreCmd::GetSourceMap => get_source_map(debugger),
+                PadreCmd::SetSourceMap(source_map) => set_source_map(debugger, source_map),
             };
 
             Box::new(future::lazy(move || match json_response {
@@ -285,3 +289,18 @@ fn set_config(
         false => Ok(serde_json::json!({"status":"ERROR"})),
     }
 }
+
+fn get_source_map(debugger: Arc<Mutex<Debugger>>) -> Result<serde_json::Value, io::Error> {
+    let source_map = debugger.lock().unwrap().source_map().to_json();
+    Ok(serde_json::json!({"status":"OK","value":source_map}))
+}
+
+/// Set the source map, unlike other config it's the same for every connection as it's
+/// about the files the debugger reports rather than how this connection behaves
+fn set_source_map(
+    debugger: Arc<Mutex<Debugger>>,
+    source_map: &SourceMap,
+) -> Result<serde_json::Value, io::Error> {
+    debugger.lock().unwrap().set_source_map(source_map.clone());
+    Ok(serde_json::json!({"status":"OK"}))
+}
@@ -3,6 +3,7 @@
 //! Various simple utilities for use in PADRE
 
 use std::env;
+use std::fs;
 use std::io::{self, BufRead};
 use std::mem;
 use std::net::TcpListener;
@@ -36,6 +37,9 @@ pub fn send_error_and_debug(err_msg: &str, debug_msg: &str) {
 /// Check whether the specified debugger and program to debug exist, including change them to
 /// be the full path name if required. If it still can't find both it will panic, otherwise it
 /// will start a Child process for running the program.
+///
+/// When attaching to a running process there's no program to run so `run_cmd` is empty and only
+/// the debugger is checked and spawned.
 pub fn check_and_spawn_process(mut debugger_cmd: Vec<String>, run_cmd: Vec<String>) -> Child {
     let mut not_found = None;
 
@@ -45,8 +49,9 @@ pub fn check_and_spawn_process(mut debugger_cmd: Vec<String>, run_cmd: Vec<Strin
     }
 
     // Now check the debugger and program to debug exist, if not error
-    if !file_exists(&run_cmd[0]) {
-        not_found = Some(&run_cmd[0]);
+    match run_cmd.get(0) {
+        Some(cmd) if !file_exists(cmd) => not_found = Some(cmd),
+        _ => {}
     };
 
     if !file_exists(&debugger_cmd[0]) {
@@ -67,7 +72,9 @@ pub fn check_and_spawn_process(mut debugger_cmd: Vec<String>, run_cmd: Vec<Strin
         args.push(&arg[..]);
     }
 
-    args.push("--");
+    if !run_cmd.is_empty() {
+        args.push("--");
+    }
 
     for arg in &run_cmd {
         args.push(&arg[..]);
@@ -189,6 +196,97 @@ pub fn file_exists(path: &str) -> bool {
     }
 }
 
+/// Send SIGINT to a process with the UNIX `kill` command, returns whether it succeeded.
+pub fn interrupt_process(pid: u64) -> bool {
+    signal_process(pid, "INT")
+}
+
+/// Send a signal, e.g. `USR1`, to a process with the UNIX `kill` command, returns whether it
+/// succeeded.
+pub fn signal_process(pid: u64, signal: &str) -> bool {
+    match Command::new("kill")
+        .arg(format!("-{}", signal))
+        .arg(pid.to_string())
+        .status()
+    {
+        Ok(status) => status.success(),
+        Err(_) => false,
+    }
+}
+
+/// Find the lowest PID of a running process by name, the name of its executable or for
+/// interpreters the name of the script they're running.
+///
+/// We warn when there's more than one process with the name as we may well pick the wrong one.
+pub fn find_process_by_name(name: &str) -> Option<u64> {
+    let own_pid = std::process::id() as u64;
+
+    let mut pids: Vec<u64> = fs::read_dir("/proc")
+        .ok()?
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<u64>().ok())
+        .filter(|pid| *pid != own_pid)
+        .filter(|pid| {
+            let cmdline = match fs::read(format!("/proc/{}/cmdline", pid)) {
+                Ok(cmdline) => cmdline,
+                Err(_) => return false,
+            };
+
+            let args: Vec<String> = cmdline
+                .split(|c| *c == 0)
+                .filter(|arg| !arg.is_empty())
+                .map(|arg| String::from_utf8_lossy(arg).to_string())
+                .collect();
+
+            is_process_named(&args, name)
+        })
+        .collect();
+
+    pids.sort();
+
+    if pids.len() > 1 {
+        log_msg(
+            LogLevel::WARN,
+            &format!(
+                "Found {} processes named {}, using the first, PID {}",
+                pids.len(),
+                name,
+                pids[0]
+            ),
+        );
+    }
+
+    pids.first().cloned()
+}
+
+/// Whether a process with the command line arguments is named `name`, matching the executable in
+/// the first argument or the script when the executable is a Python or Node interpreter
+fn is_process_named(args: &[String], name: &str) -> bool {
+    let file_name = |arg: &str| -> Option<String> {
+        Path::new(arg)
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().to_string())
+    };
+
+    let executable = match args.first().and_then(|arg| file_name(arg)) {
+        Some(executable) => executable,
+        None => return false,
+    };
+
+    if executable == name {
+        return true;
+    }
+
+    let is_interpreter = executable.starts_with("python") || executable.starts_with("node");
+
+    match is_interpreter {
+        true => match args[1..].iter().find(|arg| !arg.starts_with('-')) {
+            Some(script) => file_name(script).as_deref() == Some(name),
+            None => false,
+        },
+        false => false,
+    }
+}
+
 /// Get the file type as output by the UNIX `file` command.
 fn get_file_type(cmd: &str) -> String {
     let output = Command::new("file")
@@ -259,8 +357,10 @@ where
 #[cfg(test)]
 mod tests {
     use std::env;
+    use std::fs;
     use std::net::TcpListener;
     use std::path::Path;
+    use std::process::Command;
     use std::thread;
     use std::time::Duration;
 
@@ -305,6 +405,43 @@ mod tests {
         assert_eq!(true, super::file_is_text("./test_files/test_node.js"));
     }
 
+    #[test]
+    fn test_find_process_by_name() {
+        // Run sleep under a name nothing else on the machine has
+        let name = format!("padre_test_sleep_{}", std::process::id());
+        let link = env::temp_dir().join(&name);
+        std::os::unix::fs::symlink("/bin/sleep", &link).unwrap();
+
+        let mut child = Command::new(&link).arg("10").spawn().unwrap();
+
+        // Give it a moment to exec before its command line can be read
+        thread::sleep(Duration::from_millis(100));
+
+        let pid = super::find_process_by_name(&name);
+
+        child.kill().unwrap();
+        fs::remove_file(&link).unwrap();
+
+        assert_eq!(Some(child.id() as u64), pid);
+        assert_eq!(
+            None,
+            super::find_process_by_name("process_surely_doesnt_exist")
+        );
+    }
+
+    #[test]
+    fn test_is_process_named() {
+        let args = |args: &[&str]| -> Vec<String> { args.iter().map(|a| a.to_string()).collect() };
+
+        assert!(super::is_process_named(&args(&["/usr/bin/node"]), "node"));
+        assert!(super::is_process_named(
+            &args(&["python3", "-u", "/src/test_prog.py"]),
+            "test_prog.py"
+        ));
+        assert!(!super::is_process_named(&args(&["vim", "/x/node"]), "node"));
+        assert!(!super::is_process_named(&args(&[]), "node"));
+    }
+
     #[test]
     fn test_file_exists() {
         assert_eq!(true, super::file_exists("./test_files/node"));
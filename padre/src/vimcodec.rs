@@ -5,7 +5,11 @@
 use std::collections::HashMap;
 use std::io;
 
-use crate::debugger::{DebuggerCmd, DebuggerCmdV1, FileLocation, Variable};
+use crate::debugger::{
+    AttachTarget, BreakpointCondition, DebuggerCmd, DebuggerCmdV1, ExceptionBreakpointMode,
+    FileLocation, FrameSelection, FunctionName, RegisterGroup, SourceMap, Variable, WatchMode,
+    WatchTarget,
+};
 use crate::server::{PadreCmd, PadreRequest, PadreSend, RequestCmd};
 use crate::util;
 
@@ -85,6 +89,110 @@ impl VimCodec {
         None
     }
 
+    /// Get and remove the optional `condition` and `hitCount` of a breakpoint from the arguments
+    ///
+    /// Returns None if either is badly specified.
+    fn get_breakpoint_condition(
+        &self,
+        args: &mut HashMap<String, serde_json::Value>,
+    ) -> Option<BreakpointCondition> {
+        let condition = match args.remove("condition") {
+            Some(s) => match s {
+                serde_json::Value::String(s) => Some(s),
+                _ => {
+                    util::send_error_and_debug(
+                        "Badly specified 'condition'",
+                        &format!("Badly specified 'condition': {}", s),
+                    );
+                    return None;
+                }
+            },
+            None => None,
+        };
+
+        let hit_count = match args.remove("hitCount") {
+            Some(h) => match h.as_u64() {
+                Some(n) if n > 0 => Some(n),
+                _ => {
+                    util::send_error_and_debug(
+                        "Badly specified 'hitCount'",
+                        &format!("Badly specified 'hitCount': {}", h),
+                    );
+                    return None;
+                }
+            },
+            None => None,
+        };
+
+        Some(BreakpointCondition::new(condition, hit_count))
+    }
+
+    /// Get and remove a `function` with its optional `module` and `regex` flag from the arguments
+    fn get_function_name(
+        &self,
+        args: &mut HashMap<String, serde_json::Value>,
+    ) -> Option<FunctionName> {
+        let name = self.get_string("function", args)?;
+
+        let module = match args.remove("module") {
+            Some(s) => match s {
+                serde_json::Value::String(s) => Some(s),
+                _ => {
+                    util::send_error_and_debug(
+                        "Badly specified 'module'",
+                        &format!("Badly specified 'module': {}", s),
+                    );
+                    return None;
+                }
+            },
+            None => None,
+        };
+
+        let is_regex = match args.remove("regex") {
+            Some(b) => match b {
+                serde_json::Value::Bool(b) => b,
+                _ => {
+                    util::send_error_and_debug(
+                        "Badly specified 'regex'",
+                        &format!("Badly specified 'regex': {}", b),
+                    );
+                    return None;
+                }
+            },
+            None => false,
+        };
+
+        Some(FunctionName::new(name, module, is_regex))
+    }
+
+    /// Get and remove either a `variable` or an `expression` to watch and the optional `mode`
+    /// from the arguments, the mode defaults to watching for writes
+    fn get_watch(
+        &self,
+        args: &mut HashMap<String, serde_json::Value>,
+    ) -> Option<(WatchTarget, WatchMode)> {
+        let target = match args.contains_key("expression") {
+            true => WatchTarget::Expression(self.get_string("expression", args)?),
+            false => WatchTarget::Variable(self.get_variable(args)?),
+        };
+
+        let mode = match args.remove("mode") {
+            Some(m) => match m.as_str().and_then(WatchMode::from_str) {
+                Some(m) => m,
+                None => {
+                    util::send_error_and_debug(
+                        "Badly specified 'mode'",
+                        &format!("Badly specified 'mode': {}", m),
+                    );
+                    return None;
+                }
+            },
+            None => WatchMode::Write,
+        };
+
+        Some((target, mode))
+    }
+
     /// Get and remove a `variable` from the arguments passed
     fn get_variable(&self, args: &mut HashMap<String, serde_json::Value>) -> Option<Variable> {
         match args.remove("variable") {
@@ -135,6 +243,42 @@ impl VimCodec {
         }
     }
 
+    /// Get and remove a source map `value` from the arguments, it should be a list of pairs of
+    /// paths, the debugger's path first and then the local path
+    fn get_source_map(&self, args: &mut HashMap<String, serde_json::Value>) -> Option<SourceMap> {
+        let value = match args.remove("value") {
+            Some(v) => v,
+            None => {
+                util::send_error_and_debug("Can't understand request", "Need to specify a 'value'");
+                return None;
+            }
+        };
+
+        let mappings: Option<Vec<(String, String)>> = value.as_array().and_then(|pairs| {
+            pairs
+                .iter()
+                .map(|pair| match pair.as_array() {
+                    Some(p) if p.len() == 2 => match (p[0].as_str(), p[1].as_str()) {
+                        (Some(d), Some(l)) => Some((d.to_string(), l.to_string())),
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .collect()
+        });
+
+        match mappings {
+            Some(m) => Some(SourceMap::new(m)),
+            None => {
+                util::send_error_and_debug(
+                    "Badly specified source map 'value'",
+                    &format!("Badly specified source map 'value': {}", value),
+                );
+                None
+            }
+        }
+    }
+
     /// Get and remove the key specified from the arguments as an i64
     fn get_i64(&self, key: &str, args: &mut HashMap<String, serde_json::Value>) -> Option<i64> {
         match args.remove(key) {
@@ -306,28 +450,362 @@ impl Decoder for VimCodec {
                 id,
                 RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::Run)),
             ))),
+            "restart" => Ok(Some(PadreRequest::new(
+                id,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::Restart)),
+            ))),
+            "kill" => Ok(Some(PadreRequest::new(
+                id,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::Kill)),
+            ))),
+            "detach" => Ok(Some(PadreRequest::new(
+                id,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::Detach)),
+            ))),
             "stepOver" => Ok(Some(PadreRequest::new(
                 id,
                 RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::StepOver)),
             ))),
+            "stepOut" => Ok(Some(PadreRequest::new(
+                id,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::StepOut)),
+            ))),
             "stepIn" => Ok(Some(PadreRequest::new(
                 id,
                 RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::StepIn)),
             ))),
+            "stepInstruction" => Ok(Some(PadreRequest::new(
+                id,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::StepInstruction)),
+            ))),
+            "nextInstruction" => Ok(Some(PadreRequest::new(
+                id,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::NextInstruction)),
+            ))),
+            "runToLine" => {
+                let file_location = self.get_file_location(&mut args);
+                let ignore_breakpoints = match args.remove("ignoreBreakpoints") {
+                    Some(b) => match b {
+                        serde_json::Value::Bool(b) => b,
+                        _ => {
+                            util::send_error_and_debug(
+                                "Badly specified 'ignoreBreakpoints'",
+                                &format!("Badly specified 'ignoreBreakpoints': {}", b),
+                            );
+                            return Ok(None);
+                        }
+                    },
+                    None => false,
+                };
+                match file_location {
+                    Some(fl) => Ok(Some(PadreRequest::new(
+                        id,
+                        RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::RunToLine(
+                            fl,
+                            ignore_breakpoints,
+                        ))),
+                    ))),
+                    None => return Ok(None),
+                }
+            }
             "continue" => Ok(Some(PadreRequest::new(
                 id,
                 RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::Continue)),
             ))),
+            "pause" => Ok(Some(PadreRequest::new(
+                id,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::Pause)),
+            ))),
+            "backtrace" => Ok(Some(PadreRequest::new(
+                id,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::Backtrace)),
+            ))),
+            "disassemble" => {
+                let address = match args.contains_key("address") {
+                    true => match self.get_string("address", &mut args) {
+                        Some(a) if is_address(&a) => Some(a),
+                        Some(a) => {
+                            util::send_error_and_debug(
+                                "Badly specified 'address'",
+                                &format!("Badly specified 'address': {}", a),
+                            );
+                            return Ok(None);
+                        }
+                        None => return Ok(None),
+                    },
+                    false => None,
+                };
+                let count = match args.contains_key("count") {
+                    true => match self.get_i64("count", &mut args) {
+                        Some(c) if c > 0 => Some(c as u64),
+                        Some(c) => {
+                            util::send_error_and_debug(
+                                "Badly specified 'count'",
+                                &format!("Badly specified 'count': {}", c),
+                            );
+                            return Ok(None);
+                        }
+                        None => return Ok(None),
+                    },
+                    false => None,
+                };
+                Ok(Some(PadreRequest::new(
+                    id,
+                    RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::Disassemble(
+                        address, count,
+                    ))),
+                )))
+            }
+            "registers" => {
+                let group = match args.remove("group") {
+                    Some(g) => match g.as_str().and_then(RegisterGroup::from_str) {
+                        Some(g) => Some(g),
+                        None => {
+                            util::send_error_and_debug(
+                                "Badly specified 'group'",
+                                &format!("Badly specified 'group': {}", g),
+                            );
+                            return Ok(None);
+                        }
+                    },
+                    None => None,
+                };
+                Ok(Some(PadreRequest::new(
+                    id,
+                    RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::Registers(group))),
+                )))
+            }
+            "setRegister" => {
+                let name = match self.get_string("name", &mut args) {
+                    Some(n) => n,
+                    None => return Ok(None),
+                };
+                let value = match self.get_string("value", &mut args) {
+                    Some(v) => v,
+                    None => return Ok(None),
+                };
+                Ok(Some(PadreRequest::new(
+                    id,
+                    RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::SetRegister(
+                        name, value,
+                    ))),
+                )))
+            }
+            "selectFrame" => match self.get_i64("index", &mut args) {
+                Some(index) if index >= 0 => Ok(Some(PadreRequest::new(
+                    id,
+                    RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::SelectFrame(
+                        FrameSelection::Index(index as u64),
+                    ))),
+                ))),
+                Some(index) => {
+                    util::send_error_and_debug(
+                        "Badly specified 'index'",
+                        &format!("Badly specified 'index': {}", index),
+                    );
+                    return Ok(None);
+                }
+                None => return Ok(None),
+            },
+            "frameUp" => Ok(Some(PadreRequest::new(
+                id,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::SelectFrame(
+                    FrameSelection::Up,
+                ))),
+            ))),
+            "frameDown" => Ok(Some(PadreRequest::new(
+                id,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::SelectFrame(
+                    FrameSelection::Down,
+                ))),
+            ))),
+            "threads" => Ok(Some(PadreRequest::new(
+                id,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::Threads)),
+            ))),
+            "selectThread" => match self.get_i64("id", &mut args) {
+                Some(thread_id) if thread_id >= 0 => Ok(Some(PadreRequest::new(
+                    id,
+                    RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::SelectThread(
+                        thread_id as u64,
+                    ))),
+                ))),
+                Some(thread_id) => {
+                    util::send_error_and_debug(
+                        "Badly specified 'id'",
+                        &format!("Badly specified 'id': {}", thread_id),
+                    );
+                    return Ok(None);
+                }
+                None => return Ok(None),
+            },
+            "locals" => Ok(Some(PadreRequest::new(
+                id,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::Locals)),
+            ))),
+            "expandVariable" => match self.get_i64("variablesReference", &mut args) {
+                Some(r) if r > 0 => Ok(Some(PadreRequest::new(
+                    id,
+                    RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::ExpandVariable(
+                        r as u64,
+                    ))),
+                ))),
+                Some(r) => {
+                    util::send_error_and_debug(
+                        "Badly specified 'variablesReference'",
+                        &format!("Badly specified 'variablesReference': {}", r),
+                    );
+                    return Ok(None);
+                }
+                None => return Ok(None),
+            },
+            "attach" => {
+                let target = match args.contains_key("pid") {
+                    true => match self.get_i64("pid", &mut args) {
+                        Some(p) if p > 0 => AttachTarget::Pid(p as u64),
+                        Some(p) => {
+                            util::send_error_and_debug(
+                                "Badly specified 'pid'",
+                                &format!("Badly specified 'pid': {}", p),
+                            );
+                            return Ok(None);
+                        }
+                        None => return Ok(None),
+                    },
+                    false => match self.get_string("name", &mut args) {
+                        Some(name) => AttachTarget::Name(name),
+                        None => return Ok(None),
+                    },
+                };
+                Ok(Some(PadreRequest::new(
+                    id,
+                    RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::Attach(target))),
+                )))
+            }
+            "evaluate" => {
+                let expression = match self.get_string("expression", &mut args) {
+                    Some(e) => e,
+                    None => return Ok(None),
+                };
+                let frame = match args.contains_key("frame") {
+                    true => match self.get_i64("frame", &mut args) {
+                        Some(f) if f >= 0 => Some(f as u64),
+                        Some(f) => {
+                            util::send_error_and_debug(
+                                "Badly specified 'frame'",
+                                &format!("Badly specified 'frame': {}", f),
+                            );
+                            return Ok(None);
+                        }
+                        None => return Ok(None),
+                    },
+                    false => None,
+                };
+                Ok(Some(PadreRequest::new(
+                    id,
+                    RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::Evaluate(
+                        expression, frame,
+                    ))),
+                )))
+            }
+            "setVariable" => {
+                let variable = self.get_variable(&mut args);
+                let value = self.get_string("value", &mut args);
+                match (variable, value) {
+                    (Some(variable), Some(value)) => Ok(Some(PadreRequest::new(
+                        id,
+                        RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::SetVariable(
+                            variable, value,
+                        ))),
+                    ))),
+                    _ => return Ok(None),
+                }
+            }
             "breakpoint" => {
                 let file_location = self.get_file_location(&mut args);
-                match file_location {
-                    Some(fl) => Ok(Some(PadreRequest::new(
+                let condition = self.get_breakpoint_condition(&mut args);
+                match (file_location, condition) {
+                    (Some(fl), Some(bc)) => Ok(Some(PadreRequest::new(
                         id,
-                        RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::Breakpoint(fl))),
+                        RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::Breakpoint(fl, bc))),
                     ))),
+                    _ => return Ok(None),
+                }
+            }
+            "logpoint" => {
+                let file_location = self.get_file_location(&mut args);
+                match file_location {
+                    Some(fl) => match self.get_string("message", &mut args) {
+                        Some(msg) => Ok(Some(PadreRequest::new(
+                            id,
+                            RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::Logpoint(
+                                fl, msg,
+                            ))),
+                        ))),
+                        None => return Ok(None),
+                    },
                     None => return Ok(None),
                 }
             }
+            "breakpointFunction" => match self.get_function_name(&mut args) {
+                Some(f) => Ok(Some(PadreRequest::new(
+                    id,
+                    RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::BreakpointFunction(f))),
+                ))),
+                None => return Ok(None),
+            },
+            "removeBreakpoint" => match args.contains_key("function") {
+                true => match self.get_function_name(&mut args) {
+                    Some(f) => Ok(Some(PadreRequest::new(
+                        id,
+                        RequestCmd::DebuggerCmd(DebuggerCmd::V1(
+                            DebuggerCmdV1::RemoveBreakpointFunction(f),
+                        )),
+                    ))),
+                    None => return Ok(None),
+                },
+                false => {
+                    let file_location = self.get_file_location(&mut args);
+                    match file_location {
+                        Some(fl) => Ok(Some(PadreRequest::new(
+                            id,
+                            RequestCmd::DebuggerCmd(DebuggerCmd::V1(
+                                DebuggerCmdV1::RemoveBreakpoint(fl),
+                            )),
+                        ))),
+                        None => return Ok(None),
+                    }
+                }
+            },
+            "listBreakpoints" => Ok(Some(PadreRequest::new(
+                id,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::ListBreakpoints)),
+            ))),
+            "setExceptionBreakpoints" => match self.get_string("mode", &mut args) {
+                Some(mode) => match ExceptionBreakpointMode::from_str(&mode) {
+                    Some(mode) => Ok(Some(PadreRequest::new(
+                        id,
+                        RequestCmd::DebuggerCmd(DebuggerCmd::V1(
+                            DebuggerCmdV1::SetExceptionBreakpoints(mode),
+                        )),
+                    ))),
+                    None => {
+                        util::send_error_and_debug(
+                            "Badly specified 'mode'",
+                            &format!("Badly specified 'mode': {}", mode),
+                        );
+                        return Ok(None);
+                    }
+                },
+                None => return Ok(None),
+            },
+            "watch" => match self.get_watch(&mut args) {
+                Some((target, mode)) => Ok(Some(PadreRequest::new(
+                    id,
+                    RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::Watch(target, mode))),
+                ))),
+                None => return Ok(None),
+            },
             "print" => {
                 let variable = self.get_variable(&mut args);
                 match variable {
@@ -341,16 +819,29 @@ impl Decoder for VimCodec {
             "getConfig" => {
                 let key = self.get_string("key", &mut args);
                 match key {
-                    Some(k) => Ok(Some(PadreRequest::new(
-                        id,
-                        RequestCmd::PadreCmd(PadreCmd::GetConfig(k)),
-                    ))),
+                    Some(k) => match k.as_str() {
+                        "sourceMap" => Ok(Some(PadreRequest::new(
+                            id,
+                            RequestCmd::PadreCmd(PadreCmd::GetSourceMap),
+                        ))),
+                        _ => Ok(Some(PadreRequest::new(
+                            id,
+                            RequestCmd::PadreCmd(PadreCmd::GetConfig(k)),
+                        ))),
+                    },
                     None => return Ok(None),
                 }
             }
             "setConfig" => {
                 let key = self.get_string("key", &mut args);
                 match key {
+                    Some(ref k) if k == "sourceMap" => match self.get_source_map(&mut args) {
+                        Some(source_map) => Ok(Some(PadreRequest::new(
+                            id,
+                            RequestCmd::PadreCmd(PadreCmd::SetSourceMap(source_map)),
+                        ))),
+                        None => return Ok(None),
+                    },
                     Some(k) => {
                         let value = self.get_i64("value", &mut args);
                         match value {
@@ -417,9 +908,21 @@ impl Encoder for VimCodec {
     }
 }
 
+/// Whether the string is an address as a decimal or `0x` prefixed hex number
+fn is_address(s: &str) -> bool {
+    match s.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).is_ok(),
+        None => s.parse::<u64>().is_ok(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::debugger::{DebuggerCmd, DebuggerCmdV1};
+    use crate::debugger::{
+        AttachTarget, BreakpointCondition, DebuggerCmd, DebuggerCmdV1, ExceptionBreakpointMode,
+        FileLocation, FrameSelection, FunctionName, RegisterGroup, SourceMap, Variable, WatchMode,
+        WatchTarget,
+    };
     use crate::server::{Notification, PadreCmd, PadreRequest, PadreSend, RequestCmd, Response};
 
     use bytes::{BufMut, BytesMut};
@@ -497,6 +1000,450 @@ mod tests {
         );
     }
 
+    #[test]
+    fn check_logpoint_json_decoding() {
+        let mut codec = super::VimCodec::new();
+        let mut buf = BytesMut::new();
+        buf.reserve(72);
+        buf.put(r#"[123,{"cmd":"logpoint","file":"test.c","line":12,"message":"i is {i}"}]"#);
+
+        let padre_request = codec.decode(&mut buf).unwrap().unwrap();
+
+        assert_eq!(
+            PadreRequest::new(
+                123,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::Logpoint(
+                    FileLocation::new("test.c".to_string(), 12),
+                    "i is {i}".to_string()
+                )))
+            ),
+            padre_request
+        );
+    }
+
+    #[test]
+    fn check_breakpoint_function_json_decoding() {
+        let mut codec = super::VimCodec::new();
+        let mut buf = BytesMut::new();
+        buf.reserve(78);
+        buf.put(
+            r#"[123,{"cmd":"breakpointFunction","function":"a","module":"test","regex":true}]"#,
+        );
+
+        let padre_request = codec.decode(&mut buf).unwrap().unwrap();
+
+        assert_eq!(
+            PadreRequest::new(
+                123,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::BreakpointFunction(
+                    FunctionName::new("a".to_string(), Some("test".to_string()), true)
+                )))
+            ),
+            padre_request
+        );
+    }
+
+    #[test]
+    fn check_set_exception_breakpoints_json_decoding() {
+        let mut codec = super::VimCodec::new();
+        let mut buf = BytesMut::new();
+        buf.reserve(58);
+        buf.put(r#"[123,{"cmd":"setExceptionBreakpoints","mode":"uncaught"}]"#);
+
+        let padre_request = codec.decode(&mut buf).unwrap().unwrap();
+
+        assert_eq!(
+            PadreRequest::new(
+                123,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::SetExceptionBreakpoints(
+                    ExceptionBreakpointMode::Uncaught
+                )))
+            ),
+            padre_request
+        );
+    }
+
+    #[test]
+    fn check_watch_json_decoding() {
+        let mut codec = super::VimCodec::new();
+        let mut buf = BytesMut::new();
+        buf.reserve(52);
+        buf.put(r#"[123,{"cmd":"watch","variable":"abc","mode":"read"}]"#);
+
+        let padre_request = codec.decode(&mut buf).unwrap().unwrap();
+
+        assert_eq!(
+            PadreRequest::new(
+                123,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::Watch(
+                    WatchTarget::Variable(Variable::new("abc".to_string())),
+                    WatchMode::Read
+                )))
+            ),
+            padre_request
+        );
+
+        buf.reserve(45);
+        buf.put(r#"[124,{"cmd":"watch","expression":"&s->a[1]"}]"#);
+
+        let padre_request = codec.decode(&mut buf).unwrap().unwrap();
+
+        assert_eq!(
+            PadreRequest::new(
+                124,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::Watch(
+                    WatchTarget::Expression("&s->a[1]".to_string()),
+                    WatchMode::Write
+                )))
+            ),
+            padre_request
+        );
+    }
+
+    #[test]
+    fn check_run_to_line_json_decoding() {
+        let mut codec = super::VimCodec::new();
+        let mut buf = BytesMut::new();
+        buf.reserve(76);
+        buf.put(r#"[123,{"cmd":"runToLine","file":"test.c","line":12,"ignoreBreakpoints":true}]"#);
+
+        let padre_request = codec.decode(&mut buf).unwrap().unwrap();
+
+        assert_eq!(
+            PadreRequest::new(
+                123,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::RunToLine(
+                    FileLocation::new("test.c".to_string(), 12),
+                    true
+                )))
+            ),
+            padre_request
+        );
+    }
+
+    #[test]
+    fn check_select_frame_json_decoding() {
+        let mut codec = super::VimCodec::new();
+        let mut buf = BytesMut::new();
+        buf.reserve(37);
+        buf.put(r#"[123,{"cmd":"selectFrame","index":2}]"#);
+
+        let padre_request = codec.decode(&mut buf).unwrap().unwrap();
+
+        assert_eq!(
+            PadreRequest::new(
+                123,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::SelectFrame(
+                    FrameSelection::Index(2)
+                )))
+            ),
+            padre_request
+        );
+    }
+
+    #[test]
+    fn check_select_thread_json_decoding() {
+        let mut codec = super::VimCodec::new();
+        let mut buf = BytesMut::new();
+        buf.reserve(35);
+        buf.put(r#"[123,{"cmd":"selectThread","id":2}]"#);
+
+        let padre_request = codec.decode(&mut buf).unwrap().unwrap();
+
+        assert_eq!(
+            PadreRequest::new(
+                123,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::SelectThread(2)))
+            ),
+            padre_request
+        );
+    }
+
+    #[test]
+    fn check_expand_variable_json_decoding() {
+        let mut codec = super::VimCodec::new();
+        let mut buf = BytesMut::new();
+        buf.reserve(53);
+        buf.put(r#"[123,{"cmd":"expandVariable","variablesReference":4}]"#);
+
+        let padre_request = codec.decode(&mut buf).unwrap().unwrap();
+
+        assert_eq!(
+            PadreRequest::new(
+                123,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::ExpandVariable(4)))
+            ),
+            padre_request
+        );
+    }
+
+    #[test]
+    fn check_attach_json_decoding() {
+        let mut codec = super::VimCodec::new();
+        let mut buf = BytesMut::new();
+        buf.reserve(36);
+        buf.put(r#"[123,{"cmd":"attach","pid":4321}]"#);
+
+        let padre_request = codec.decode(&mut buf).unwrap().unwrap();
+
+        assert_eq!(
+            PadreRequest::new(
+                123,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::Attach(AttachTarget::Pid(
+                    4321
+                ))))
+            ),
+            padre_request
+        );
+
+        let mut buf = BytesMut::new();
+        buf.reserve(40);
+        buf.put(r#"[124,{"cmd":"attach","name":"server"}]"#);
+
+        let padre_request = codec.decode(&mut buf).unwrap().unwrap();
+
+        assert_eq!(
+            PadreRequest::new(
+                124,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::Attach(
+                    AttachTarget::Name("server".to_string())
+                )))
+            ),
+            padre_request
+        );
+    }
+
+    #[test]
+    fn check_evaluate_json_decoding() {
+        let mut codec = super::VimCodec::new();
+        let mut buf = BytesMut::new();
+        buf.reserve(55);
+        buf.put(r#"[123,{"cmd":"evaluate","expression":"a + b","frame":1}]"#);
+
+        let padre_request = codec.decode(&mut buf).unwrap().unwrap();
+
+        assert_eq!(
+            PadreRequest::new(
+                123,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::Evaluate(
+                    "a + b".to_string(),
+                    Some(1)
+                )))
+            ),
+            padre_request
+        );
+    }
+
+    #[test]
+    fn check_set_variable_json_decoding() {
+        let mut codec = super::VimCodec::new();
+        let mut buf = BytesMut::new();
+        buf.reserve(60);
+        buf.put(r#"[123,{"cmd":"setVariable","variable":"a.b[1]","value":"42"}]"#);
+
+        let padre_request = codec.decode(&mut buf).unwrap().unwrap();
+
+        assert_eq!(
+            PadreRequest::new(
+                123,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::SetVariable(
+                    Variable::new("a.b[1]".to_string()),
+                    "42".to_string()
+                )))
+            ),
+            padre_request
+        );
+    }
+
+    #[test]
+    fn check_remove_breakpoint_json_decoding() {
+        let mut codec = super::VimCodec::new();
+        let mut buf = BytesMut::new();
+        buf.reserve(58);
+        buf.put(r#"[123,{"cmd":"removeBreakpoint","file":"test.c","line":12}]"#);
+
+        let padre_request = codec.decode(&mut buf).unwrap().unwrap();
+
+        assert_eq!(
+            PadreRequest::new(
+                123,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::RemoveBreakpoint(
+                    FileLocation::new("test.c".to_string(), 12)
+                )))
+            ),
+            padre_request
+        );
+
+        let mut buf = BytesMut::new();
+        buf.reserve(50);
+        buf.put(r#"[124,{"cmd":"removeBreakpoint","function":"main"}]"#);
+
+        let padre_request = codec.decode(&mut buf).unwrap().unwrap();
+
+        assert_eq!(
+            PadreRequest::new(
+                124,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::RemoveBreakpointFunction(
+                    FunctionName::new("main".to_string(), None, false)
+                )))
+            ),
+            padre_request
+        );
+    }
+
+    #[test]
+    fn check_conditional_breakpoint_json_decoding() {
+        let mut codec = super::VimCodec::new();
+        let mut buf = BytesMut::new();
+        buf.reserve(88);
+        buf.put(
+            r#"[123,{"cmd":"breakpoint","file":"test.c","line":12,"condition":"i > 3","hitCount":5}]"#,
+        );
+
+        let padre_request = codec.decode(&mut buf).unwrap().unwrap();
+
+        assert_eq!(
+            PadreRequest::new(
+                123,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::Breakpoint(
+                    FileLocation::new("test.c".to_string(), 12),
+                    BreakpointCondition::new(Some("i > 3".to_string()), Some(5))
+                )))
+            ),
+            padre_request
+        );
+    }
+
+    #[test]
+    fn check_list_breakpoints_json_decoding() {
+        let mut codec = super::VimCodec::new();
+        let mut buf = BytesMut::new();
+        buf.reserve(32);
+        buf.put(r#"[123,{"cmd":"listBreakpoints"}]"#);
+
+        let padre_request = codec.decode(&mut buf).unwrap().unwrap();
+
+        assert_eq!(
+            PadreRequest::new(
+                123,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::ListBreakpoints))
+            ),
+            padre_request
+        );
+    }
+
+    #[test]
+    fn check_disassemble_json_decoding() {
+        let mut codec = super::VimCodec::new();
+        let mut buf = BytesMut::new();
+        buf.reserve(27);
+        buf.put(r#"[123,{"cmd":"disassemble"}]"#);
+
+        let padre_request = codec.decode(&mut buf).unwrap().unwrap();
+
+        assert_eq!(
+            PadreRequest::new(
+                123,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::Disassemble(None, None)))
+            ),
+            padre_request
+        );
+
+        let mut buf = BytesMut::new();
+        buf.reserve(56);
+        buf.put(r#"[124,{"cmd":"disassemble","address":"0x1f60","count":8}]"#);
+
+        let padre_request = codec.decode(&mut buf).unwrap().unwrap();
+
+        assert_eq!(
+            PadreRequest::new(
+                124,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::Disassemble(
+                    Some("0x1f60".to_string()),
+                    Some(8)
+                )))
+            ),
+            padre_request
+        );
+    }
+
+    #[test]
+    fn check_is_address() {
+        assert!(super::is_address("0x1f60"));
+        assert!(super::is_address("8032"));
+        assert!(!super::is_address("0x1f60\nprocess kill"));
+        assert!(!super::is_address("$pc"));
+    }
+
+    #[test]
+    fn check_registers_json_decoding() {
+        let mut codec = super::VimCodec::new();
+        let mut buf = BytesMut::new();
+        buf.reserve(44);
+        buf.put(r#"[123,{"cmd":"registers","group":"general"}]"#);
+
+        let padre_request = codec.decode(&mut buf).unwrap().unwrap();
+
+        assert_eq!(
+            PadreRequest::new(
+                123,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::Registers(Some(
+                    RegisterGroup::General
+                ))))
+            ),
+            padre_request
+        );
+
+        let mut buf = BytesMut::new();
+        buf.reserve(57);
+        buf.put(r#"[124,{"cmd":"setRegister","name":"rax","value":"0x2a"}]"#);
+
+        let padre_request = codec.decode(&mut buf).unwrap().unwrap();
+
+        assert_eq!(
+            PadreRequest::new(
+                124,
+                RequestCmd::DebuggerCmd(DebuggerCmd::V1(DebuggerCmdV1::SetRegister(
+                    "rax".to_string(),
+                    "0x2a".to_string()
+                )))
+            ),
+            padre_request
+        );
+    }
+
+    #[test]
+    fn check_source_map_config_json_decoding() {
+        let mut codec = super::VimCodec::new();
+        let mut buf = BytesMut::new();
+        buf.reserve(83);
+        buf.put(r#"[123,{"cmd":"setConfig","key":"sourceMap","value":[["/build/src","/home/me/src"]]}]"#);
+
+        let padre_request = codec.decode(&mut buf).unwrap().unwrap();
+
+        assert_eq!(
+            PadreRequest::new(
+                123,
+                RequestCmd::PadreCmd(PadreCmd::SetSourceMap(SourceMap::new(vec![(
+                    "/build/src".to_string(),
+                    "/home/me/src".to_string()
+                )])))
+            ),
+            padre_request
+        );
+
+        let mut buf = BytesMut::new();
+        buf.reserve(43);
+        buf.put(r#"[124,{"cmd":"getConfig","key":"sourceMap"}]"#);
+
+        let padre_request = codec.decode(&mut buf).unwrap().unwrap();
+
+        assert_eq!(
+            PadreRequest::new(124, RequestCmd::PadreCmd(PadreCmd::GetSourceMap)),
+            padre_request
+        );
+    }
+
     #[test]
     fn check_json_encoding_response() {
         let mut codec = super::VimCodec::new();
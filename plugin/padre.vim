@@ -39,12 +39,34 @@ endif
 
 command -nargs=* -complete=file PadreDebug call padre#debugger#Debug(<f-args>)
 command -nargs=0 PadreRun call padre#debugger#Run()
+command -nargs=0 PadreRestart call padre#debugger#Restart()
+command -nargs=1 PadreAttach call padre#debugger#Attach(<f-args>)
+command -nargs=0 PadreKill call padre#debugger#Kill()
+command -nargs=0 PadreDetach call padre#debugger#Detach()
 command -nargs=0 PadreBreakpoint call padre#debugger#Breakpoint()
 command -nargs=0 PadreStepIn call padre#debugger#StepIn()
 command -nargs=0 PadreStepOver call padre#debugger#StepOver()
+command -nargs=0 PadreStepOut call padre#debugger#StepOut()
+command -nargs=0 PadreStepInstruction call padre#debugger#StepInstruction()
+command -nargs=0 PadreNextInstruction call padre#debugger#NextInstruction()
+command -nargs=0 -bang PadreRunToLine call padre#debugger#RunToLine('<bang>')
 command -nargs=0 PadreStop call padre#debugger#Stop()
 command -nargs=1 PadrePrintVariable call padre#debugger#PrintVariable(<f-args>)
 command -nargs=0 PadreContinue call padre#debugger#Continue()
+command -nargs=0 PadrePause call padre#debugger#Pause()
+command -nargs=0 PadreBacktrace call padre#debugger#Backtrace()
+command -nargs=? PadreDisassemble call padre#debugger#Disassemble(<f-args>)
+command -nargs=? PadreRegisters call padre#debugger#Registers(<f-args>)
+command -nargs=+ PadreSetRegister call padre#debugger#SetRegister(<f-args>)
+command -nargs=0 PadreThreads call padre#debugger#Threads()
+command -nargs=1 PadreSelectThread call padre#debugger#SelectThread(<f-args>)
+command -nargs=1 PadreSelectFrame call padre#debugger#SelectFrame(<f-args>)
+command -nargs=0 PadreFrameUp call padre#debugger#FrameUp()
+command -nargs=0 PadreFrameDown call padre#debugger#FrameDown()
+command -nargs=0 PadreLocals call padre#debugger#Locals()
+command -nargs=1 PadreExpandVariable call padre#debugger#ExpandVariable(<f-args>)
+command -nargs=1 PadreEvaluate call padre#debugger#Evaluate(<q-args>)
+command -nargs=+ PadreSetVariable call padre#debugger#SetVariable(<f-args>)
 command -nargs=0 PadreAddDataWindow call padre#debugger#AddDataWindow()
 command -nargs=0 PadreDataBufferFlick call padre#debugger#DataBufferFlick()
 